@@ -0,0 +1,191 @@
+//! Scheduled JSON snapshots written by the daemon's writer thread.
+//!
+//! Each snapshot holds the entries copied since the previous one, so the directory reads as an
+//! append-only log; the first snapshot in an empty directory holds everything.
+
+use crate::config::{expand_home, BackupConfig};
+use crate::db::Database;
+use crate::error::{CliError, Result};
+use chrono::{DateTime, Duration, Local, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const PREFIX: &str = "clippie-";
+/// UTC time of the snapshot in its file name, so names sort chronologically.
+const NAME_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+pub struct Backup {
+    dir: PathBuf,
+    at: NaiveTime,
+    keep: usize,
+    next_run: DateTime<Utc>,
+}
+
+impl Backup {
+    pub fn new(settings: &BackupConfig) -> Result<Self> {
+        let at = NaiveTime::parse_from_str(&settings.at, "%H:%M")
+            .map_err(|_| CliError::ConfigError(format!("backup.at must be HH:MM, got '{}'", settings.at)))?;
+        let mut backup = Backup {
+            dir: expand_home(&settings.dir)?,
+            at,
+            keep: settings.keep.max(1),
+            next_run: Utc::now(),
+        };
+        backup.next_run = backup.first_run(Local::now());
+        Ok(backup)
+    }
+
+    /// Catch up right away if the last scheduled time passed without a snapshot, e.g. while asleep.
+    fn first_run(&self, now: DateTime<Local>) -> DateTime<Utc> {
+        let missed = snapshots(&self.dir)
+            .last()
+            .is_none_or(|(taken, _)| *taken < scheduled_on_or_before(self.at, now));
+        if missed {
+            now.with_timezone(&Utc)
+        } else {
+            scheduled_after(self.at, now)
+        }
+    }
+
+    pub fn due(&self, now: DateTime<Utc>) -> bool {
+        now >= self.next_run
+    }
+
+    /// Write a snapshot and prune old ones. A failed run is retried at the next scheduled time.
+    pub fn run(&mut self, db: &Database, now: DateTime<Utc>) -> Result<PathBuf> {
+        self.next_run = scheduled_after(self.at, now.with_timezone(&Local));
+        let path = write_snapshot(db, &self.dir, now)?;
+        prune(&self.dir, self.keep)?;
+        Ok(path)
+    }
+}
+
+fn at_on(at: NaiveTime, day: chrono::NaiveDate) -> Option<DateTime<Utc>> {
+    Local.from_local_datetime(&day.and_time(at)).earliest().map(|t| t.with_timezone(&Utc))
+}
+
+fn scheduled_on_or_before(at: NaiveTime, now: DateTime<Local>) -> DateTime<Utc> {
+    let today = now.date_naive();
+    match at_on(at, today) {
+        Some(time) if time <= now => time,
+        _ => at_on(at, today - Duration::days(1)).unwrap_or(now.with_timezone(&Utc) - Duration::days(1)),
+    }
+}
+
+fn scheduled_after(at: NaiveTime, now: DateTime<Local>) -> DateTime<Utc> {
+    let today = now.date_naive();
+    match at_on(at, today) {
+        Some(time) if time > now => time,
+        _ => at_on(at, today + Duration::days(1)).unwrap_or(now.with_timezone(&Utc) + Duration::days(1)),
+    }
+}
+
+/// Existing snapshots, oldest first.
+fn snapshots(dir: &Path) -> Vec<(DateTime<Utc>, PathBuf)> {
+    let Ok(read) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut found: Vec<_> = read
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let stem = path.file_name()?.to_str()?.strip_prefix(PREFIX)?.strip_suffix(".json")?;
+            let taken = NaiveDateTime::parse_from_str(stem, NAME_FORMAT).ok()?.and_utc();
+            Some((taken, path))
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+/// Entries copied in the second of a snapshot go into the next one, so none fall between files.
+fn write_snapshot(db: &Database, dir: &Path, now: DateTime<Utc>) -> Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(dir, fs::Permissions::from_mode(0o700));
+    }
+
+    let previous = snapshots(dir).last().map(|(taken, _)| *taken);
+    let since = previous.map(|t| t.timestamp() - 1).unwrap_or(i64::MIN);
+    let until = now.timestamp() - 1;
+    let entries = db.get_entries_copied_between(since, until)?;
+
+    let snapshot = json!({
+        "since": previous.map(|t| t.to_rfc3339()),
+        "until": now.to_rfc3339(),
+        "entries": entries.iter().map(|e| e.to_json()).collect::<Vec<_>>(),
+    });
+
+    let path = dir.join(format!("{}{}.json", PREFIX, now.format(NAME_FORMAT)));
+    let partial = path.with_extension("json.partial");
+    fs::write(&partial, serde_json::to_string_pretty(&snapshot)?)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(&partial, fs::Permissions::from_mode(0o600));
+    }
+    fs::rename(&partial, &path)?;
+    Ok(path)
+}
+
+fn prune(dir: &Path, keep: usize) -> Result<()> {
+    let all = snapshots(dir);
+    for (_, path) in &all[..all.len().saturating_sub(keep)] {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::NewEntry;
+    use tempfile::{NamedTempFile, TempDir};
+
+    fn snapshot_contents(path: &Path) -> Vec<String> {
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        json["entries"].as_array().unwrap().iter().map(|e| e["content"].as_str().unwrap().to_string()).collect()
+    }
+
+    #[test]
+    fn test_snapshots_are_incremental_and_pruned() {
+        let tmp = NamedTempFile::new().unwrap();
+        let db = Database::open(tmp.path()).unwrap();
+        let dir = TempDir::new().unwrap();
+        let settings = BackupConfig { dir: dir.path().to_path_buf(), at: "02:00".to_string(), keep: 2 };
+        let mut backup = Backup::new(&settings).unwrap();
+        assert!(backup.due(Utc::now()));
+
+        let base = DateTime::from_timestamp(Utc::now().timestamp() + 60, 0).unwrap();
+        let copied_at = |content: &str, at: DateTime<Utc>| {
+            db.insert_many(&[NewEntry { last_copied: at, ..NewEntry::new(content) }]).unwrap();
+        };
+
+        copied_at("first", base - Duration::seconds(10));
+        copied_at("second", base);
+        let first = backup.run(&db, base).unwrap();
+        assert_eq!(snapshot_contents(&first), vec!["first"]);
+        assert!(!backup.due(base));
+
+        let second = backup.run(&db, base + Duration::seconds(5)).unwrap();
+        assert_eq!(snapshot_contents(&second), vec!["second"]);
+
+        let third = backup.run(&db, base + Duration::seconds(10)).unwrap();
+        assert!(snapshot_contents(&third).is_empty());
+        assert_eq!(snapshots(dir.path()).len(), 2);
+        assert!(!first.exists());
+    }
+
+    #[test]
+    fn test_schedule_times() {
+        let at = NaiveTime::from_hms_opt(2, 0, 0).unwrap();
+        let now = Local::now();
+        let before = scheduled_on_or_before(at, now);
+        let after = scheduled_after(at, now);
+        assert!(before <= now && after > now);
+        assert!(after - before <= Duration::hours(25));
+        assert!(Backup::new(&BackupConfig { dir: PathBuf::from("/tmp"), at: "2am".to_string(), keep: 1 }).is_err());
+    }
+}
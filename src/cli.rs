@@ -1,4 +1,5 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -9,12 +10,26 @@ use clap::{Parser, Subcommand};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
+
+    #[arg(long, global = true, help = "Disable colors (also honors the NO_COLOR environment variable)")]
+    pub no_color: bool,
+
+    #[arg(long, global = true, value_name = "NAME", help = "Use this profile's database instead of the active one")]
+    pub profile: Option<String>,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     #[command(about = "Launch the clipboard history browser")]
-    Tui,
+    Tui {
+        #[arg(long, help = "Paste the selected entry into the focused app after exiting")]
+        paste: bool,
+        #[arg(long = "type", conflicts_with = "paste", help = "Type the selected entry into the focused app key by key, for apps that block pasting")]
+        type_out: bool,
+    },
+
+    #[command(about = "Pick an entry and print it without a newline, for a zsh ZLE widget")]
+    Zle,
 
     #[command(about = "Configure database location")]
     Setup,
@@ -25,28 +40,304 @@ pub enum Commands {
     #[command(about = "Stop the clipboard monitoring daemon")]
     Stop,
 
+    #[command(about = "Restart the clipboard monitoring daemon, e.g. after changing the config")]
+    Restart,
+
     #[command(about = "Show daemon status")]
     Status,
 
+    #[command(about = "Check that the daemon is capturing (exit 0 ok, 1 stalled, 2 unreachable)")]
+    Health,
+
+    #[command(about = "Diagnose the config, database, service install and daemon, suggesting fixes")]
+    Doctor,
+
+    #[command(about = "Compact the database and truncate its WAL, reporting the space reclaimed")]
+    Vacuum,
+
+    #[command(about = "Show entry counts and sizes, daily growth and the most copied entries")]
+    Stats {
+        #[arg(long, help = "Print the figures as JSON")]
+        json: bool,
+    },
+
     #[command(about = "Clear clipboard history")]
     Clear {
         #[arg(long)]
         all: bool,
     },
 
-    #[command(about = "Install the launchd daemon")]
+    #[command(about = "Open the picker in a small terminal window and paste the selection")]
+    Popup {
+        #[arg(long = "type", help = "Type the selection key by key instead of pasting it")]
+        type_out: bool,
+    },
+
+    #[command(about = "Print clipboard history to stdout")]
+    List {
+        #[arg(long, help = "Maximum number of entries to print")]
+        limit: Option<usize>,
+        #[arg(long, value_enum, default_value_t = ListFormat::Plain)]
+        format: ListFormat,
+        #[arg(long, conflicts_with_all = ["format", "tsv"], help = "Shorthand for --format json")]
+        json: bool,
+        #[arg(long, conflicts_with = "format", help = "Shorthand for --format tsv")]
+        tsv: bool,
+        #[arg(long, help = "Only print entries fuzzy-matching this query")]
+        query: Option<String>,
+        #[arg(long = "lang", help = "Only print entries tagged with this language (rust, sql, shell, json, ...)")]
+        language: Option<String>,
+        #[arg(long, help = "Only print entries of this kind (url, email, path, json, code, color, phone, text)")]
+        kind: Option<String>,
+        #[arg(long, help = "Only print entries copied since a duration ago (30m, 2h, 7d) or a date (2024-05-01)")]
+        since: Option<String>,
+    },
+
+    #[command(about = "Export clipboard history to a file")]
+    Export {
+        #[arg(long, value_enum)]
+        format: ExportFormat,
+        #[arg(long)]
+        out: PathBuf,
+        #[arg(long, help = "Number of most recent entries to export (default 50 for html, all otherwise)")]
+        limit: Option<usize>,
+        #[arg(long, help = "Only entries copied since a duration ago (30m, 2h, 7d) or a date (2024-05-01)")]
+        since: Option<String>,
+        #[arg(long, help = "Only entries copied before a duration ago or a date")]
+        until: Option<String>,
+    },
+
+    #[command(about = "Merge entries from an export or another clipboard manager into the history")]
+    Import {
+        file: PathBuf,
+        #[arg(long, value_enum, default_value_t = ImportFormat::Auto)]
+        format: ImportFormat,
+    },
+
+    #[command(about = "Print an entry to stdout by recency (1 is the latest) or --id")]
+    Get {
+        #[arg(help = "Position in history, 1 for the most recent entry")]
+        target: usize,
+        #[arg(long, help = "Treat the argument as a database id")]
+        id: bool,
+    },
+
+    #[command(about = "Put an entry back on the clipboard by recency (1 is the latest) or --id")]
+    Copy {
+        #[arg(required_unless_present = "stdin", help = "Position in history, 1 for the most recent entry")]
+        target: Option<usize>,
+        #[arg(long, help = "Treat the argument as a database id")]
+        id: bool,
+        #[arg(long, conflicts_with_all = ["target", "id"], help = "Copy the entry a line read from stdin stands for, as printed by 'clippie pick'")]
+        stdin: bool,
+    },
+
+    #[command(about = "Copy an entry rewritten, e.g. --op snake or --op base64-decode; the stored entry is unchanged")]
+    Transform {
+        #[arg(help = "Position in history, 1 for the most recent entry")]
+        target: usize,
+        #[arg(long, help = "Treat the argument as a database id")]
+        id: bool,
+        #[arg(long, help = "upper, lower, camel, snake, trim, base64-encode, base64-decode, url-encode, url-decode, json-pretty, json-minify or clean-url")]
+        op: String,
+        #[arg(long, help = "Print the result instead of copying it")]
+        print: bool,
+    },
+
+    #[command(about = "Print one line per entry for fzf, rofi and launchers; --query copies the chosen line")]
+    Pick {
+        #[arg(long, help = "Start each line with the entry id and a tab, e.g. for fzf --with-nth 2..")]
+        with_index: bool,
+        #[arg(short, long, help = "Print at most this many entries")]
+        limit: Option<usize>,
+        #[arg(long, value_name = "LINE", help = "Copy the entry this line stands for instead of printing")]
+        query: Option<String>,
+    },
+
+    #[command(about = "Put the next entry from the paste queue on the clipboard")]
+    Pop {
+        #[arg(long, help = "Also paste it into the focused app")]
+        paste: bool,
+        #[arg(long, conflicts_with = "paste", help = "Empty the queue instead")]
+        clear: bool,
+    },
+
+    #[command(about = "Read and change settings in ~/.clippie/config.toml")]
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    #[command(about = "List, create and switch between profiles, each with its own database")]
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+
+    #[command(about = "Exchange entries with clippie on other machines")]
+    Sync {
+        #[command(subcommand)]
+        action: SyncAction,
+    },
+
+    #[command(about = "Save entries as named snippets that outlive the history")]
+    Snippet {
+        #[command(subcommand)]
+        action: SnippetAction,
+    },
+
+    #[command(about = "Save text from stdin or a file to the history")]
+    Add {
+        #[arg(long, help = "Read this file instead of stdin")]
+        file: Option<PathBuf>,
+        #[arg(long, help = "Also put the text on the clipboard")]
+        copy: bool,
+    },
+
+    #[command(about = "Print a highlighted rendering of an entry (for fzf --preview)")]
+    Preview {
+        id: i64,
+        #[arg(long, help = "Wrap width (defaults to $FZF_PREVIEW_COLUMNS or the terminal width)")]
+        width: Option<usize>,
+    },
+
+    #[command(about = "Serve a line-based JSON protocol on stdin/stdout for editor plugins")]
+    Rpc,
+
+    #[command(about = "Serve clipboard history over a token-protected local HTTP API")]
+    Serve {
+        #[arg(long, help = "Port to listen on (defaults to api.port in the config)")]
+        port: Option<u16>,
+    },
+
+    #[command(about = "Install the daemon service (launchd or systemd)")]
     Install,
 
+    #[command(about = "Stop the daemon and remove its service definition")]
+    Uninstall,
+
     #[command(about = "Pause clipboard monitoring")]
-    Pause,
+    Pause {
+        #[arg(long = "for", value_name = "DURATION", help = "Resume automatically after e.g. 10m or 1h30m")]
+        duration: Option<String>,
+    },
 
     #[command(about = "Resume clipboard monitoring")]
     Resume,
 
+    #[command(about = "Stop saving clipboard history until resumed or the timer runs out")]
+    Incognito {
+        #[arg(long = "for", value_name = "DURATION", help = "Resume automatically after e.g. 30m or 1h30m")]
+        duration: Option<String>,
+    },
+
+    #[command(about = "Show capture status and recent entries in the menu bar")]
+    Menubar,
+
     #[command(about = "Run the daemon process", hide = true)]
     Daemon,
 }
 
+#[derive(Subcommand, Debug)]
+pub enum SnippetAction {
+    #[command(about = "Save an entry by recency (1 is the latest) or --id under a name")]
+    Add {
+        name: String,
+        #[arg(default_value_t = 1, help = "Position in history, 1 for the most recent entry")]
+        target: usize,
+        #[arg(long, help = "Treat the position as a database id")]
+        id: bool,
+    },
+    #[command(about = "Put a snippet on the clipboard")]
+    Copy { name: String },
+    #[command(about = "Print each snippet's name and first line")]
+    List,
+    #[command(about = "Delete a snippet")]
+    Remove { name: String },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    #[command(about = "Print every setting, defaults included, as key = value")]
+    List,
+    #[command(about = "Print one setting, e.g. polling.max_interval_ms")]
+    Get { key: String },
+    #[command(about = "Change a setting; the value is checked before the file is written")]
+    Set { key: String, value: String },
+    #[command(about = "Remove a setting from the file so its default applies")]
+    Unset { key: String },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileAction {
+    #[command(about = "Print every profile and its database, marking the active one")]
+    List,
+    #[command(about = "Add a profile, with its database at ~/.clippie/<NAME>.db unless --db is given")]
+    Create {
+        name: String,
+        #[arg(long, value_name = "PATH", help = "Database file for the profile")]
+        db: Option<PathBuf>,
+    },
+    #[command(about = "Make a profile the active one for the daemon and the TUI")]
+    Switch { name: String },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SyncAction {
+    #[command(about = "Show each peer's last exchange, entry counts and errors")]
+    Status,
+    #[command(about = "Print a new random key for sync.key, to use on every machine")]
+    Key,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ListFormat {
+    /// One `id<TAB>content` line per entry, newlines escaped
+    Plain,
+    /// A list of property lists for Emacs `read`
+    Sexp,
+    /// A JSON array of entry objects
+    Json,
+    /// `id`, `last_copied`, `language`, `source_app` and `content` columns, tabs and newlines escaped
+    Tsv,
+    /// Alfred script filter items; `arg` is the entry id
+    Alfred,
+    /// Items for a Raycast `List`, with `id`, `title`, `subtitle` and `accessories`; `arg` is the entry id
+    Raycast,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    /// Alfred snippet collection (.alfredsnippets)
+    AlfredSnippets,
+    /// Raycast snippets JSON
+    RaycastSnippets,
+    /// Standalone searchable HTML page
+    Html,
+    /// Every field including copy counts; readable by `clippie import`
+    Json,
+    /// One row per entry with a header, for spreadsheets
+    Csv,
+    /// Plain text with a dated header per entry
+    Txt,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ImportFormat {
+    /// Pick from the file name and contents
+    Auto,
+    /// `clippie export --format json`, a backup snapshot, `clippie list --json` or an array of strings
+    Json,
+    /// One entry per line, the first being the most recent
+    Lines,
+    /// Maccy's Storage.sqlite
+    Maccy,
+    /// Flycut's preferences plist
+    Flycut,
+    /// A JSON array of CopyQ item texts
+    Copyq,
+}
+
 impl Cli {
     pub fn parse_args() -> Self {
         Parser::parse()
@@ -61,6 +352,12 @@ mod tests {
     fn test_cli_status_command() {
         let cli = Cli::try_parse_from(["clippie", "status"]).unwrap();
         assert!(matches!(cli.command, Some(Commands::Status)));
+        let cli = Cli::try_parse_from(["clippie", "doctor"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Doctor)));
+        let cli = Cli::try_parse_from(["clippie", "vacuum"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Vacuum)));
+        let cli = Cli::try_parse_from(["clippie", "stats", "--json"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Stats { json: true })));
     }
 
     #[test]
@@ -68,4 +365,72 @@ mod tests {
         let cli = Cli::try_parse_from(["clippie", "clear", "--all"]).unwrap();
         assert!(matches!(cli.command, Some(Commands::Clear { all: true })));
     }
+
+    #[test]
+    fn test_cli_pause_for() {
+        let cli = Cli::try_parse_from(["clippie", "pause", "--for", "10m"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Pause { duration: Some(d) }) if d == "10m"));
+    }
+
+    #[test]
+    fn test_cli_pop() {
+        let cli = Cli::try_parse_from(["clippie", "pop", "--paste"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Pop { paste: true, clear: false })));
+        assert!(Cli::try_parse_from(["clippie", "pop", "--paste", "--clear"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_config_set() {
+        let cli = Cli::try_parse_from(["clippie", "config", "set", "max_age_days", "90"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Config { action: ConfigAction::Set { key, value } }) if key == "max_age_days" && value == "90"
+        ));
+    }
+
+    #[test]
+    fn test_cli_transform() {
+        let cli = Cli::try_parse_from(["clippie", "transform", "3", "--id", "--op", "json-pretty"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Transform { target: 3, id: true, op, print: false }) if op == "json-pretty"));
+        assert!(Cli::try_parse_from(["clippie", "transform", "3"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_pick_and_copy_stdin() {
+        let cli = Cli::try_parse_from(["clippie", "pick", "--with-index", "--query", "3\tfoo"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Pick { with_index: true, limit: None, query: Some(q) }) if q == "3\tfoo"));
+        let cli = Cli::try_parse_from(["clippie", "copy", "--stdin"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Copy { target: None, stdin: true, .. })));
+        assert!(Cli::try_parse_from(["clippie", "copy"]).is_err());
+        assert!(Cli::try_parse_from(["clippie", "copy", "2", "--stdin"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_profile() {
+        let cli = Cli::try_parse_from(["clippie", "profile", "create", "work", "--db", "/tmp/work.db"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Profile { action: ProfileAction::Create { name, db: Some(_) } }) if name == "work"
+        ));
+        let cli = Cli::try_parse_from(["clippie", "list", "--profile", "work"]).unwrap();
+        assert_eq!(cli.profile.as_deref(), Some("work"));
+    }
+
+    #[test]
+    fn test_cli_snippet_add() {
+        let cli = Cli::try_parse_from(["clippie", "snippet", "add", "deploy"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Snippet { action: SnippetAction::Add { name, target: 1, id: false } }) if name == "deploy"
+        ));
+    }
+
+    #[test]
+    fn test_cli_export_format() {
+        let cli = Cli::try_parse_from(["clippie", "export", "--format", "raycast-snippets", "--out", "s.json"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Export { format: ExportFormat::RaycastSnippets, limit: None, .. })
+        ));
+    }
 }
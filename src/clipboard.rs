@@ -1,11 +1,35 @@
 use crate::error::{CliError, Result};
+use crate::config::{ConfigManager, DuplicatePolicy, Osc52Config, Osc52Mode};
+use crate::kind::Kind;
+use crate::osc52;
+use once_cell::sync::Lazy;
 use sha2::{Digest, Sha256};
+use std::io::ErrorKind;
 use std::process::Command;
 
+/// Read once per process; the daemon polls too often to reload the config every time.
+static OSC52: Lazy<Osc52Config> =
+    Lazy::new(|| ConfigManager::new().and_then(|c| c.load()).map(|c| c.osc52).unwrap_or_default());
+
+/// Whether to go through the terminal instead of `pbcopy` and `pbpaste`.
+fn prefer_osc52(mode: Osc52Mode) -> bool {
+    match mode {
+        Osc52Mode::Always => true,
+        Osc52Mode::Auto => osc52::over_ssh(),
+        Osc52Mode::Never => false,
+    }
+}
+
 pub fn get_clipboard_content() -> Result<Option<String>> {
-    let output = Command::new("pbpaste")
-        .output()
-        .map_err(|e| CliError::ClipboardError(format!("pbpaste error: {}", e)))?;
+    if OSC52.paste && prefer_osc52(OSC52.copy) {
+        return osc52::paste();
+    }
+    let output = match Command::new("pbpaste").output() {
+        Err(e) if e.kind() == ErrorKind::NotFound && OSC52.paste && OSC52.copy == Osc52Mode::Auto => {
+            return osc52::paste();
+        }
+        output => output.map_err(|e| CliError::ClipboardError(format!("pbpaste error: {}", e)))?,
+    };
 
     if !output.status.success() {
         return Ok(None);
@@ -16,6 +40,7 @@ pub fn get_clipboard_content() -> Result<Option<String>> {
 }
 
 #[allow(dead_code)]
+#[cfg(target_os = "macos")]
 pub fn get_pasteboard_change_count() -> i64 {
     use objc2::runtime::{AnyClass, AnyObject};
     use objc2::msg_send;
@@ -32,13 +57,276 @@ pub fn get_pasteboard_change_count() -> i64 {
     }
 }
 
+/// Pasteboard types that password managers attach to the items they copy.
+const PASSWORD_MANAGER_MARKERS: &[&str] = &[
+    "org.nspasteboard.ConcealedType",
+    "org.nspasteboard.TransientType",
+    "com.agilebits.onepassword",
+    "com.bitwarden.desktop",
+    "com.keepassxc.KeePassXC",
+];
+
+/// Types (UTIs) of the current pasteboard item.
+#[cfg(target_os = "macos")]
+pub fn get_pasteboard_types() -> Vec<String> {
+    use objc2::rc::Retained;
+    use objc2::runtime::{AnyClass, AnyObject};
+    use objc2::{msg_send, msg_send_id};
+    use objc2_foundation::{NSArray, NSString};
+
+    unsafe {
+        let Some(pasteboard_class) = AnyClass::get("NSPasteboard") else {
+            return Vec::new();
+        };
+        let pasteboard: *mut AnyObject = msg_send![pasteboard_class, generalPasteboard];
+        if pasteboard.is_null() {
+            return Vec::new();
+        }
+        let Some(types): Option<Retained<NSArray<NSString>>> = msg_send_id![pasteboard, types] else {
+            return Vec::new();
+        };
+        let count: usize = msg_send![&*types, count];
+        (0..count)
+            .map(|i| {
+                let uti: Retained<NSString> = msg_send_id![&*types, objectAtIndex: i];
+                uti.to_string()
+            })
+            .collect()
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn get_pasteboard_types() -> Vec<String> {
+    Vec::new()
+}
+
+/// Type of the pasteboard items Finder puts there for copied files, successor of `NSFilenamesPboardType`.
+pub const FILE_URL_TYPE: &str = "public.file-url";
+
+/// Paths of the files on the pasteboard, one per item of `FILE_URL_TYPE`.
+#[cfg(target_os = "macos")]
+pub fn get_pasteboard_files() -> Vec<String> {
+    use objc2::rc::Retained;
+    use objc2::runtime::{AnyClass, AnyObject};
+    use objc2::{msg_send, msg_send_id};
+    use objc2_foundation::{NSArray, NSString};
+
+    unsafe {
+        let (Some(pasteboard_class), Some(url_class)) = (AnyClass::get("NSPasteboard"), AnyClass::get("NSURL")) else {
+            return Vec::new();
+        };
+        let pasteboard: *mut AnyObject = msg_send![pasteboard_class, generalPasteboard];
+        if pasteboard.is_null() {
+            return Vec::new();
+        }
+        let Some(items): Option<Retained<NSArray<AnyObject>>> = msg_send_id![pasteboard, pasteboardItems] else {
+            return Vec::new();
+        };
+        let file_url_type = NSString::from_str(FILE_URL_TYPE);
+        let count: usize = msg_send![&*items, count];
+        (0..count)
+            .filter_map(|i| {
+                let item: Retained<AnyObject> = msg_send_id![&*items, objectAtIndex: i];
+                let url: Option<Retained<NSString>> = msg_send_id![&*item, stringForType: &*file_url_type];
+                // Finder hands out file reference URLs (`file:///.file/id=…`) that only NSURL resolves to a path.
+                let url: Option<Retained<AnyObject>> = msg_send_id![url_class, URLWithString: &*url?];
+                let url: Option<Retained<AnyObject>> = msg_send_id![&*url?, filePathURL];
+                let path: Option<Retained<NSString>> = msg_send_id![&*url?, path];
+                Some(path?.to_string())
+            })
+            .collect()
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn get_pasteboard_files() -> Vec<String> {
+    Vec::new()
+}
+
+/// Put `paths` on the clipboard as file references, as copying them in Finder does, so they
+/// paste as files. Without AppKit, or over OSC 52, the paths go on as text, one per line.
+pub fn set_clipboard_files(paths: &[&str]) -> Result<()> {
+    #[cfg(target_os = "macos")]
+    if !prefer_osc52(OSC52.copy) {
+        return write_file_urls(paths);
+    }
+    set_clipboard_content(&paths.join("\n"))
+}
+
+#[cfg(target_os = "macos")]
+fn write_file_urls(paths: &[&str]) -> Result<()> {
+    use objc2::rc::Retained;
+    use objc2::runtime::{AnyClass, AnyObject, Bool};
+    use objc2::{msg_send, msg_send_id};
+    use objc2_foundation::NSString;
+
+    let failed = || CliError::ClipboardError("Failed to put the files on the pasteboard".to_string());
+    unsafe {
+        let (Some(pasteboard_class), Some(url_class), Some(array_class)) =
+            (AnyClass::get("NSPasteboard"), AnyClass::get("NSURL"), AnyClass::get("NSMutableArray"))
+        else {
+            return Err(failed());
+        };
+        let pasteboard: *mut AnyObject = msg_send![pasteboard_class, generalPasteboard];
+        if pasteboard.is_null() {
+            return Err(failed());
+        }
+        let urls: Retained<AnyObject> = msg_send_id![array_class, array];
+        for path in paths {
+            let path = NSString::from_str(path);
+            let url: Retained<AnyObject> = msg_send_id![url_class, fileURLWithPath: &*path];
+            let _: () = msg_send![&*urls, addObject: &*url];
+        }
+        let _: isize = msg_send![pasteboard, clearContents];
+        let written: Bool = msg_send![pasteboard, writeObjects: &*urls];
+        if written.as_bool() { Ok(()) } else { Err(failed()) }
+    }
+}
+
+/// Formatted versions of copied text, kept so that pasting from clippie keeps the formatting.
+pub const RICH_TEXT_TYPES: [&str; 2] = ["public.rtf", "public.html"];
+/// Larger flavors, such as a whole web page copied as HTML, are dropped and only the text kept.
+pub const MAX_FLAVOR_BYTES: usize = 1 << 20;
+
+/// Pasteboard data of another type stored alongside an entry's plain text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flavor {
+    pub uti: String,
+    pub data: Vec<u8>,
+}
+
+/// The `RICH_TEXT_TYPES` flavors on the pasteboard.
+#[cfg(target_os = "macos")]
+pub fn get_pasteboard_flavors() -> Vec<Flavor> {
+    use objc2::rc::Retained;
+    use objc2::runtime::{AnyClass, AnyObject};
+    use objc2::{msg_send, msg_send_id};
+    use objc2_foundation::{NSData, NSString};
+
+    unsafe {
+        let Some(pasteboard_class) = AnyClass::get("NSPasteboard") else {
+            return Vec::new();
+        };
+        let pasteboard: *mut AnyObject = msg_send![pasteboard_class, generalPasteboard];
+        if pasteboard.is_null() {
+            return Vec::new();
+        }
+        RICH_TEXT_TYPES
+            .iter()
+            .filter_map(|uti| {
+                let data: Option<Retained<NSData>> = msg_send_id![pasteboard, dataForType: &*NSString::from_str(uti)];
+                Some(Flavor { uti: uti.to_string(), data: data?.bytes().to_vec() })
+            })
+            .collect()
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn get_pasteboard_flavors() -> Vec<Flavor> {
+    Vec::new()
+}
+
+/// Put `text` on the pasteboard together with its other flavors, so apps that understand them
+/// paste it formatted.
+#[cfg(target_os = "macos")]
+fn write_flavors(text: &str, flavors: &[Flavor]) -> Result<()> {
+    use objc2::runtime::{AnyClass, AnyObject, Bool};
+    use objc2::msg_send;
+    use objc2_foundation::{NSData, NSString};
+
+    let failed = || CliError::ClipboardError("Failed to put the formatted text on the pasteboard".to_string());
+    unsafe {
+        let Some(pasteboard_class) = AnyClass::get("NSPasteboard") else {
+            return Err(failed());
+        };
+        let pasteboard: *mut AnyObject = msg_send![pasteboard_class, generalPasteboard];
+        if pasteboard.is_null() {
+            return Err(failed());
+        }
+        let _: isize = msg_send![pasteboard, clearContents];
+        let plain = NSString::from_str("public.utf8-plain-text");
+        let written: Bool = msg_send![pasteboard, setString: &*NSString::from_str(text), forType: &*plain];
+        if !written.as_bool() {
+            return Err(failed());
+        }
+        for flavor in flavors {
+            let data = NSData::with_bytes(&flavor.data);
+            let _: Bool = msg_send![pasteboard, setData: &*data, forType: &*NSString::from_str(&flavor.uti)];
+        }
+        Ok(())
+    }
+}
+
+/// Without AppKit there's only the plain text to put back.
+#[cfg(not(target_os = "macos"))]
+fn write_flavors(text: &str, _flavors: &[Flavor]) -> Result<()> {
+    set_clipboard_content(text)
+}
+
+/// Put an entry's content on the clipboard: the files of a `Kind::Files` entry as file references,
+/// text with its stored `flavors` when it has any, else as plain text.
+pub fn set_clipboard_entry(content: &str, kind: Kind, flavors: &[Flavor]) -> Result<()> {
+    match kind {
+        Kind::Files => set_clipboard_files(&content.lines().filter(|line| !line.is_empty()).collect::<Vec<_>>()),
+        _ if !flavors.is_empty() && !prefer_osc52(OSC52.copy) => write_flavors(content, flavors),
+        _ => set_clipboard_content(content),
+    }
+}
+
+/// An application content was copied from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceApp {
+    pub bundle_id: String,
+    pub name: String,
+}
+
+/// The frontmost application, which is normally the one that just copied.
+#[cfg(target_os = "macos")]
+pub fn frontmost_app() -> Option<SourceApp> {
+    use objc2::rc::Retained;
+    use objc2::runtime::{AnyClass, AnyObject};
+    use objc2::{msg_send, msg_send_id};
+    use objc2_foundation::NSString;
+
+    unsafe {
+        let workspace_class = AnyClass::get("NSWorkspace")?;
+        let workspace: *mut AnyObject = msg_send![workspace_class, sharedWorkspace];
+        if workspace.is_null() {
+            return None;
+        }
+        let app: *mut AnyObject = msg_send![workspace, frontmostApplication];
+        if app.is_null() {
+            return None;
+        }
+        let bundle_id: Option<Retained<NSString>> = msg_send_id![app, bundleIdentifier];
+        let name: Option<Retained<NSString>> = msg_send_id![app, localizedName];
+        let bundle_id = bundle_id?.to_string();
+        Some(SourceApp { name: name.map_or_else(|| bundle_id.clone(), |name| name.to_string()), bundle_id })
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn frontmost_app() -> Option<SourceApp> {
+    None
+}
+
+/// Whether the pasteboard item was placed there by a password manager.
+pub fn is_password_manager_item(types: &[String]) -> bool {
+    types.iter().any(|t| PASSWORD_MANAGER_MARKERS.contains(&t.as_str()))
+}
+
+/// Put `content` on the clipboard with `pbcopy`, or through the terminal with OSC 52 in SSH
+/// sessions and where `pbcopy` is missing, as `osc52.copy` says.
 pub fn set_clipboard_content(content: &str) -> Result<()> {
     use std::io::Write;
 
-    let mut child = Command::new("pbcopy")
-        .stdin(std::process::Stdio::piped())
-        .spawn()
-        .map_err(|e| CliError::ClipboardError(format!("Failed to run pbcopy: {}", e)))?;
+    if prefer_osc52(OSC52.copy) {
+        return osc52::copy(content);
+    }
+    let mut child = match Command::new("pbcopy").stdin(std::process::Stdio::piped()).spawn() {
+        Err(e) if e.kind() == ErrorKind::NotFound && OSC52.copy == Osc52Mode::Auto => return osc52::copy(content),
+        child => child.map_err(|e| CliError::ClipboardError(format!("Failed to run pbcopy: {}", e)))?,
+    };
 
     child
         .stdin
@@ -58,16 +346,100 @@ pub fn set_clipboard_content(content: &str) -> Result<()> {
     }
 }
 
+/// Pause between keystrokes when typing an entry out, unless `type_delay_ms` says otherwise.
+pub const DEFAULT_TYPE_DELAY_MS: u64 = 15;
+
+/// Type `text` key by key into whichever app regains focus once we exit, for remote consoles
+/// and forms that block pasting.
+pub fn type_into_focused_app(text: &str, delay: std::time::Duration) -> Result<()> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    // Both read the text from stdin, so it never shows up in `ps`.
+    let (child, input) = if cfg!(target_os = "macos") {
+        let child = Command::new("osascript").arg("-").stdin(Stdio::piped()).spawn();
+        (child, type_script(text, delay))
+    } else {
+        let command = format!("sleep 0.3; xdotool type --clearmodifiers --delay {} --file -", delay.as_millis());
+        (Command::new("sh").args(["-c", &command]).stdin(Stdio::piped()).spawn(), text.to_string())
+    };
+    let mut child = child.map_err(|e| CliError::ClipboardError(format!("Failed to start typing: {}", e)))?;
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| CliError::ClipboardError("Failed to open stdin".to_string()))?
+        .write_all(input.as_bytes())
+        .map_err(|e| CliError::ClipboardError(format!("Failed to send the text to type: {}", e)))?;
+    Ok(())
+}
+
+/// AppleScript typing `text` one key at a time: line breaks and tabs as key presses, since
+/// `keystroke` drops them in some apps.
+fn type_script(text: &str, delay: std::time::Duration) -> String {
+    let mut script = String::from("delay 0.3\ntell application \"System Events\"\n");
+    for c in text.chars() {
+        match c {
+            '\r' => continue,
+            '\n' => script.push_str("key code 36"),
+            '\t' => script.push_str("key code 48"),
+            '"' => script.push_str("keystroke quote"),
+            '\\' => script.push_str("keystroke \"\\\\\""),
+            c => script.push_str(&format!("keystroke \"{}\"", c)),
+        }
+        script.push_str(&format!("\ndelay {}\n", delay.as_secs_f64()));
+    }
+    script.push_str("end tell\n");
+    script
+}
+
+/// Send a paste keystroke to whichever app regains focus once we exit.
+pub fn paste_into_focused_app() {
+    let spawned = if cfg!(target_os = "macos") {
+        Command::new("osascript")
+            .args(["-e", "delay 0.3"])
+            .args(["-e", "tell application \"System Events\" to keystroke \"v\" using command down"])
+            .spawn()
+    } else {
+        Command::new("sh")
+            .args(["-c", "sleep 0.3; xdotool key --clearmodifiers ctrl+v"])
+            .spawn()
+    };
+    let _ = spawned;
+}
+
 pub fn hash_content(content: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(content.as_bytes());
     format!("{:x}", hasher.finalize())
 }
 
+/// Hash of `content` after the policy's normalization, used to spot repeat copies.
+pub fn duplicate_hash(content: &str, policy: &DuplicatePolicy) -> String {
+    let mut normalized = if policy.collapse_whitespace {
+        content.split_whitespace().collect::<Vec<_>>().join(" ")
+    } else if policy.trim_trailing_whitespace {
+        content.trim_end().to_string()
+    } else {
+        content.to_string()
+    };
+    if policy.ignore_case {
+        normalized = normalized.to_lowercase();
+    }
+    hash_content(&normalized)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_type_script() {
+        let script = type_script("a\"\\\r\n", std::time::Duration::from_millis(20));
+        assert!(script.starts_with("delay 0.3\ntell application \"System Events\"\nkeystroke \"a\"\ndelay 0.02\n"));
+        assert!(script.contains("keystroke quote\ndelay 0.02\nkeystroke \"\\\\\"\ndelay 0.02\nkey code 36\n"));
+        assert!(script.ends_with("end tell\n"));
+    }
+
     #[test]
     fn test_hash_content() {
         let hash = hash_content("test content");
@@ -75,6 +447,24 @@ mod tests {
         assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
     }
 
+    #[test]
+    fn test_is_password_manager_item() {
+        let types = vec!["public.utf8-plain-text".to_string(), "org.nspasteboard.ConcealedType".to_string()];
+        assert!(is_password_manager_item(&types));
+        assert!(!is_password_manager_item(&["public.utf8-plain-text".to_string()]));
+    }
+
+    #[test]
+    fn test_duplicate_hash() {
+        let trim = DuplicatePolicy::default();
+        assert_eq!(duplicate_hash("foo\n", &trim), duplicate_hash("foo", &trim));
+        assert_ne!(duplicate_hash("  foo", &trim), duplicate_hash("foo", &trim));
+        assert_ne!(duplicate_hash("foo\n", &DuplicatePolicy { trim_trailing_whitespace: false, ..trim }), hash_content("foo"));
+
+        let loose = DuplicatePolicy { trim_trailing_whitespace: false, collapse_whitespace: true, ignore_case: true };
+        assert_eq!(duplicate_hash("SELECT *\n  FROM t ", &loose), duplicate_hash("select * from t", &loose));
+    }
+
     #[test]
     fn test_hash_consistency() {
         let hash1 = hash_content("test");
@@ -0,0 +1,227 @@
+//! CSS colors such as `#ff8800`, `rgb(255 136 0)` or `hsl(32, 100%, 50%)`: parsing them,
+//! finding them in text and converting between the notations.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Candidates in a line of text; `parse` decides which of them are colors.
+static CANDIDATE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)#[[:xdigit:]]{3,8}\b|\b(rgba?|hsla?)\([^()]*\)").expect("color pattern is valid"));
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    /// Opacity from 0 to 1.
+    pub a: f64,
+}
+
+impl Rgba {
+    /// `#rrggbb`, with `aa` appended when it isn't opaque.
+    pub fn hex(self) -> String {
+        let hex = format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b);
+        match self.opaque() {
+            true => hex,
+            false => format!("{}{:02x}", hex, (self.a * 255.0).round() as u8),
+        }
+    }
+
+    /// `rgb(r, g, b)`, or `rgba(r, g, b, a)` when it isn't opaque.
+    pub fn rgb(self) -> String {
+        match self.opaque() {
+            true => format!("rgb({}, {}, {})", self.r, self.g, self.b),
+            false => format!("rgba({}, {}, {}, {})", self.r, self.g, self.b, alpha(self.a)),
+        }
+    }
+
+    /// `hsl(h, s%, l%)`, or `hsla(…, a)` when it isn't opaque; rounded to whole numbers.
+    pub fn hsl(self) -> String {
+        let (h, s, l) = to_hsl(self.r, self.g, self.b);
+        let (h, s, l) = (h.round() as u32 % 360, (s * 100.0).round(), (l * 100.0).round());
+        match self.opaque() {
+            true => format!("hsl({}, {}%, {}%)", h, s, l),
+            false => format!("hsla({}, {}%, {}%, {})", h, s, l, alpha(self.a)),
+        }
+    }
+
+    /// Whether dark text reads better on it than light text.
+    pub fn is_light(self) -> bool {
+        0.299 * self.r as f64 + 0.587 * self.g as f64 + 0.114 * self.b as f64 > 150.0
+    }
+
+    fn opaque(self) -> bool {
+        self.a >= 1.0
+    }
+}
+
+/// Opacity with at most two decimals: `0.5`, `0.33`.
+fn alpha(a: f64) -> String {
+    format!("{}", (a * 100.0).round() / 100.0)
+}
+
+/// The color `text` is, or `None` when it's anything else.
+pub fn parse(text: &str) -> Option<Rgba> {
+    let text = text.trim();
+    if let Some(hex) = text.strip_prefix('#') {
+        return parse_hex(hex);
+    }
+    let lower = text.to_ascii_lowercase();
+    let (function, rest) = lower.split_once('(')?;
+    let args: Vec<&str> = rest.strip_suffix(')')?.split([',', ' ', '/']).filter(|arg| !arg.is_empty()).collect();
+    let (channels, a) = match args.as_slice() {
+        [x, y, z] => ([*x, *y, *z], 1.0),
+        [x, y, z, a] => ([*x, *y, *z], parse_alpha(a)?),
+        _ => return None,
+    };
+    let (r, g, b) = match function {
+        "rgb" | "rgba" => {
+            let [r, g, b] = channels.map(parse_channel);
+            (r?, g?, b?)
+        }
+        "hsl" | "hsla" => {
+            let h: f64 = channels[0].strip_suffix("deg").unwrap_or(channels[0]).parse().ok()?;
+            let [s, l] = [channels[1], channels[2]].map(|v| parse_number(v.strip_suffix('%').unwrap_or(v), 100.0));
+            from_hsl(h.rem_euclid(360.0), s? / 100.0, l? / 100.0)
+        }
+        _ => return None,
+    };
+    Some(Rgba { r, g, b, a })
+}
+
+/// Every color in `text` by byte range, leftmost first.
+pub fn find(text: &str) -> Vec<(usize, usize, Rgba)> {
+    if !text.contains(['#', '(']) {
+        return vec![];
+    }
+    CANDIDATE
+        .find_iter(text)
+        // `&#123;` is an HTML entity and `a#bad` an anchor, not colors.
+        .filter(|m| !text[..m.start()].ends_with(|c: char| c == '&' || c.is_alphanumeric()))
+        .filter_map(|m| Some((m.start(), m.end(), parse(m.as_str())?)))
+        .collect()
+}
+
+/// `rgb`, `rgba`, `rrggbb` or `rrggbbaa`.
+fn parse_hex(hex: &str) -> Option<Rgba> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digits: Vec<u8> = match hex.len() {
+        3 | 4 => hex.chars().map(|c| c.to_digit(16).map(|d| d as u8 * 17)).collect::<Option<_>>()?,
+        6 | 8 => (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok()).collect::<Option<_>>()?,
+        _ => return None,
+    };
+    Some(Rgba { r: digits[0], g: digits[1], b: digits[2], a: digits.get(3).map_or(1.0, |&a| a as f64 / 255.0) })
+}
+
+/// A number from 0 to `max`.
+fn parse_number(text: &str, max: f64) -> Option<f64> {
+    text.parse::<f64>().ok().filter(|v| (0.0..=max).contains(v))
+}
+
+/// `0`–`255` or `0%`–`100%`.
+fn parse_channel(text: &str) -> Option<u8> {
+    let value = match text.strip_suffix('%') {
+        Some(percent) => parse_number(percent, 100.0)? * 2.55,
+        None => parse_number(text, 255.0)?,
+    };
+    Some(value.round() as u8)
+}
+
+/// `0`–`1` or `0%`–`100%`.
+fn parse_alpha(text: &str) -> Option<f64> {
+    match text.strip_suffix('%') {
+        Some(percent) => Some(parse_number(percent, 100.0)? / 100.0),
+        None => parse_number(text, 1.0),
+    }
+}
+
+/// Hue in degrees, saturation and lightness from 0 to 1.
+fn to_hsl(r: u8, g: u8, b: u8) -> (f64, f64, f64) {
+    let [r, g, b] = [r, g, b].map(|c| c as f64 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    let d = max - min;
+    if d == 0.0 {
+        return (0.0, 0.0, l);
+    }
+    let s = d / (1.0 - (2.0 * l - 1.0).abs());
+    let h = if max == r {
+        60.0 * ((g - b) / d).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / d + 2.0)
+    } else {
+        60.0 * ((r - g) / d + 4.0)
+    };
+    (h, s, l)
+}
+
+fn from_hsl(h: f64, s: f64, l: f64) -> (u8, u8, u8) {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+    let m = l - c / 2.0;
+    let (r, g, b) = match (h / 60.0) as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let channel = |v: f64| ((v + m) * 255.0).round() as u8;
+    (channel(r), channel(g), channel(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgba(r: u8, g: u8, b: u8, a: f64) -> Option<Rgba> {
+        Some(Rgba { r, g, b, a })
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("#ff8800"), rgba(255, 136, 0, 1.0));
+        assert_eq!(parse("#F80"), rgba(255, 136, 0, 1.0));
+        assert_eq!(parse("#ff880080"), rgba(255, 136, 0, 128.0 / 255.0));
+        assert_eq!(parse("rgb(255, 136, 0)"), rgba(255, 136, 0, 1.0));
+        assert_eq!(parse("RGBA(100%, 0%, 0%, 0.5)"), rgba(255, 0, 0, 0.5));
+        assert_eq!(parse("rgb(255 136 0 / 50%)"), rgba(255, 136, 0, 0.5));
+        assert_eq!(parse("hsl(32, 100%, 50%)"), rgba(255, 136, 0, 1.0));
+        assert_eq!(parse("hsl(120deg 100% 25%)"), rgba(0, 128, 0, 1.0));
+
+        assert_eq!(parse("#ff88"), rgba(255, 255, 136, 136.0 / 255.0));
+        assert_eq!(parse("#ff888"), None);
+        assert_eq!(parse("#hashtag"), None);
+        assert_eq!(parse("rgb(256, 0, 0)"), None);
+        assert_eq!(parse("rgb(1, 2)"), None);
+        assert_eq!(parse("calc(1, 2, 3)"), None);
+    }
+
+    #[test]
+    fn test_conversions() {
+        let orange = parse("#ff8800").unwrap();
+        assert_eq!(orange.hex(), "#ff8800");
+        assert_eq!(orange.rgb(), "rgb(255, 136, 0)");
+        assert_eq!(orange.hsl(), "hsl(32, 100%, 50%)");
+        assert!(orange.is_light());
+
+        let navy = parse("rgba(0, 0, 128, 0.5)").unwrap();
+        assert_eq!(navy.hex(), "#00008080");
+        assert_eq!(navy.rgb(), "rgba(0, 0, 128, 0.5)");
+        assert_eq!(navy.hsl(), "hsla(240, 100%, 25%, 0.5)");
+        assert!(!navy.is_light());
+        assert_eq!(parse("#808080").unwrap().hsl(), "hsl(0, 0%, 50%)");
+    }
+
+    #[test]
+    fn test_find() {
+        let css = "a { color: #f80; border: 1px solid rgb(0 0 0); } a#bad &#123; #zzz";
+        let found: Vec<&str> = find(css).iter().map(|&(start, end, _)| &css[start..end]).collect();
+        assert_eq!(found, ["#f80", "rgb(0 0 0)"]);
+        assert!(find("no colors here").is_empty());
+    }
+}
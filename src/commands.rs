@@ -2,8 +2,50 @@ pub mod setup;
 pub mod status;
 pub mod clear;
 pub mod install;
+pub mod export;
+pub mod list;
+pub mod popup;
+pub mod preview;
+pub mod rpc;
+pub mod serve;
+pub mod health;
+pub mod doctor;
+pub mod vacuum;
+pub mod stats;
+pub mod config;
+pub mod incognito;
+pub mod get;
+pub mod pick;
+pub mod transform;
+pub mod add;
+pub mod import;
+pub mod pop;
+pub mod snippet;
+pub mod profile;
+pub mod sync;
 
 pub use setup::run_setup;
 pub use status::run_status;
 pub use clear::run_clear;
-pub use install::run_install;
+pub use install::{run_install, run_uninstall};
+pub use export::run_export;
+pub use list::run_list;
+pub use popup::run_popup;
+pub use preview::run_preview;
+pub use rpc::run_rpc;
+pub use serve::run_serve;
+pub use health::run_health;
+pub use doctor::run_doctor;
+pub use vacuum::run_vacuum;
+pub use stats::run_stats;
+pub use config::run_config;
+pub use incognito::run_incognito;
+pub use get::{run_copy, run_copy_stdin, run_get};
+pub use pick::run_pick;
+pub use transform::run_transform;
+pub use add::run_add;
+pub use import::run_import;
+pub use pop::run_pop;
+pub use snippet::run_snippet;
+pub use profile::run_profile;
+pub use sync::run_sync;
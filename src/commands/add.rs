@@ -0,0 +1,110 @@
+use crate::clipboard::{self, duplicate_hash};
+use crate::config::{Config, ConfigManager};
+use crate::daemon::ignore_set;
+use crate::db::{Database, NewEntry};
+use crate::error::{CliError, Result};
+use crate::kind::Kind;
+use crate::{secrets, urls};
+use regex::RegexSet;
+use std::io::Read;
+use std::path::Path;
+
+/// Save text from `file` or stdin to the history, and optionally put it on the clipboard too.
+pub async fn run_add(file: Option<&Path>, copy: bool) -> Result<()> {
+    let content = match file {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => {
+            let mut content = String::new();
+            std::io::stdin().read_to_string(&mut content)?;
+            content
+        }
+    };
+    if content.trim().is_empty() {
+        return Err(CliError::InvalidInput("Nothing to add: input is empty".to_string()));
+    }
+
+    let config = ConfigManager::new()?;
+    let db = Database::open(config.get_db_path()?)?;
+    if AddSettings::load(&config)?.add(&db, &content)?.is_none() {
+        eprintln!("Not saved: the text matches one of ignore_patterns");
+    }
+
+    if copy {
+        clipboard::set_clipboard_content(&content)?;
+    }
+    Ok(())
+}
+
+/// The parts of the config the daemon applies to a capture, for text that arrives another way.
+pub(crate) struct AddSettings {
+    settings: Config,
+    ignore_patterns: RegexSet,
+    /// Key for hashing secrets, present when `secret_encryption` is configured.
+    hash_key: Option<String>,
+}
+
+impl AddSettings {
+    pub(crate) fn load(config: &ConfigManager) -> Result<Self> {
+        let settings = config.load().unwrap_or_default();
+        let hash_key = match settings.secret_encryption {
+            Some(_) => Some(config.hash_key()?),
+            None => None,
+        };
+        Ok(AddSettings::new(settings, hash_key))
+    }
+
+    pub(crate) fn new(settings: Config, hash_key: Option<String>) -> Self {
+        AddSettings { ignore_patterns: ignore_set(&settings.ignore_patterns), settings, hash_key }
+    }
+
+    /// Store `content` the way the daemon would have captured it, returning its id, or `None`
+    /// when it matches `ignore_patterns`.
+    pub(crate) fn add(&self, db: &Database, content: &str) -> Result<Option<i64>> {
+        if self.ignore_patterns.is_match(content.trim()) {
+            return Ok(None);
+        }
+        let content = urls::clean(content, &self.settings.url_rules, urls::Stage::Capture);
+        let hash = duplicate_hash(&content, &self.settings.duplicates);
+
+        if let Some(encryption) = &self.settings.secret_encryption
+            && secrets::looks_like_secret(&content)
+        {
+            let key = self
+                .hash_key
+                .as_deref()
+                .ok_or_else(|| CliError::EncryptionError("No key to hash secrets with".to_string()))?;
+            let entry = NewEntry {
+                content: secrets::encrypt(encryption, &content)?,
+                content_hash: secrets::keyed_hash(key, &hash),
+                encrypted: true,
+                language: None,
+                kind: Kind::Text,
+                ..NewEntry::new(&content)
+            };
+            db.insert_many(std::slice::from_ref(&entry))?;
+            db.existing_id(&entry.content_hash, &entry.content)
+        } else {
+            db.insert_entry(&content, &hash).map(Some)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_add_deduplicates() {
+        let tmp = NamedTempFile::new().unwrap();
+        let db = Database::open(tmp.path()).unwrap();
+        let settings = AddSettings::new(Config::default(), None);
+
+        settings.add(&db, "make test\n").unwrap();
+        settings.add(&db, "make test").unwrap();
+        let entries = db.get_all_entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].content, "make test\n");
+        assert_eq!(entries[0].language.as_deref(), Some("shell"));
+    }
+}
@@ -38,5 +38,8 @@ pub async fn run_clear(all: bool) -> Result<()> {
         println!("✓ Deleted {} old clipboard entries\n", count);
     }
 
+    // Fold the deletions into the main file so the reported size shrinks.
+    db.checkpoint()?;
+
     Ok(())
 }
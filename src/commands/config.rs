@@ -0,0 +1,223 @@
+use crate::cli::ConfigAction;
+use crate::config::{is_known_key, Config, ConfigManager};
+use crate::error::{CliError, Result};
+use crate::ipc;
+use toml_edit::{DocumentMut, Item, Table, TableLike, Value};
+
+/// Top-level settings only the TUI, `clippie serve` and `clippie popup` read; the daemon doesn't
+/// need a restart for them.
+const CLIENT_KEYS: [&str; 4] = ["tui", "api", "popup_terminal", "recent_db_paths"];
+
+/// Settings `list` masks; `get` still prints them.
+const SECRET_KEYS: [&str; 4] = ["api.read_token", "api.write_token", "tui.lock_passphrase", "sync.key"];
+
+pub async fn run_config(action: ConfigAction) -> Result<()> {
+    let manager = ConfigManager::new()?;
+    // Loading converts a config.json from an older version, so edits land in config.toml.
+    let config = manager.load()?;
+
+    match action {
+        ConfigAction::List => {
+            for (key, value) in flatten(&effective(&config)?) {
+                let shown = if SECRET_KEYS.contains(&key.as_str()) {
+                    format!("\"{}\"", crate::secrets::MASK)
+                } else {
+                    value.to_string()
+                };
+                println!("{} = {}", key, shown);
+            }
+        }
+        ConfigAction::Get { key } => {
+            if !is_known_key(&key) {
+                return Err(unknown(&key));
+            }
+            match lookup(&effective(&config)?, &key) {
+                Some(toml::Value::String(text)) => println!("{}", text),
+                Some(toml::Value::Table(table)) => print!("{}", toml::to_string_pretty(table).unwrap_or_default()),
+                Some(value) => println!("{}", value),
+                // Optional settings that aren't set print nothing, like an empty variable.
+                None => {}
+            }
+        }
+        ConfigAction::Set { key, value } => {
+            if !is_known_key(&key) {
+                return Err(unknown(&key));
+            }
+            let mut doc = read_document(&manager)?;
+            // A bare word like `dark` isn't valid TOML, and a number may be meant as a string, so
+            // the text is tried as a string when it doesn't fit as typed. The first error is the one shown.
+            let mut error = None;
+            for candidate in [value.parse::<Value>().ok(), Some(Value::from(value.as_str()))].into_iter().flatten() {
+                set_value(&mut doc, &key, candidate)?;
+                match manager.save_text(&doc.to_string()) {
+                    Ok(_) => {
+                        error = None;
+                        break;
+                    }
+                    Err(e) => {
+                        error.get_or_insert(e);
+                    }
+                }
+            }
+            if let Some(e) = error {
+                return Err(e);
+            }
+            println!("{} = {}", key, lookup(&effective(&manager.load()?)?, &key).map(|v| v.to_string()).unwrap_or_default());
+            note_restart(&manager, &key).await;
+        }
+        ConfigAction::Unset { key } => {
+            if !is_known_key(&key) {
+                return Err(unknown(&key));
+            }
+            let mut doc = read_document(&manager)?;
+            if !remove_value(&mut doc, &key) {
+                println!("{} was not set", key);
+                return Ok(());
+            }
+            manager.save_text(&doc.to_string())?;
+            println!("{} is back to its default", key);
+            note_restart(&manager, &key).await;
+        }
+    }
+    Ok(())
+}
+
+fn unknown(key: &str) -> CliError {
+    CliError::ConfigError(format!("{}: unknown key", key))
+}
+
+/// The config with every default filled in, as TOML values.
+fn effective(config: &Config) -> Result<toml::Table> {
+    toml::Table::try_from(config).map_err(|e| CliError::ConfigError(e.to_string()))
+}
+
+fn read_document(manager: &ConfigManager) -> Result<DocumentMut> {
+    let path = manager.get_config_path()?;
+    let text = std::fs::read_to_string(&path).unwrap_or_default();
+    text.parse()
+        .map_err(|e| CliError::ConfigError(format!("{}: {}", path.display(), e)))
+}
+
+fn lookup<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+    let mut parts = key.split('.');
+    let mut value = table.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_table()?.get(part)?;
+    }
+    Some(value)
+}
+
+/// `dotted.key` and value pairs for every leaf setting, tables flattened and arrays kept whole.
+fn flatten(table: &toml::Table) -> Vec<(String, toml::Value)> {
+    let mut pairs = Vec::new();
+    for (key, value) in table {
+        match value {
+            toml::Value::Table(nested) => {
+                pairs.extend(flatten(nested).into_iter().map(|(k, v)| (format!("{}.{}", key, k), v)));
+            }
+            value => pairs.push((key.clone(), value.clone())),
+        }
+    }
+    pairs
+}
+
+/// Set `key` in `doc`, creating the tables on the way to it.
+fn set_value(doc: &mut DocumentMut, key: &str, value: Value) -> Result<()> {
+    let parts: Vec<&str> = key.split('.').collect();
+    let (last, parents) = parts.split_last().expect("split yields at least one part");
+    let mut table: &mut dyn TableLike = doc.as_table_mut();
+    let mut inline = false;
+    for (i, part) in parents.iter().enumerate() {
+        if !table.contains_key(part) {
+            let mut new = Table::new();
+            new.set_implicit(true);
+            table.insert(part, Item::Table(new));
+        }
+        let item = table.get_mut(part).expect("inserted above");
+        inline |= item.is_inline_table();
+        table = item
+            .as_table_like_mut()
+            .ok_or_else(|| CliError::ConfigError(format!("{}: not a table", parts[..=i].join("."))))?;
+    }
+    match table.get_mut(last) {
+        // Replace in place so comments and spacing around the old value stay.
+        Some(Item::Value(old)) => {
+            let decor = old.decor().clone();
+            *old = value;
+            *old.decor_mut() = decor;
+        }
+        _ => {
+            table.insert(last, Item::Value(value));
+            if inline {
+                table.fmt();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Remove `key` from `doc`, and tables left empty by it. Returns false if it wasn't there.
+fn remove_value(doc: &mut DocumentMut, key: &str) -> bool {
+    let parts: Vec<&str> = key.split('.').collect();
+    remove_from(doc.as_table_mut(), &parts)
+}
+
+fn remove_from(table: &mut dyn TableLike, parts: &[&str]) -> bool {
+    let [part, rest @ ..] = parts else {
+        return false;
+    };
+    if rest.is_empty() {
+        return table.remove(part).is_some();
+    }
+    let Some(nested) = table.get_mut(part).and_then(Item::as_table_like_mut) else {
+        return false;
+    };
+    let removed = remove_from(nested, rest);
+    if removed && nested.is_empty() {
+        table.remove(part);
+    }
+    removed
+}
+
+/// Tell the user to restart a running daemon after changing a setting it reads at startup.
+async fn note_restart(manager: &ConfigManager, key: &str) {
+    let top = key.split('.').next().unwrap_or(key);
+    if !CLIENT_KEYS.contains(&top) && ipc::send(manager, ipc::Request::Stats).await.is_ok() {
+        println!("The daemon reads this at startup; run 'clippie restart' to apply it.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_value_keeps_comments_and_creates_tables() {
+        let mut doc: DocumentMut = "# keep a week\nmax_age_days = 7\nauto_clear = { after_secs = 30 }\n".parse().unwrap();
+        set_value(&mut doc, "max_age_days", Value::from(90)).unwrap();
+        set_value(&mut doc, "auto_clear.all_copies", Value::from(true)).unwrap();
+        set_value(&mut doc, "tui.keymap.down", Value::from("ctrl-n")).unwrap();
+        let text = doc.to_string();
+        assert!(text.starts_with("# keep a week\nmax_age_days = 90\n"), "{}", text);
+        assert!(text.contains("auto_clear = { after_secs = 30, all_copies = true }"), "{}", text);
+        assert!(text.contains("[tui.keymap]\ndown = \"ctrl-n\""), "{}", text);
+        assert!(set_value(&mut doc, "max_age_days.x", Value::from(1)).is_err());
+
+        assert!(remove_value(&mut doc, "auto_clear.after_secs"));
+        assert!(!remove_value(&mut doc, "backup.dir"));
+        assert!(remove_value(&mut doc, "tui.keymap.down"));
+        assert!(!doc.to_string().contains("[tui"), "{}", doc);
+        assert!(!doc.to_string().contains("after_secs"));
+    }
+
+    #[test]
+    fn test_flatten_and_lookup() {
+        let config = Config { max_entries: Some(10), ..Config::default() };
+        let table = effective(&config).unwrap();
+        let pairs = flatten(&table);
+        assert!(pairs.contains(&("max_entries".to_string(), toml::Value::Integer(10))));
+        assert!(pairs.contains(&("polling.min_interval_ms".to_string(), toml::Value::Integer(500))));
+        assert_eq!(lookup(&table, "tui.background"), Some(&toml::Value::String("auto".to_string())));
+        assert_eq!(lookup(&table, "max_age_days"), None);
+    }
+}
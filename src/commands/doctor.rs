@@ -0,0 +1,261 @@
+use crate::config::{expand_home, ConfigManager};
+use crate::db::Database;
+use crate::error::Result;
+use crate::ipc;
+use crate::service::{self, ServiceManager};
+use std::path::Path;
+use std::process;
+
+/// Result of one check; failures say how to fix them.
+#[derive(Debug, PartialEq)]
+struct Finding {
+    message: String,
+    fix: Option<String>,
+}
+
+impl Finding {
+    fn ok(message: impl Into<String>) -> Self {
+        Finding { message: message.into(), fix: None }
+    }
+
+    fn fail(message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Finding { message: message.into(), fix: Some(fix.into()) }
+    }
+}
+
+/// Check the config, database, service definition and running daemon, printing a fix for each
+/// problem. Exits 1 when anything failed.
+pub async fn run_doctor() -> Result<()> {
+    let config = ConfigManager::new()?;
+    let mut findings = check_config(&config);
+
+    let db_path = config.get_db_path().or_else(|_| config.default_db_path())?;
+    findings.extend(check_database(&db_path));
+    if let Some(dir) = config.get_socket_path()?.parent() {
+        findings.extend(check_private(dir, "directory"));
+    }
+    if let Ok(path) = config.get_config_path()
+        && path.exists()
+    {
+        findings.extend(check_private(&path, "config file"));
+    }
+    findings.extend(check_service(&config).await);
+
+    println!("\nClippie Doctor");
+    println!("==============\n");
+    for finding in &findings {
+        match &finding.fix {
+            None => println!("✓ {}", finding.message),
+            Some(fix) => println!("✗ {}\n    → {}", finding.message, fix),
+        }
+    }
+
+    let failed = findings.iter().filter(|f| f.fix.is_some()).count();
+    if failed == 0 {
+        println!("\nNo problems found.\n");
+        Ok(())
+    } else {
+        println!("\n{} problem{} found.\n", failed, if failed == 1 { "" } else { "s" });
+        process::exit(1);
+    }
+}
+
+fn check_config(config: &ConfigManager) -> Vec<Finding> {
+    let Ok(path) = config.get_config_path() else {
+        return vec![Finding::fail("Could not determine the home directory", "Set $HOME")];
+    };
+    if !path.exists() {
+        return vec![Finding::ok("No config file; using the defaults")];
+    }
+    let settings = match config.load() {
+        Ok(settings) => settings,
+        Err(e) => {
+            return vec![Finding::fail(
+                format!("{} does not parse: {}", path.display(), e),
+                "Fix the setting it names, or move the file aside to start over from the defaults",
+            )];
+        }
+    };
+
+    let mut findings = vec![Finding::ok(format!("Config {} is valid", path.display()))];
+    for pattern in &settings.ignore_patterns {
+        if let Err(e) = regex::Regex::new(pattern) {
+            findings.push(Finding::fail(
+                format!("ignore_patterns entry {:?} is not a valid regex: {}", pattern, e),
+                "Fix or remove it in the config; the daemon skips it until then",
+            ));
+        }
+    }
+    if let Some(path) = &settings.db_path
+        && expand_home(path).is_ok_and(|path| !path.parent().is_some_and(Path::exists))
+    {
+        findings.push(Finding::fail(
+            format!("The folder for db_path {} does not exist", path.display()),
+            "Create it, or point db_path elsewhere with 'clippie setup'",
+        ));
+    }
+    findings
+}
+
+fn check_database(path: &Path) -> Vec<Finding> {
+    if !path.exists() {
+        return vec![Finding::fail(
+            format!("No database at {}", path.display()),
+            "Run 'clippie setup', or start the daemon to create it",
+        )];
+    }
+    if let Err(e) = std::fs::OpenOptions::new().read(true).write(true).open(path) {
+        return vec![Finding::fail(
+            format!("Can't read and write {}: {}", path.display(), e),
+            format!("chmod u+rw {}", path.display()),
+        )];
+    }
+    let db = match Database::open(path) {
+        Ok(db) => db,
+        Err(e) => {
+            return vec![Finding::fail(
+                format!("Can't open {}: {}", path.display(), e),
+                "Restore it from a backup, or move it aside and 'clippie import' an export into a fresh one",
+            )];
+        }
+    };
+    match db.integrity_check() {
+        Ok(problems) if problems.is_empty() => {
+            let count = db.count_entries().unwrap_or(0);
+            vec![Finding::ok(format!(
+                "Database {} passed the integrity check ({} {})",
+                path.display(),
+                count,
+                if count == 1 { "entry" } else { "entries" }
+            ))]
+        }
+        Ok(problems) => vec![Finding::fail(
+            format!("Database integrity check failed: {}", problems.join("; ")),
+            "Save what's readable with 'clippie export --format json', then import it into a fresh database",
+        )],
+        Err(e) => vec![Finding::fail(
+            format!("Database integrity check could not run: {}", e),
+            "Stop the daemon with 'clippie stop' and try again",
+        )],
+    }
+}
+
+/// Fail if other users can read or write `path`, which holds clipboard history or tokens.
+fn check_private(path: &Path, what: &str) -> Vec<Finding> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return vec![];
+    };
+    let mode = metadata.permissions().mode() & 0o777;
+    if mode & 0o077 == 0 {
+        return vec![];
+    }
+    let private = if metadata.is_dir() { "700" } else { "600" };
+    vec![Finding::fail(
+        format!("The {} {} is open to other users (mode {:o})", what, path.display(), mode),
+        format!("chmod {} {}", private, path.display()),
+    )]
+}
+
+async fn check_service(config: &ConfigManager) -> Vec<Finding> {
+    let mut findings: Vec<Finding> = service::legacy_agents()
+        .iter()
+        .map(|path| {
+            Finding::fail(
+                format!("{} was left by an older version under another label", path.display()),
+                "Run 'clippie install' to remove it and install the current agent",
+            )
+        })
+        .collect();
+    let manager = ServiceManager::detect();
+    if !manager.is_installed() {
+        findings.push(Finding::fail(
+            format!("The daemon is not installed as a {} service", manager.name()),
+            "Run 'clippie install'",
+        ));
+        return findings;
+    }
+    let path = manager.service_path().map(|p| p.display().to_string()).unwrap_or_default();
+    findings.push(Finding::ok(format!("{} service installed at {}", manager.name(), path)));
+
+    let this_binary = std::env::current_exe().and_then(|p| p.canonicalize()).ok();
+    match manager.installed_binary() {
+        None => findings.push(Finding::fail(
+            format!("{} names no daemon binary", path),
+            "Run 'clippie install' to rewrite it",
+        )),
+        Some(binary) if !binary.exists() => findings.push(Finding::fail(
+            format!("{} runs {}, which does not exist", path, binary.display()),
+            "Run 'clippie install' to point it at this binary",
+        )),
+        Some(binary) if binary.canonicalize().ok() != this_binary => findings.push(Finding::fail(
+            format!(
+                "{} runs {}, not this binary ({})",
+                path,
+                binary.display(),
+                this_binary.as_deref().unwrap_or(Path::new("?")).display()
+            ),
+            "Run 'clippie install' from the clippie you mean to keep",
+        )),
+        Some(_) => {}
+    }
+
+    let status = manager.status();
+    let answering = ipc::send(config, ipc::Request::Stats).await.is_ok();
+    findings.push(match (status.running, answering) {
+        (true, true) => Finding::ok(match status.pid {
+            Some(pid) => format!("Daemon running (PID {}) and answering", pid),
+            None => "Daemon running and answering".to_string(),
+        }),
+        (true, false) => Finding::fail(
+            "The daemon is running but not answering on ~/.clippie/daemon.sock",
+            "Run 'clippie restart'",
+        ),
+        (false, true) => Finding::fail(
+            format!("A daemon is answering, but not one {} runs", manager.name()),
+            "Stop the one started by hand and run 'clippie start', so it comes back after crashes and logins",
+        ),
+        (false, _) if status.throttled => Finding::fail(
+            format!("{} keeps restarting the daemon after it exits", manager.name()),
+            "Look for the cause in ~/.clippie/daemon.err",
+        ),
+        (false, _) => Finding::fail(
+            format!("The daemon is not loaded in {}", manager.name()),
+            "Run 'clippie start'",
+        ),
+    });
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_check_private() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clipboard.db");
+        std::fs::write(&path, "").unwrap();
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let findings = check_private(&path, "database");
+        assert_eq!(findings[0].fix.as_deref(), Some(format!("chmod 600 {}", path.display()).as_str()));
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+        assert!(check_private(&path, "database").is_empty());
+    }
+
+    #[test]
+    fn test_check_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clipboard.db");
+        assert!(check_database(&path)[0].fix.is_some());
+
+        Database::open(&path).unwrap().insert_entry("hello", "h1").unwrap();
+        let findings = check_database(&path);
+        assert!(findings[0].fix.is_none(), "{:?}", findings);
+        assert!(findings[0].message.contains("(1 entry)"));
+    }
+}
@@ -0,0 +1,326 @@
+use crate::cli::ExportFormat;
+use crate::clipboard::hash_content;
+use crate::config::ConfigManager;
+use crate::db::{ClipboardEntry, Database, Snippet};
+use crate::error::{CliError, Result};
+use crate::commands::list::parse_since;
+use chrono::{Local, Utc};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const SNIPPET_NAME_WIDTH: usize = 40;
+/// Entries exported by the HTML format unless `--limit` says otherwise.
+const DEFAULT_HTML_LIMIT: usize = 50;
+
+pub async fn run_export(
+    format: ExportFormat,
+    out: &Path,
+    limit: Option<usize>,
+    since: Option<&str>,
+    until: Option<&str>,
+) -> Result<()> {
+    let since = since.map(parse_since).transpose()?;
+    let until = until.map(parse_since).transpose()?;
+    let config = ConfigManager::new()?;
+    let db_path = config.get_db_path()?;
+    if !db_path.exists() {
+        eprintln!("Error: Database not found at {}", db_path.display());
+        return Ok(());
+    }
+
+    let db = Database::open(&db_path)?;
+    let mut entries = db.with_full_content(db.get_all_entries()?)?;
+    let in_range = |at| since.is_none_or(|t| at >= t) && until.is_none_or(|t| at < t);
+    let mut snippets = snippets_to_export(db.get_snippets()?, &entries);
+    snippets.retain(|s| in_range(s.created_at));
+    entries.retain(|e| in_range(e.last_copied));
+    let limit = match format {
+        ExportFormat::Html => Some(limit.unwrap_or(DEFAULT_HTML_LIMIT)),
+        _ => limit,
+    };
+    if let Some(limit) = limit {
+        entries.truncate(limit);
+        snippets.truncate(limit);
+    }
+
+    match format {
+        ExportFormat::AlfredSnippets => write_alfred_snippets(&snippets, out)?,
+        ExportFormat::RaycastSnippets => fs::write(out, serde_json::to_string_pretty(&raycast_snippets(&snippets))?)?,
+        ExportFormat::Html => fs::write(out, html_report(&entries))?,
+        ExportFormat::Json => fs::write(out, serde_json::to_string_pretty(&json_dump(&entries, &db.get_copy_counts()?))?)?,
+        ExportFormat::Csv => fs::write(out, csv_dump(&entries, &db.get_copy_counts()?))?,
+        ExportFormat::Txt => fs::write(out, txt_dump(&entries, &db.get_copy_counts()?))?,
+    }
+
+    let exported = match format {
+        ExportFormat::AlfredSnippets | ExportFormat::RaycastSnippets => format!("{} snippets", snippets.len()),
+        _ => format!("{} entries", entries.len()),
+    };
+    println!("✓ Exported {} to {}", exported, out.display());
+    Ok(())
+}
+
+/// What the snippet formats export: the saved snippets by name, then template entries named by
+/// their first line, newest first. Encrypted ones are left out, as their text is ciphertext.
+fn snippets_to_export(saved: Vec<Snippet>, entries: &[ClipboardEntry]) -> Vec<Snippet> {
+    let templates: Vec<Snippet> = entries
+        .iter()
+        .filter(|e| e.template && !saved.iter().any(|s| s.content == e.content))
+        .map(|e| Snippet { name: snippet_name(&e.content), content: e.content.clone(), encrypted: e.encrypted, created_at: e.last_copied })
+        .collect();
+    saved.into_iter().chain(templates).filter(|s| !s.encrypted).collect()
+}
+
+/// Short display name for a snippet: the first line of the entry, truncated.
+fn snippet_name(content: &str) -> String {
+    let first_line = content.trim().lines().next().unwrap_or_default();
+    if first_line.chars().count() > SNIPPET_NAME_WIDTH {
+        let truncated: String = first_line.chars().take(SNIPPET_NAME_WIDTH - 1).collect();
+        format!("{truncated}…")
+    } else {
+        first_line.to_string()
+    }
+}
+
+fn raycast_snippets(snippets: &[Snippet]) -> serde_json::Value {
+    snippets.iter().map(|s| json!({ "name": s.name, "text": s.content })).collect()
+}
+
+fn alfred_snippet(snippet: &Snippet) -> (String, serde_json::Value) {
+    let uid = hash_content(&snippet.content);
+    let snippet = json!({
+        "alfredsnippet": {
+            "snippet": snippet.content,
+            "uid": uid,
+            "name": snippet.name,
+            "keyword": "",
+        }
+    });
+    (format!("{}.json", uid), snippet)
+}
+
+/// Alfred imports a zip archive of one JSON file per snippet.
+fn write_alfred_snippets(snippets: &[Snippet], out: &Path) -> Result<()> {
+    let staging = std::env::temp_dir().join(format!("clippie-alfred-{}", std::process::id()));
+    fs::create_dir_all(&staging)?;
+
+    let mut files = Vec::with_capacity(snippets.len());
+    for snippet in snippets {
+        let (file_name, snippet) = alfred_snippet(snippet);
+        let path = staging.join(file_name);
+        fs::write(&path, serde_json::to_string_pretty(&snippet)?)?;
+        files.push(path);
+    }
+
+    if out.exists() {
+        fs::remove_file(out)?;
+    }
+    let status = Command::new("zip").arg("-jq").arg(out).args(&files).status();
+    let _ = fs::remove_dir_all(&staging);
+
+    match status {
+        Ok(s) if s.success() => Ok(()),
+        Ok(_) => Err(CliError::ExportError("zip failed to create the snippet archive".to_string())),
+        Err(e) => Err(CliError::ExportError(format!("Failed to run zip: {}", e))),
+    }
+}
+
+/// Same shape as the daemon's backup snapshots, so `clippie import` reads either.
+pub(crate) fn json_dump(entries: &[ClipboardEntry], counts: &HashMap<i64, i64>) -> serde_json::Value {
+    let entries: Vec<_> = entries
+        .iter()
+        .map(|e| {
+            let mut value = e.to_json();
+            value["copy_count"] = json!(counts.get(&e.id).copied().unwrap_or(1));
+            value
+        })
+        .collect();
+    json!({ "exported_at": Utc::now().to_rfc3339(), "entries": entries })
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// RFC 4180 CSV with a header row; encrypted entries keep their ciphertext.
+fn csv_dump(entries: &[ClipboardEntry], counts: &HashMap<i64, i64>) -> String {
+    let mut csv = String::from("id,created_at,last_copied,copy_count,encrypted,language,source_app,content\r\n");
+    for e in entries {
+        let fields = [
+            e.id.to_string(),
+            e.created_at.to_rfc3339(),
+            e.last_copied.to_rfc3339(),
+            counts.get(&e.id).copied().unwrap_or(1).to_string(),
+            e.encrypted.to_string(),
+            e.language.clone().unwrap_or_default(),
+            e.source_app.clone().unwrap_or_default(),
+            e.content.clone(),
+        ];
+        csv.push_str(&fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// Readable dump: a dated header line per entry, then its text.
+fn txt_dump(entries: &[ClipboardEntry], counts: &HashMap<i64, i64>) -> String {
+    entries
+        .iter()
+        .map(|e| {
+            format!(
+                "─── {} · copied {}×\n{}\n",
+                e.last_copied.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+                counts.get(&e.id).copied().unwrap_or(1),
+                e.display_content()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Clippie history</title>
+<style>
+  body { font-family: -apple-system, system-ui, sans-serif; max-width: 900px; margin: 2rem auto; padding: 0 1rem; color: #222; }
+  input { width: 100%; padding: .5rem; font-size: 1rem; margin-bottom: 1rem; box-sizing: border-box; }
+  details { border-bottom: 1px solid #ddd; padding: .4rem 0; }
+  summary { cursor: pointer; white-space: nowrap; overflow: hidden; text-overflow: ellipsis; }
+  time { color: #888; font-size: .85rem; margin-right: .75rem; }
+  pre { white-space: pre-wrap; word-break: break-word; background: #f6f6f6; padding: .75rem; border-radius: 4px; }
+</style>
+</head>
+<body>
+<h1>Clippie history</h1>
+<p>{{count}} entries exported {{exported}}</p>
+<input id="filter" type="search" placeholder="Filter entries…" autofocus>
+{{entries}}
+<script>
+  document.getElementById('filter').addEventListener('input', (e) => {
+    const query = e.target.value.toLowerCase();
+    document.querySelectorAll('details').forEach((d) => {
+      d.hidden = !d.dataset.content.includes(query);
+    });
+  });
+</script>
+</body>
+</html>
+"#;
+
+/// A standalone page: one collapsible `<details>` per entry plus a client-side filter.
+fn html_report(entries: &[ClipboardEntry]) -> String {
+    let items: String = entries
+        .iter()
+        .map(|e| {
+            format!(
+                "<details data-content=\"{}\"><summary><time>{}</time>{}</summary><pre>{}</pre></details>\n",
+                escape_html(&e.content.to_lowercase()),
+                e.last_copied.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+                escape_html(&snippet_name(&e.content)),
+                escape_html(&e.content)
+            )
+        })
+        .collect();
+
+    HTML_TEMPLATE
+        .replace("{{count}}", &entries.len().to_string())
+        .replace("{{exported}}", &Local::now().format("%Y-%m-%d %H:%M").to_string())
+        .replace("{{entries}}", &items)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kind::Kind;
+
+    fn entry(content: &str) -> ClipboardEntry {
+        ClipboardEntry {
+            id: 1,
+            content: content.to_string(),
+            created_at: Utc::now(),
+            last_copied: Utc::now(),
+            encrypted: false,
+            template: false,
+            language: None,
+            source_app: None,
+            sensitive: false,
+            kind: Kind::Text,
+            title: None,
+            truncated: false,
+        }
+    }
+
+    #[test]
+    fn test_snippet_name_uses_first_line() {
+        assert_eq!(snippet_name("  git status\ngit diff"), "git status");
+        assert_eq!(snippet_name(&"x".repeat(80)).chars().count(), SNIPPET_NAME_WIDTH);
+    }
+
+    fn snippet(name: &str, content: &str) -> Snippet {
+        Snippet { name: name.to_string(), content: content.to_string(), encrypted: false, created_at: Utc::now() }
+    }
+
+    #[test]
+    fn test_snippets_to_export() {
+        let template = |content| ClipboardEntry { template: true, ..entry(content) };
+        let saved = vec![snippet("greet", "hello\nworld"), Snippet { encrypted: true, ..snippet("key", "-----BEGIN AGE") }];
+        let entries = [entry("recent"), template("Dear {{name}},\nthanks"), template("hello\nworld")];
+
+        let names: Vec<String> = snippets_to_export(saved, &entries).into_iter().map(|s| s.name).collect();
+        assert_eq!(names, ["greet", "Dear {{name}},"]);
+    }
+
+    #[test]
+    fn test_raycast_snippets() {
+        let value = raycast_snippets(&[snippet("greet", "hello\nworld")]);
+        assert_eq!(value[0]["name"], "greet");
+        assert_eq!(value[0]["text"], "hello\nworld");
+    }
+
+    #[test]
+    fn test_html_report_escapes_content() {
+        let html = html_report(&[entry("<script>alert(\"x\")</script>")]);
+        assert!(html.contains("<pre>&lt;script&gt;alert(&quot;x&quot;)&lt;/script&gt;</pre>"));
+        assert!(html.contains("1 entries exported"));
+        assert!(!html.contains("{{entries}}"));
+    }
+
+    #[test]
+    fn test_csv_and_json_dumps() {
+        let e = ClipboardEntry { id: 7, ..entry("say \"hi\",\nbye") };
+        let counts = HashMap::from([(7, 3)]);
+
+        let csv = csv_dump(std::slice::from_ref(&e), &counts);
+        let row = csv.lines().nth(1).unwrap();
+        assert!(row.starts_with("7,"));
+        assert!(row.contains(",3,false,,,\"say \"\"hi\"\","));
+
+        let dump = json_dump(&[e], &counts);
+        assert_eq!(dump["entries"][0]["copy_count"], 3);
+        assert_eq!(dump["entries"][0]["content"], "say \"hi\",\nbye");
+    }
+
+    #[test]
+    fn test_alfred_snippet() {
+        let (file_name, value) = alfred_snippet(&snippet("greet", "hello"));
+        assert!(file_name.ends_with(".json"));
+        assert_eq!(value["alfredsnippet"]["snippet"], "hello");
+        assert_eq!(value["alfredsnippet"]["name"], "greet");
+    }
+}
@@ -0,0 +1,93 @@
+use crate::clipboard;
+use crate::config::{Config, ConfigManager};
+use crate::db::{ClipboardEntry, Database};
+use crate::error::{CliError, Result};
+use crate::{secrets, urls};
+use std::io::Write;
+
+/// Print an entry to stdout exactly as stored, without a trailing newline of its own.
+pub async fn run_get(target: usize, by_id: bool) -> Result<()> {
+    let content = resolve(target, by_id)?;
+    let mut stdout = std::io::stdout();
+    stdout.write_all(content.as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Put an entry back on the clipboard, as picking it in the TUI would.
+pub async fn run_copy(target: usize, by_id: bool) -> Result<()> {
+    let config = ConfigManager::new()?;
+    let db = Database::open(config.get_db_path()?)?;
+    let entry = find(&db, target, by_id)?;
+    let content = copy_content(&entry.content, entry.encrypted, &config.load().unwrap_or_default())?;
+    clipboard::set_clipboard_entry(&content, entry.kind, &db.flavors_for(entry.id, &content)?)
+}
+
+/// Copy the entry a line on stdin stands for, so `clippie pick | fzf | clippie copy --stdin` works.
+pub async fn run_copy_stdin() -> Result<()> {
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    if line.trim().is_empty() {
+        // fzf and rofi print nothing when the pick is cancelled.
+        return Ok(());
+    }
+    super::pick::copy_picked(&line)
+}
+
+/// Decrypted, copy-stage content of the entry `target` points at: the n-th most recent
+/// (1 is the latest) or, with `by_id`, the entry with that database id.
+pub(crate) fn resolve(target: usize, by_id: bool) -> Result<String> {
+    let config = ConfigManager::new()?;
+    let db = Database::open(config.get_db_path()?)?;
+    let entry = find(&db, target, by_id)?;
+    copy_content(&entry.content, entry.encrypted, &config.load().unwrap_or_default())
+}
+
+/// Stored `content` as it goes on the clipboard: decrypted, or with the copy-stage URL rules applied.
+pub(crate) fn copy_content(content: &str, encrypted: bool, settings: &Config) -> Result<String> {
+    if encrypted {
+        let Some(encryption) = &settings.secret_encryption else {
+            return Err(CliError::ConfigError("Entry is encrypted but \"secret_encryption\" is not configured".to_string()));
+        };
+        return secrets::decrypt(encryption, content);
+    }
+    Ok(urls::clean(content, &settings.url_rules, urls::Stage::Copy))
+}
+
+pub(crate) fn find(db: &Database, target: usize, by_id: bool) -> Result<ClipboardEntry> {
+    let entry = if by_id {
+        db.get_entry(target as i64)?
+    } else if target == 0 {
+        return Err(CliError::InvalidInput("Index starts at 1 for the most recent entry".to_string()));
+    } else {
+        db.get_entry_by_recency(target - 1)?
+    };
+    entry.ok_or_else(|| match by_id {
+        true => CliError::InvalidInput(format!("No entry with id {}", target)),
+        false => CliError::InvalidInput(format!("History has fewer than {} entries", target)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::NewEntry;
+    use chrono::{Duration, Utc};
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_find_by_index_and_id() {
+        let tmp = NamedTempFile::new().unwrap();
+        let db = Database::open(tmp.path()).unwrap();
+        let now = Utc::now();
+        let copied = |content: &str, ago| NewEntry { last_copied: now - Duration::minutes(ago), ..NewEntry::new(content) };
+        db.insert_many(&[copied("oldest", 10), copied("latest", 0), copied("middle", 5)]).unwrap();
+
+        assert_eq!(find(&db, 1, false).unwrap().content, "latest");
+        assert_eq!(find(&db, 2, false).unwrap().content, "middle");
+        assert_eq!(find(&db, 1, true).unwrap().content, "oldest");
+        assert!(find(&db, 0, false).is_err());
+        assert!(find(&db, 4, false).is_err());
+        assert!(find(&db, 99, true).is_err());
+    }
+}
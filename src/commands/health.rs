@@ -0,0 +1,71 @@
+use crate::config::ConfigManager;
+use crate::error::Result;
+use serde_json::Value;
+use std::process;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Exit codes: 0 capturing, 1 capture stalled, 2 daemon unreachable.
+pub async fn run_health() -> Result<()> {
+    let config = ConfigManager::new()?.load()?;
+    let Some(port) = config.metrics_port else {
+        eprintln!("Error: Health checks need \"metrics_port\" set in the config.");
+        process::exit(2);
+    };
+
+    let Some((status, body)) = fetch_healthz(port).await else {
+        println!("✗ Daemon unreachable on 127.0.0.1:{}", port);
+        process::exit(2);
+    };
+
+    let since_poll = body["seconds_since_last_poll"]
+        .as_i64()
+        .map(|secs| format!("last poll {}s ago", secs))
+        .unwrap_or_else(|| "no successful poll yet".to_string());
+
+    if status == 200 {
+        println!("✓ Capturing ({})", since_poll);
+        Ok(())
+    } else {
+        println!("✗ Capture stalled ({})", since_poll);
+        process::exit(1);
+    }
+}
+
+async fn fetch_healthz(port: u16) -> Option<(u16, Value)> {
+    let request = async {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.ok()?;
+        stream
+            .write_all(b"GET /healthz HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .ok()?;
+        let mut raw = String::new();
+        stream.read_to_string(&mut raw).await.ok()?;
+        parse_response(&raw)
+    };
+    timeout(REQUEST_TIMEOUT, request).await.ok().flatten()
+}
+
+fn parse_response(raw: &str) -> Option<(u16, Value)> {
+    let (head, body) = raw.split_once("\r\n\r\n")?;
+    let status = head.split_whitespace().nth(1)?.parse().ok()?;
+    Some((status, serde_json::from_str(body).ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response() {
+        let raw = "HTTP/1.1 503 Service Unavailable\r\nContent-Type: application/json\r\n\r\n{\"status\":\"stale\"}";
+        let (status, body) = parse_response(raw).unwrap();
+        assert_eq!(status, 503);
+        assert_eq!(body["status"], "stale");
+        assert!(parse_response("garbage").is_none());
+    }
+}
@@ -0,0 +1,301 @@
+use crate::cli::ImportFormat;
+use crate::clipboard::{duplicate_hash, hash_content};
+use crate::config::{ConfigManager, DuplicatePolicy};
+use crate::db::{Database, NewEntry};
+use crate::error::{CliError, Result};
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{Connection, OpenFlags};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Merge entries from another machine or tool into the history. Content already present is
+/// merged by hash: earliest creation, latest copy and the sum of both copy counts.
+pub async fn run_import(file: &Path, format: ImportFormat) -> Result<()> {
+    let config = ConfigManager::new()?;
+    let duplicates = config.load().unwrap_or_default().duplicates;
+    let bytes = fs::read(file)?;
+    let text = || String::from_utf8(bytes.clone()).map_err(|_| CliError::InvalidInput("File is not UTF-8 text".to_string()));
+
+    let format = match format {
+        ImportFormat::Auto => detect_format(file, &bytes),
+        format => format,
+    };
+    let entries = match format {
+        ImportFormat::Json => parse_json(&text()?, &duplicates)?,
+        ImportFormat::Maccy => parse_maccy(file, &duplicates)?,
+        ImportFormat::Flycut => parse_flycut(file, &duplicates)?,
+        ImportFormat::Copyq => parse_copyq(&text()?, &duplicates)?,
+        ImportFormat::Lines | ImportFormat::Auto => parse_lines(&text()?, &duplicates),
+    };
+
+    let db = Database::open(config.get_db_path()?)?;
+    let stats = db.insert_many(&entries)?;
+    println!(
+        "✓ Imported {} entries from {} ({} merged into existing history)",
+        stats.inserted + stats.merged,
+        file.display(),
+        stats.merged
+    );
+    Ok(())
+}
+
+fn detect_format(file: &Path, bytes: &[u8]) -> ImportFormat {
+    let extension = file.extension().and_then(|ext| ext.to_str()).unwrap_or("").to_ascii_lowercase();
+    let start = String::from_utf8_lossy(&bytes[..bytes.len().min(64)]).trim_start().to_string();
+    if bytes.starts_with(b"SQLite format 3\0") {
+        ImportFormat::Maccy
+    } else if extension == "plist" || bytes.starts_with(b"bplist") || start.starts_with("<?xml") {
+        ImportFormat::Flycut
+    } else if extension == "json" || start.starts_with(['{', '[']) {
+        ImportFormat::Json
+    } else {
+        ImportFormat::Lines
+    }
+}
+
+/// `clippie export --format json`, a backup snapshot, `clippie list --json`, or a plain array of strings.
+fn parse_json(text: &str, duplicates: &DuplicatePolicy) -> Result<Vec<NewEntry>> {
+    let value: Value = serde_json::from_str(text)?;
+    let items = match &value {
+        Value::Array(items) => items,
+        Value::Object(_) => value["entries"]
+            .as_array()
+            .ok_or_else(|| CliError::InvalidInput("Expected an \"entries\" array".to_string()))?,
+        _ => return Err(CliError::InvalidInput("Expected a JSON array or object".to_string())),
+    };
+    Ok(items.iter().filter_map(|item| json_entry(item, duplicates)).collect())
+}
+
+fn json_entry(item: &Value, duplicates: &DuplicatePolicy) -> Option<NewEntry> {
+    let content = item.as_str().or_else(|| item["content"].as_str())?;
+    if content.trim().is_empty() {
+        return None;
+    }
+    let mut entry = NewEntry::new(content);
+    let encrypted = item["encrypted"].as_bool().unwrap_or(false);
+    entry.content_hash = if encrypted { hash_content(content) } else { duplicate_hash(content, duplicates) };
+    entry.encrypted = encrypted;
+    if let Some(created_at) = timestamp(&item["created_at"]) {
+        entry.created_at = created_at;
+    }
+    entry.last_copied = timestamp(&item["last_copied"]).unwrap_or(entry.created_at);
+    entry.created_at = entry.created_at.min(entry.last_copied);
+    entry.copy_count = item["copy_count"].as_i64().unwrap_or(1).max(1);
+    entry.template = item["template"].as_bool().unwrap_or(false);
+    entry.language = match item["language"].as_str() {
+        _ if encrypted => None,
+        Some(language) => crate::language::normalize(language).or(entry.language),
+        None => entry.language,
+    };
+    entry.source_app = item["source_app"].as_str().map(str::to_string);
+    Some(entry)
+}
+
+/// RFC 3339 text or unix seconds.
+fn timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::String(text) => DateTime::parse_from_rfc3339(text).ok().map(|t| t.with_timezone(&Utc)),
+        Value::Number(n) => DateTime::from_timestamp(n.as_i64()?, 0),
+        _ => None,
+    }
+}
+
+/// Seconds between the Unix epoch and Core Data's reference date, 2001-01-01.
+const CORE_DATA_EPOCH: f64 = 978_307_200.0;
+
+/// Maccy's `Storage.sqlite` (under `~/Library/Containers/org.p0deje.Maccy/Data/Library/Application Support/Maccy/`).
+/// Only the plain-text representation of each item is imported.
+fn parse_maccy(file: &Path, duplicates: &DuplicatePolicy) -> Result<Vec<NewEntry>> {
+    let conn = Connection::open_with_flags(file, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut stmt = conn.prepare(
+        "SELECT CAST(c.ZVALUE AS TEXT), i.ZFIRSTCOPIEDAT, i.ZLASTCOPIEDAT, i.ZNUMBEROFCOPIES, i.ZAPPLICATION
+         FROM ZHISTORYITEM i JOIN ZHISTORYITEMCONTENT c ON c.ZITEM = i.Z_PK
+         WHERE c.ZTYPE = 'public.utf8-plain-text'",
+    )?;
+    let core_data_time = |seconds: Option<f64>| seconds.and_then(|s| DateTime::from_timestamp((s + CORE_DATA_EPOCH) as i64, 0));
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, Option<String>>(0)?,
+            core_data_time(row.get(1)?),
+            core_data_time(row.get(2)?),
+            row.get::<_, Option<i64>>(3)?,
+            row.get::<_, Option<String>>(4)?,
+        ))
+    })?;
+
+    let mut entries = Vec::new();
+    for row in rows {
+        let (Some(content), first, last, count, bundle_id) = row? else {
+            continue;
+        };
+        if content.trim().is_empty() {
+            continue;
+        }
+        let mut entry = plain_entry(&content, duplicates);
+        entry.last_copied = last.or(first).unwrap_or(entry.last_copied);
+        entry.created_at = first.unwrap_or(entry.last_copied).min(entry.last_copied);
+        entry.copy_count = count.unwrap_or(1).max(1);
+        entry.source_app = bundle_id.as_deref().and_then(|id| id.rsplit('.').next()).map(str::to_string);
+        entry.source_bundle_id = bundle_id;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Flycut's preferences plist (`~/Library/Preferences/com.generalarcade.flycut.plist`), whose
+/// `store.jcList` holds the clippings. `plutil` turns binary or XML plists into JSON.
+fn parse_flycut(file: &Path, duplicates: &DuplicatePolicy) -> Result<Vec<NewEntry>> {
+    let output = Command::new("plutil")
+        .args(["-convert", "json", "-o", "-"])
+        .arg(file)
+        .output()
+        .map_err(|e| CliError::InvalidInput(format!("Failed to run plutil: {}", e)))?;
+    if !output.status.success() {
+        return Err(CliError::InvalidInput(format!(
+            "plutil could not read {}: {}",
+            file.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    flycut_entries(&serde_json::from_slice(&output.stdout)?, duplicates)
+}
+
+fn flycut_entries(plist: &Value, duplicates: &DuplicatePolicy) -> Result<Vec<NewEntry>> {
+    let clippings = plist["store"]["jcList"]
+        .as_array()
+        .ok_or_else(|| CliError::InvalidInput("No Flycut clippings (store.jcList) found".to_string()))?;
+    let now = Utc::now();
+    Ok(clippings
+        .iter()
+        .enumerate()
+        .filter_map(|(i, clipping)| {
+            let content = clipping["Contents"].as_str().filter(|c| !c.trim().is_empty())?;
+            let mut entry = plain_entry(content, duplicates);
+            // Newest first; older Flycut versions don't store a timestamp.
+            entry.last_copied = timestamp(&clipping["Timestamp"]).unwrap_or(now - Duration::seconds(i as i64));
+            entry.created_at = entry.last_copied;
+            entry.source_app = clipping["AppLocalizedName"].as_str().map(str::to_string);
+            entry.source_bundle_id = clipping["AppBundleURL"].as_str().and_then(bundle_name).map(str::to_string);
+            Some(entry)
+        })
+        .collect())
+}
+
+/// `Foo.app` from a Flycut `file:///Applications/Foo.app/` bundle URL.
+fn bundle_name(url: &str) -> Option<&str> {
+    url.trim_end_matches('/').rsplit('/').next().filter(|name| !name.is_empty())
+}
+
+/// A CopyQ dump: a JSON array of item texts, or of item objects keyed by MIME type, e.g. from
+/// `copyq 'var items = []; for (var i = 0; i < size(); ++i) items.push(str(read(i))); print(JSON.stringify(items))'`.
+fn parse_copyq(text: &str, duplicates: &DuplicatePolicy) -> Result<Vec<NewEntry>> {
+    let items: Vec<Value> = serde_json::from_str(text)?;
+    let now = Utc::now();
+    Ok(items
+        .iter()
+        .filter_map(|item| item.as_str().or_else(|| item["text/plain"].as_str()))
+        .filter(|content| !content.trim().is_empty())
+        .enumerate()
+        .map(|(i, content)| {
+            // CopyQ lists its newest item first and doesn't export times.
+            let copied = now - Duration::seconds(i as i64);
+            NewEntry { created_at: copied, last_copied: copied, ..plain_entry(content, duplicates) }
+        })
+        .collect())
+}
+
+fn plain_entry(content: &str, duplicates: &DuplicatePolicy) -> NewEntry {
+    NewEntry { content_hash: duplicate_hash(content, duplicates), ..NewEntry::new(content) }
+}
+
+/// One entry per non-empty line, the first line being the most recent.
+fn parse_lines(text: &str, duplicates: &DuplicatePolicy) -> Vec<NewEntry> {
+    let now = Utc::now();
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            let copied = now - Duration::seconds(i as i64);
+            NewEntry { created_at: copied, last_copied: copied, ..plain_entry(line, duplicates) }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_parse_export_json() {
+        let text = r#"{"exported_at": "2024-05-02T00:00:00Z", "entries": [
+            {"content": "SELECT 1", "created_at": "2024-05-01T10:00:00Z", "last_copied": 1714600000,
+             "copy_count": 3, "template": true, "language": "sql", "source_app": "TablePlus"},
+            {"content": "  "},
+            "plain string"
+        ]}"#;
+        let entries = parse_json(text, &DuplicatePolicy::default()).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].copy_count, 3);
+        assert_eq!(entries[0].last_copied.timestamp(), 1714600000);
+        assert_eq!(entries[0].language, Some("sql"));
+        assert!(entries[0].template);
+        assert_eq!(entries[1].content, "plain string");
+        assert!(parse_json("42", &DuplicatePolicy::default()).is_err());
+    }
+
+    #[test]
+    fn test_parse_maccy_store() {
+        let tmp = NamedTempFile::new().unwrap();
+        let conn = Connection::open(tmp.path()).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE ZHISTORYITEM (Z_PK INTEGER PRIMARY KEY, ZFIRSTCOPIEDAT TIMESTAMP, ZLASTCOPIEDAT TIMESTAMP,
+                                        ZNUMBEROFCOPIES INTEGER, ZAPPLICATION VARCHAR);
+             CREATE TABLE ZHISTORYITEMCONTENT (Z_PK INTEGER PRIMARY KEY, ZITEM INTEGER, ZTYPE VARCHAR, ZVALUE BLOB);
+             INSERT INTO ZHISTORYITEM VALUES (1, 700000000.5, 700000100.0, 4, 'com.apple.Safari');
+             INSERT INTO ZHISTORYITEMCONTENT VALUES (1, 1, 'public.utf8-plain-text', CAST('hello maccy' AS BLOB));
+             INSERT INTO ZHISTORYITEMCONTENT VALUES (2, 1, 'public.html', CAST('<b>hello</b>' AS BLOB));",
+        )
+        .unwrap();
+        drop(conn);
+
+        assert_eq!(detect_format(tmp.path(), &fs::read(tmp.path()).unwrap()), ImportFormat::Maccy);
+        let entries = parse_maccy(tmp.path(), &DuplicatePolicy::default()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].content, "hello maccy");
+        assert_eq!(entries[0].copy_count, 4);
+        assert_eq!(entries[0].last_copied.timestamp(), 978_307_200 + 700_000_100);
+        assert_eq!(entries[0].source_app.as_deref(), Some("Safari"));
+    }
+
+    #[test]
+    fn test_flycut_and_copyq_dumps() {
+        let plist = serde_json::json!({"store": {"jcList": [
+            {"Contents": "newest", "AppLocalizedName": "Terminal", "AppBundleURL": "file:///System/Applications/Utilities/Terminal.app/"},
+            {"Contents": "older", "Timestamp": 1700000000}
+        ]}});
+        let entries = flycut_entries(&plist, &DuplicatePolicy::default()).unwrap();
+        assert_eq!(entries[0].source_app.as_deref(), Some("Terminal"));
+        assert_eq!(entries[0].source_bundle_id.as_deref(), Some("Terminal.app"));
+        assert_eq!(entries[1].last_copied.timestamp(), 1700000000);
+
+        let items = parse_copyq(r#"["first", {"text/plain": "second"}, {"image/png": "..."}]"#, &DuplicatePolicy::default()).unwrap();
+        assert_eq!(items.iter().map(|e| e.content.as_str()).collect::<Vec<_>>(), vec!["first", "second"]);
+        assert!(items[0].last_copied > items[1].last_copied);
+    }
+
+    #[test]
+    fn test_import_merges_with_existing_history() {
+        let tmp = NamedTempFile::new().unwrap();
+        let db = Database::open(tmp.path()).unwrap();
+        db.insert_entry("git status", &hash_content("git status")).unwrap();
+
+        let entries = parse_lines("git status\n\ncargo test\n", &DuplicatePolicy::default());
+        assert!(entries[0].last_copied > entries[1].last_copied);
+        let stats = db.insert_many(&entries).unwrap();
+        assert_eq!((stats.inserted, stats.merged), (1, 1));
+        assert_eq!(db.count_entries().unwrap(), 2);
+    }
+}
@@ -0,0 +1,72 @@
+use crate::config::ConfigManager;
+use crate::error::{CliError, Result};
+use chrono::{Local, Utc};
+use std::time::Duration;
+
+pub async fn run_incognito(duration: Option<String>) -> Result<()> {
+    let config = ConfigManager::new()?;
+
+    let Some(duration) = duration else {
+        config.set_incognito(None)?;
+        println!("Incognito on. Nothing will be saved until 'clippie resume'.");
+        return Ok(());
+    };
+
+    let duration = parse_duration(&duration)?;
+    let until = Utc::now()
+        + chrono::Duration::from_std(duration).map_err(|_| CliError::InvalidInput("Duration is too long".to_string()))?;
+    config.set_incognito(Some(until))?;
+    println!(
+        "Incognito on until {}. Nothing will be saved until then.",
+        until.with_timezone(&Local).format("%H:%M")
+    );
+    Ok(())
+}
+
+/// Parse durations like `45s`, `30m`, `2h`, `1d` or `1h30m`. A bare number means minutes.
+pub fn parse_duration(text: &str) -> Result<Duration> {
+    let invalid = || CliError::InvalidInput(format!("Invalid duration '{}', expected e.g. 30m or 1h30m", text));
+    let text = text.trim();
+    if let Ok(minutes) = text.parse::<u64>() {
+        return Ok(Duration::from_secs(minutes * 60));
+    }
+
+    let mut total = 0u64;
+    let mut number = String::new();
+    for ch in text.chars() {
+        if ch.is_ascii_digit() {
+            number.push(ch);
+            continue;
+        }
+        let value: u64 = number.parse().map_err(|_| invalid())?;
+        number.clear();
+        total += value
+            * match ch {
+                's' => 1,
+                'm' => 60,
+                'h' => 3600,
+                'd' => 86400,
+                _ => return Err(invalid()),
+            };
+    }
+    if !number.is_empty() || total == 0 {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30m").unwrap(), Duration::from_secs(1800));
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+        assert_eq!(parse_duration("45s").unwrap(), Duration::from_secs(45));
+        assert_eq!(parse_duration("15").unwrap(), Duration::from_secs(900));
+        assert!(parse_duration("30x").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("1h30").is_err());
+    }
+}
@@ -0,0 +1,100 @@
+use super::get::{copy_content, find};
+use crate::clipboard;
+use crate::config::{ConfigManager, SensitivePolicy};
+use crate::db::{ClipboardEntry, Database};
+use crate::error::{CliError, Result};
+
+/// Characters of an entry shown on its line; the rest is cut off with `…`.
+const LINE_CHARS: usize = 300;
+
+/// Print one line per entry for fzf, rofi or a launcher, or with `query`, copy the entry a
+/// line printed earlier stands for.
+pub async fn run_pick(with_index: bool, limit: Option<usize>, query: Option<&str>) -> Result<()> {
+    match query {
+        Some(line) => copy_picked(line),
+        None => {
+            let (_, entries, show_sensitive) = open()?;
+            for entry in entries.iter().take(limit.unwrap_or(usize::MAX)) {
+                let line = pick_line(entry, show_sensitive);
+                if with_index {
+                    println!("{}\t{}", entry.id, line);
+                } else {
+                    println!("{}", line);
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Copy the entry that `line` from `clippie pick` stands for.
+pub(crate) fn copy_picked(line: &str) -> Result<()> {
+    let config = ConfigManager::new()?;
+    let (db, entries, show_sensitive) = open()?;
+    let picked = resolve_line(&entries, line, show_sensitive)
+        .ok_or_else(|| CliError::InvalidInput("No entry matches the picked line".to_string()))?;
+    // The list holds what was rendered; the clipboard gets the entry as stored.
+    let entry = find(&db, picked.id as usize, true)?;
+    let content = copy_content(&entry.content, entry.encrypted, &config.load().unwrap_or_default())?;
+    clipboard::set_clipboard_content(&content)
+}
+
+fn open() -> Result<(Database, Vec<ClipboardEntry>, bool)> {
+    let config = ConfigManager::new()?;
+    let db = Database::open(config.get_db_path()?)?;
+    let entries = db.get_all_entries()?;
+    let show_sensitive = config.load().is_ok_and(|c| c.sensitive_entries == SensitivePolicy::Show);
+    Ok((db, entries, show_sensitive))
+}
+
+/// The entry on one line: backslashes and line breaks escaped, secrets masked unless shown.
+fn pick_line(entry: &ClipboardEntry, show_sensitive: bool) -> String {
+    let text = if show_sensitive { entry.display_content().into() } else { entry.masked_content() };
+    let mut line: String = text.chars().take(LINE_CHARS).collect();
+    line = line.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r");
+    if text.chars().nth(LINE_CHARS).is_some() {
+        line.push('…');
+    }
+    line
+}
+
+/// The entry `line` was printed for: by its `id<TAB>` prefix when it has one, else the most
+/// recent entry that renders to the same line.
+fn resolve_line<'a>(entries: &'a [ClipboardEntry], line: &str, show_sensitive: bool) -> Option<&'a ClipboardEntry> {
+    let line = line.trim_end_matches(['\n', '\r']);
+    let by_index = line
+        .split_once('\t')
+        .and_then(|(id, _)| id.parse::<i64>().ok())
+        .and_then(|id| entries.iter().find(|entry| entry.id == id));
+    by_index.or_else(|| entries.iter().find(|entry| pick_line(entry, show_sensitive) == line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kind::Kind;
+    use chrono::{DateTime, Utc};
+
+    fn entry(id: i64, content: &str) -> ClipboardEntry {
+        let ts = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).unwrap();
+        ClipboardEntry { id, content: content.to_string(), created_at: ts, last_copied: ts, encrypted: false, template: false, language: None, source_app: None, sensitive: false, kind: Kind::Text, title: None, truncated: false }
+    }
+
+    #[test]
+    fn test_pick_line_escapes_and_cuts() {
+        assert_eq!(pick_line(&entry(1, "a\\b\nc\r\n"), false), "a\\\\b\\nc\\r\\n");
+        let long = pick_line(&entry(1, &"x".repeat(LINE_CHARS + 1)), false);
+        assert_eq!(long.chars().count(), LINE_CHARS + 1);
+        assert!(long.ends_with('…'));
+        assert_eq!(pick_line(&entry(1, &"x".repeat(LINE_CHARS)), false).chars().count(), LINE_CHARS);
+    }
+
+    #[test]
+    fn test_resolve_line() {
+        let entries = vec![entry(5, "two\nlines"), entry(3, "tab\there"), entry(2, "two\nlines")];
+        assert_eq!(resolve_line(&entries, "two\\nlines\n", false).unwrap().id, 5);
+        assert_eq!(resolve_line(&entries, "2\ttwo\\nlines", false).unwrap().id, 2);
+        assert_eq!(resolve_line(&entries, "tab\there", false).unwrap().id, 3);
+        assert!(resolve_line(&entries, "missing", false).is_none());
+    }
+}
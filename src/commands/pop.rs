@@ -0,0 +1,28 @@
+use super::get::copy_content;
+use crate::clipboard;
+use crate::config::ConfigManager;
+use crate::db::Database;
+use crate::error::{CliError, Result};
+
+/// Put the entry at the front of the paste queue on the clipboard and, with `paste`, paste it.
+/// Bound to a hotkey, repeated pops fill a form one field at a time.
+pub async fn run_pop(paste: bool, clear: bool) -> Result<()> {
+    let config = ConfigManager::new()?;
+    let db = Database::open(config.get_db_path()?)?;
+
+    if clear {
+        let removed = db.clear_queue()?;
+        println!("Removed {} queued {}", removed, if removed == 1 { "entry" } else { "entries" });
+        return Ok(());
+    }
+
+    let Some(entry) = db.pop_queue()? else {
+        return Err(CliError::InvalidInput("Paste queue is empty; queue entries with p in the TUI".to_string()));
+    };
+    clipboard::set_clipboard_content(&copy_content(&entry.content, entry.encrypted, &config.load().unwrap_or_default())?)?;
+    if paste {
+        clipboard::paste_into_focused_app();
+    }
+    eprintln!("{} left in the paste queue", db.queue_len()?);
+    Ok(())
+}
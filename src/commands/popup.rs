@@ -0,0 +1,124 @@
+use crate::config::{ConfigManager, PopupTerminal};
+use crate::error::{CliError, Result};
+use std::process::Command;
+
+const POPUP_COLUMNS: u32 = 100;
+const POPUP_LINES: u32 = 24;
+const POPUP_WIDTH_PX: i32 = 900;
+const POPUP_HEIGHT_PX: i32 = 480;
+
+/// Open the picker in a small terminal window near the mouse cursor; the
+/// chosen entry is pasted, or with `type_out` typed, into the previously focused app.
+pub async fn run_popup(type_out: bool) -> Result<()> {
+    let config = ConfigManager::new()?.load()?;
+    let terminal = config.popup_terminal.unwrap_or_default();
+    let binary = std::env::current_exe()?;
+    let flag = if type_out { "--type" } else { "--paste" };
+    let command = format!("{} tui {}", shell_quote(&binary.to_string_lossy()), flag);
+    let position = cursor_position();
+
+    let result = match terminal {
+        PopupTerminal::Kitty => {
+            let mut cmd = Command::new("kitty");
+            cmd.args([
+                "--title", "clippie",
+                "-o", "remember_window_size=no",
+                "-o", &format!("initial_window_width={}c", POPUP_COLUMNS),
+                "-o", &format!("initial_window_height={}c", POPUP_LINES),
+                "sh", "-c", &command,
+            ]);
+            cmd.spawn()
+        }
+        PopupTerminal::Alacritty => {
+            let mut cmd = Command::new("alacritty");
+            cmd.args(["--title", "clippie"])
+                .args(["-o", &format!("window.dimensions.columns={}", POPUP_COLUMNS)])
+                .args(["-o", &format!("window.dimensions.lines={}", POPUP_LINES)]);
+            if let Some((x, y)) = position {
+                cmd.args(["-o", &format!("window.position.x={}", x)])
+                    .args(["-o", &format!("window.position.y={}", y)]);
+            }
+            cmd.args(["-e", "sh", "-c", &command]).spawn()
+        }
+        PopupTerminal::System => {
+            let program = std::env::var("TERMINAL").ok().filter(|t| !t.is_empty());
+            Command::new(program.as_deref().unwrap_or("x-terminal-emulator"))
+                .args(["-e", "sh", "-c", &command])
+                .spawn()
+        }
+        PopupTerminal::Iterm | PopupTerminal::Terminal => Command::new("osascript")
+            .args(["-e", &applescript(terminal, &command, position)])
+            .spawn(),
+    };
+
+    result.map(|_| ()).map_err(|e| {
+        CliError::ConfigError(format!("Failed to launch {:?} for the popup: {}", terminal, e))
+    })
+}
+
+fn applescript(terminal: PopupTerminal, command: &str, position: Option<(i32, i32)>) -> String {
+    let (x, y) = position.unwrap_or((200, 200));
+    let bounds = format!("{{{}, {}, {}, {}}}", x, y, x + POPUP_WIDTH_PX, y + POPUP_HEIGHT_PX);
+    let command = command.replace('\\', "\\\\").replace('"', "\\\"");
+
+    if terminal == PopupTerminal::Iterm {
+        format!(
+            "tell application \"iTerm\"
+    set w to (create window with default profile command \"{command}\")
+    set bounds of w to {bounds}
+    activate
+end tell"
+        )
+    } else {
+        format!(
+            "tell application \"Terminal\"
+    do script \"{command}; exit\"
+    set bounds of front window to {bounds}
+    activate
+end tell"
+        )
+    }
+}
+
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+/// Mouse position in top-left-origin screen points.
+#[cfg(target_os = "macos")]
+fn cursor_position() -> Option<(i32, i32)> {
+    use objc2::rc::Retained;
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send, msg_send_id};
+    use objc2_foundation::{NSPoint, NSRect};
+
+    unsafe {
+        let location: NSPoint = msg_send![class!(NSEvent), mouseLocation];
+        let screens: Retained<AnyObject> = msg_send_id![class!(NSScreen), screens];
+        let primary: Option<Retained<AnyObject>> = msg_send_id![&*screens, firstObject];
+        let frame: NSRect = msg_send![&*primary?, frame];
+        Some((location.x as i32, (frame.size.height - location.y) as i32))
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn cursor_position() -> Option<(i32, i32)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("/Apps/it's/clippie"), r"'/Apps/it'\''s/clippie'");
+    }
+
+    #[test]
+    fn test_applescript_bounds() {
+        let script = applescript(PopupTerminal::Iterm, "clippie tui --paste", Some((10, 20)));
+        assert!(script.contains("tell application \"iTerm\""));
+        assert!(script.contains("{10, 20, 910, 500}"));
+    }
+}
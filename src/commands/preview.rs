@@ -0,0 +1,85 @@
+use crate::config::ConfigManager;
+use crate::db::Database;
+use crate::error::Result;
+use crate::tui::components::{find_highlights, format_absolute_date, wrap_text};
+
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+pub async fn run_preview(id: i64, width: Option<usize>, color: bool) -> Result<()> {
+    let config = ConfigManager::new()?;
+    let db = Database::open(config.get_db_path()?)?;
+
+    let Some(entry) = db.get_entry(id)? else {
+        eprintln!("Error: No entry with id {}", id);
+        return Ok(());
+    };
+
+    println!("{}─ {}{}", DIM, format_absolute_date(&entry.created_at), RESET);
+    println!();
+    let language = color.then_some(entry.language.as_deref()).flatten();
+    for line in render_lines(&entry.content, width.unwrap_or_else(preview_width), language, color) {
+        println!("{}", line);
+    }
+
+    Ok(())
+}
+
+/// Width of the fzf preview window, or of the terminal when run directly.
+fn preview_width() -> usize {
+    std::env::var("FZF_PREVIEW_COLUMNS")
+        .ok()
+        .and_then(|c| c.parse().ok())
+        .or_else(|| crossterm::terminal::size().ok().map(|(w, _)| w as usize))
+        .unwrap_or(80)
+}
+
+/// Wrapped lines, with patterns and `language` syntax colored unless `color` is off.
+fn render_lines(content: &str, width: usize, language: Option<&str>, color: bool) -> Vec<String> {
+    content
+        .lines()
+        .flat_map(|line| wrap_text(line, width))
+        .map(|line| if color { highlight_ansi(&line, language) } else { line })
+        .collect()
+}
+
+fn highlight_ansi(text: &str, language: Option<&str>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last_end = 0;
+    for (start, end, ptype) in find_highlights(text, language) {
+        out.push_str(&text[last_end..start]);
+        out.push_str(&format!("\x1b[{}m{}{}", ptype.ansi_code(), &text[start..end], RESET));
+        last_end = end;
+    }
+    out.push_str(&text[last_end..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_ansi_url() {
+        let out = highlight_ansi("see https://example.com now", None);
+        assert_eq!(out, "see \x1b[34mhttps://example.com\x1b[0m now");
+    }
+
+    #[test]
+    fn test_highlight_ansi_code() {
+        let out = highlight_ansi("curl https://example.com # fetch", Some("shell"));
+        assert_eq!(out, "curl \x1b[34mhttps://example.com\x1b[0m\x1b[90m # fetch\x1b[0m");
+    }
+
+    #[test]
+    fn test_highlight_ansi_color() {
+        let out = highlight_ansi("bg #000", None);
+        assert_eq!(out, "bg \x1b[97;48;2;0;0;0m#000\x1b[0m");
+    }
+
+    #[test]
+    fn test_render_lines_wraps() {
+        let lines = render_lines("hello world test", 10, None, true);
+        assert_eq!(lines, vec!["hello", "world test"]);
+    }
+}
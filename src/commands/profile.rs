@@ -0,0 +1,70 @@
+use crate::cli::ProfileAction;
+use crate::config::{expand_home, ConfigManager};
+use crate::db::Database;
+use crate::error::Result;
+use crate::ipc;
+use std::path::PathBuf;
+
+pub async fn run_profile(action: ProfileAction) -> Result<()> {
+    let manager = ConfigManager::new()?;
+
+    match action {
+        ProfileAction::List => {
+            let active = manager.active_profile()?;
+            let profiles = manager.profiles()?;
+            let width = profiles.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+            for (name, path) in &profiles {
+                let mark = if *name == active { '*' } else { ' ' };
+                println!("{} {:<width$}  {}", mark, name, path.display(), width = width);
+            }
+            let recent = manager.load()?.recent_db_paths;
+            let leftover = unclaimed(&recent, &profiles);
+            if !leftover.is_empty() {
+                println!("\nDatabases switched to before profiles existed:");
+                for path in leftover {
+                    println!("  {}", path.display());
+                }
+                println!("Keep one with 'clippie profile create NAME --db PATH'.");
+            }
+        }
+        ProfileAction::Create { name, db } => {
+            let path = manager.create_profile(&name, db.as_deref())?;
+            // Open it now so the TUI finds a database before the daemon has captured anything.
+            Database::open(&path)?;
+            println!("Created profile \"{}\" at {}", name, path.display());
+            println!("Use it with 'clippie profile switch {}' or '--profile {}'.", name, name);
+        }
+        ProfileAction::Switch { name } => {
+            let path = manager.switch_profile(&name)?;
+            println!("Switched to profile \"{}\" ({})", name, path.display());
+            if ipc::send(&manager, ipc::Request::Stats).await.is_ok() {
+                println!("The daemon still captures into the old database; run 'clippie restart' to move it over.");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Paths in `recent` that no profile uses.
+fn unclaimed(recent: &[PathBuf], profiles: &[(String, PathBuf)]) -> Vec<PathBuf> {
+    recent
+        .iter()
+        .filter_map(|path| expand_home(path).ok())
+        .filter(|path| !profiles.iter().any(|(_, used)| used == path))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unclaimed_skips_profile_databases() {
+        let profiles = vec![
+            ("default".to_string(), PathBuf::from("/data/clipboard.db")),
+            ("work".to_string(), PathBuf::from("/data/work.db")),
+        ];
+        let recent = vec![PathBuf::from("/data/work.db"), PathBuf::from("/data/old.db")];
+        assert_eq!(unclaimed(&recent, &profiles), vec![PathBuf::from("/data/old.db")]);
+    }
+}
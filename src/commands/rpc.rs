@@ -0,0 +1,117 @@
+use crate::clipboard::duplicate_hash;
+use crate::config::{ConfigManager, DuplicatePolicy};
+use crate::db::{ClipboardEntry, Database};
+use crate::error::Result;
+use crate::tui::fuzzy;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+
+const DEFAULT_LIMIT: usize = 50;
+
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// Serve one JSON request per stdin line, answering with one JSON line each.
+pub async fn run_rpc() -> Result<()> {
+    let config = ConfigManager::new()?;
+    let db = Database::open(config.get_db_path()?)?;
+    let duplicates = config.load().unwrap_or_default().duplicates;
+
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        writeln!(stdout, "{}", handle_line(&db, &duplicates, &line))?;
+        stdout.flush()?;
+    }
+
+    Ok(())
+}
+
+fn handle_line(db: &Database, duplicates: &DuplicatePolicy, line: &str) -> Value {
+    let request: Request = match serde_json::from_str(line) {
+        Ok(r) => r,
+        Err(e) => return json!({ "id": null, "error": format!("invalid request: {}", e) }),
+    };
+
+    match dispatch(db, duplicates, &request.method, &request.params) {
+        Ok(result) => json!({ "id": request.id, "result": result }),
+        Err(e) => json!({ "id": request.id, "error": e }),
+    }
+}
+
+fn dispatch(db: &Database, duplicates: &DuplicatePolicy, method: &str, params: &Value) -> std::result::Result<Value, String> {
+    let limit = params["limit"].as_u64().map(|l| l as usize).unwrap_or(DEFAULT_LIMIT);
+
+    match method {
+        "list" => {
+            let entries = db.get_recent_entries(limit).map_err(|e| e.to_string())?;
+            Ok(entries.iter().map(ClipboardEntry::to_json).collect())
+        }
+        "search" => {
+            let query = params["query"].as_str().ok_or("missing param: query")?;
+            let entries = db.get_all_entries().map_err(|e| e.to_string())?;
+            Ok(fuzzy::filter_entries(&entries, query).into_iter().take(limit).map(ClipboardEntry::to_json).collect())
+        }
+        "get" => {
+            let id = params["id"].as_i64().ok_or("missing param: id")?;
+            let entry = db.get_entry(id).map_err(|e| e.to_string())?;
+            Ok(entry.as_ref().map(ClipboardEntry::to_json).unwrap_or(Value::Null))
+        }
+        "insert" => {
+            let content = params["content"].as_str().ok_or("missing param: content")?;
+            if content.trim().is_empty() {
+                return Err("content is empty".to_string());
+            }
+            let id = db.insert_entry(content, &duplicate_hash(content, duplicates)).map_err(|e| e.to_string())?;
+            Ok(json!({ "id": id }))
+        }
+        other => Err(format!("unknown method: {}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_insert_then_get() {
+        let tmp = NamedTempFile::new().unwrap();
+        let db = Database::open(tmp.path()).unwrap();
+
+        let inserted = handle_line(&db, &DuplicatePolicy::default(), r#"{"id": 1, "method": "insert", "params": {"content": "hello"}}"#);
+        let id = inserted["result"]["id"].as_i64().unwrap();
+
+        let got = handle_line(&db, &DuplicatePolicy::default(), &format!(r#"{{"id": 2, "method": "get", "params": {{"id": {}}}}}"#, id));
+        assert_eq!(got["id"], 2);
+        assert_eq!(got["result"]["content"], "hello");
+    }
+
+    #[test]
+    fn test_search_and_errors() {
+        let tmp = NamedTempFile::new().unwrap();
+        let db = Database::open(tmp.path()).unwrap();
+        db.insert_entry("cargo build", "h1").unwrap();
+        db.insert_entry("git status", "h2").unwrap();
+
+        let found = handle_line(&db, &DuplicatePolicy::default(), r#"{"method": "search", "params": {"query": "cargo"}}"#);
+        assert_eq!(found["result"].as_array().unwrap().len(), 1);
+
+        let unknown = handle_line(&db, &DuplicatePolicy::default(), r#"{"id": 3, "method": "nope"}"#);
+        assert_eq!(unknown["error"], "unknown method: nope");
+
+        let invalid = handle_line(&db, &DuplicatePolicy::default(), "not json");
+        assert!(invalid["error"].as_str().unwrap().starts_with("invalid request"));
+    }
+}
@@ -1,20 +1,16 @@
-use crate::config::ConfigManager;
+use crate::config::{Config, ConfigManager};
 use crate::db::Database;
 use crate::error::Result;
 use std::io::{self, Write};
+use std::path::PathBuf;
 
 pub async fn run_setup() -> Result<()> {
     println!("\n🔧 Clippie Setup Wizard\n");
 
     let config = ConfigManager::new()?;
-    let db_path = config.get_db_path()?;
-
-    if let Some(parent) = db_path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
-
-    Database::open(&db_path)?;
+    let db_path = configure(|_| {})?;
     println!("✓ Database configured at {}", db_path.display());
+    println!("✓ API tokens stored in {}", config.get_config_path()?.display());
 
     print!("\nInstall the clipboard monitoring daemon? [y/N]: ");
     io::stdout().flush()?;
@@ -32,3 +28,17 @@ pub async fn run_setup() -> Result<()> {
 
     Ok(())
 }
+
+/// Apply `update` to the saved config, then create the database and API tokens.
+/// Returns the database path.
+pub fn configure(update: impl FnOnce(&mut Config)) -> Result<PathBuf> {
+    let config = ConfigManager::new()?;
+    let mut settings = config.load()?;
+    update(&mut settings);
+    config.save(&settings)?;
+
+    let db_path = config.get_db_path()?;
+    Database::open(&db_path)?;
+    config.ensure_api_tokens()?;
+    Ok(db_path)
+}
@@ -0,0 +1,72 @@
+use super::get::{copy_content, find};
+use crate::cli::SnippetAction;
+use crate::clipboard;
+use crate::config::ConfigManager;
+use crate::db::Database;
+use crate::error::{CliError, Result};
+
+pub async fn run_snippet(action: SnippetAction) -> Result<()> {
+    let config = ConfigManager::new()?;
+    let db = Database::open(config.get_db_path()?)?;
+
+    match action {
+        SnippetAction::Add { name, target, id } => {
+            let name = valid_name(&name)?;
+            let replaced = db.save_snippet(name, &find(&db, target, id)?)?;
+            println!("{} snippet \"{}\"", if replaced { "Updated" } else { "Saved" }, name);
+        }
+        SnippetAction::Copy { name } => {
+            let snippet = db
+                .get_snippet(&name)?
+                .ok_or_else(|| CliError::InvalidInput(format!("No snippet named \"{}\"", name)))?;
+            let content = copy_content(&snippet.content, snippet.encrypted, &config.load().unwrap_or_default())?;
+            clipboard::set_clipboard_content(&content)?;
+        }
+        SnippetAction::List => {
+            for snippet in db.get_snippets()? {
+                println!("{}\t{}", snippet.name, summary(&snippet.content, snippet.encrypted));
+            }
+        }
+        SnippetAction::Remove { name } => {
+            if !db.delete_snippet(&name)? {
+                return Err(CliError::InvalidInput(format!("No snippet named \"{}\"", name)));
+            }
+            println!("Removed snippet \"{}\"", name);
+        }
+    }
+    Ok(())
+}
+
+/// Names are typed on the command line and in the TUI, so surrounding whitespace is dropped.
+pub(crate) fn valid_name(name: &str) -> Result<&str> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(CliError::InvalidInput("Snippet name can't be empty".to_string()));
+    }
+    Ok(name)
+}
+
+/// First line of a snippet for one-line listings.
+pub(crate) fn summary(content: &str, encrypted: bool) -> &str {
+    if encrypted {
+        return crate::secrets::ENCRYPTED_PLACEHOLDER;
+    }
+    content.lines().find(|line| !line.trim().is_empty()).unwrap_or("").trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_name() {
+        assert_eq!(valid_name("  deploy ").unwrap(), "deploy");
+        assert!(valid_name(" ").is_err());
+    }
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(summary("\n  SELECT 1;\nFROM t", false), "SELECT 1;");
+        assert_eq!(summary("-----BEGIN AGE", true), crate::secrets::ENCRYPTED_PLACEHOLDER);
+    }
+}
@@ -0,0 +1,161 @@
+use super::vacuum::disk_size;
+use crate::config::{ConfigManager, SensitivePolicy};
+use crate::db::Database;
+use crate::error::Result;
+use chrono::{Local, NaiveDate};
+use serde_json::json;
+
+/// Days covered by the entries-per-day series, today included.
+const HISTORY_DAYS: i64 = 30;
+/// Length of the most-copied list.
+const TOP_ENTRIES: usize = 10;
+/// Characters shown of each most-copied entry.
+const TOP_PREVIEW_CHARS: usize = 60;
+
+/// Print what's in the history: sizes, how fast it grows, and what gets copied most.
+pub async fn run_stats(json: bool) -> Result<()> {
+    let config = ConfigManager::new()?;
+    let db_path = config.get_db_path()?;
+    if !db_path.exists() {
+        eprintln!("Error: Database not found at {}", db_path.display());
+        return Ok(());
+    }
+    let show_sensitive = config.load().is_ok_and(|c| c.sensitive_entries == SensitivePolicy::Show);
+
+    let db = Database::open(&db_path)?;
+    let sizes = db.entry_sizes()?;
+    let today = Local::now().date_naive();
+    let first_day = today - chrono::Duration::days(HISTORY_DAYS - 1);
+    let since = first_day
+        .and_hms_opt(0, 0, 0)
+        .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+        .map_or(0, |t| t.timestamp());
+    let per_day = fill_days(&db.entries_per_day(since)?, first_day, today);
+    let top: Vec<(String, i64)> = db
+        .most_copied(TOP_PREVIEW_CHARS, TOP_ENTRIES)?
+        .into_iter()
+        .map(|(entry, count)| {
+            let text = if show_sensitive { entry.display_content().into() } else { entry.masked_content() };
+            let mut line = text.replace('\r', "").replace('\n', "↵");
+            if entry.truncated {
+                line.push('…');
+            }
+            (line, count)
+        })
+        .collect();
+    let languages = db.language_counts()?;
+    let templates = db.count_templates()?;
+    let snippets = db.get_snippets()?.len();
+    let on_disk = disk_size(&db_path);
+
+    if json {
+        let value = json!({
+            "entries": sizes.len(),
+            "size_on_disk": on_disk,
+            "content_bytes": sizes.iter().sum::<u64>(),
+            "average_entry_bytes": average(&sizes),
+            "median_entry_bytes": median(&sizes),
+            "entries_per_day": per_day
+                .iter()
+                .map(|(day, count)| json!({ "date": day.to_string(), "entries": count }))
+                .collect::<Vec<_>>(),
+            "most_copied": top
+                .iter()
+                .map(|(preview, count)| json!({ "preview": preview, "copy_count": count }))
+                .collect::<Vec<_>>(),
+            "templates": templates,
+            "snippets": snippets,
+            "languages": languages.iter().map(|(language, count)| (language.clone(), json!(count))).collect::<serde_json::Map<_, _>>(),
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    println!("\nClipboard History Stats");
+    println!("=======================\n");
+    println!("Entries:         {}", sizes.len());
+    println!("Size on Disk:    {} KB", on_disk / 1024);
+    println!("Entry Size:      {:.0} bytes on average, {} median", average(&sizes), median(&sizes));
+    let counts: Vec<i64> = per_day.iter().map(|(_, count)| *count).collect();
+    let total: i64 = counts.iter().sum();
+    println!(
+        "Last {} Days:    {} ({} new, {:.1}/day)",
+        HISTORY_DAYS,
+        sparkline(&counts),
+        total,
+        total as f64 / HISTORY_DAYS as f64
+    );
+    println!("Templates:       {}", templates);
+    println!("Snippets:        {}", snippets);
+    if !languages.is_empty() {
+        let tags: Vec<String> = languages.iter().map(|(language, count)| format!("{} {}", language, count)).collect();
+        println!("Languages:       {}", tags.join(", "));
+    }
+    if !top.is_empty() {
+        println!("\nMost Copied:");
+        for (preview, count) in &top {
+            println!("  {:>5}×  {}", count, preview);
+        }
+    }
+    println!();
+    Ok(())
+}
+
+fn average(sizes: &[u64]) -> f64 {
+    if sizes.is_empty() {
+        return 0.0;
+    }
+    sizes.iter().sum::<u64>() as f64 / sizes.len() as f64
+}
+
+/// Middle of `sorted`, or the mean of the two middle values for an even count.
+fn median(sorted: &[u64]) -> u64 {
+    match sorted.len() {
+        0 => 0,
+        n if n % 2 == 1 => sorted[n / 2],
+        n => (sorted[n / 2 - 1] + sorted[n / 2]) / 2,
+    }
+}
+
+/// One count per day from `first` to `last`, zero for days missing from `days`.
+fn fill_days(days: &[(NaiveDate, i64)], first: NaiveDate, last: NaiveDate) -> Vec<(NaiveDate, i64)> {
+    first
+        .iter_days()
+        .take_while(|day| *day <= last)
+        .map(|day| (day, days.iter().find(|(d, _)| *d == day).map_or(0, |(_, count)| *count)))
+        .collect()
+}
+
+/// Bars scaled to the largest count; days with none stay blank.
+fn sparkline(counts: &[i64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = counts.iter().copied().max().unwrap_or(0).max(1);
+    counts
+        .iter()
+        .map(|&count| match count {
+            0 => ' ',
+            n => BARS[((n - 1) * BARS.len() as i64 / max) as usize],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_average_and_median() {
+        assert_eq!((average(&[]), median(&[])), (0.0, 0));
+        assert_eq!(median(&[1, 5, 100]), 5);
+        assert_eq!(median(&[1, 5, 7, 100]), 6);
+        assert_eq!(average(&[1, 5, 7, 100]), 28.25);
+    }
+
+    #[test]
+    fn test_fill_days_and_sparkline() {
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        let filled = fill_days(&[(day(2), 4), (day(4), 8)], day(1), day(4));
+        assert_eq!(filled, [(day(1), 0), (day(2), 4), (day(3), 0), (day(4), 8)]);
+        assert_eq!(sparkline(&[0, 4, 0, 8, 1]), " ▄ █▁");
+    }
+}
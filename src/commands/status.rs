@@ -1,8 +1,14 @@
-use crate::config::ConfigManager;
+use crate::config::{ConfigManager, Incognito};
 use crate::db::Database;
 use crate::error::Result;
+use crate::ipc;
+use crate::service::ServiceManager;
+use chrono::Utc;
 use std::process::Command;
 
+/// Days of history averaged for the captures-per-day figure.
+const ACTIVITY_DAYS: i64 = 7;
+
 pub async fn run_status() -> Result<()> {
     let config = ConfigManager::new()?;
 
@@ -13,23 +19,75 @@ pub async fn run_status() -> Result<()> {
     }
 
     let db_path = config.get_db_path()?;
-    let daemon_running = check_daemon_running();
+    // Ask the daemon first; the service manager only knows about it when it isn't answering.
+    let stats = ipc::send(&config, ipc::Request::Stats).await.ok();
 
     println!("\nClipboard History Manager Status");
     println!("================================\n");
-    println!("Daemon Status:   {} {}",
-        if daemon_running { "✓" } else { "✗" },
-        if daemon_running { "Running" } else { "Stopped" }
-    );
-
-    if db_path.exists() {
-        if let Ok(db) = Database::open(&db_path) {
-            if let Ok(count) = db.count_entries() {
-                println!("Entries:         {}", count);
-            }
-            if let Ok(size) = db.get_size() {
-                println!("Database Size:   {} KB", size / 1024);
+    if let Some(stats) = &stats {
+        println!("Daemon Status:   ✓ Running");
+        println!("PID:             {}", stats["pid"]);
+        print_process_usage(stats["pid"].as_u64().map(|pid| pid as u32));
+        println!("Captured:        {} since start, {} in the last minute", stats["captures_total"], stats["captures_per_minute"]);
+        if stats["errors_total"].as_u64().is_some_and(|errors| errors > 0) {
+            println!("Errors:          {}", stats["errors_total"]);
+        }
+    } else {
+        let daemon = ServiceManager::detect().status();
+        println!("Daemon Status:   {} {}",
+            if daemon.running { "✓" } else { "✗" },
+            if daemon.running { "Running (not answering on the control socket)" } else { "Stopped" }
+        );
+        if let Some(pid) = daemon.pid {
+            println!("PID:             {}", pid);
+        }
+        print_process_usage(daemon.pid);
+        if let Some(code) = &daemon.last_exit_code {
+            println!("Last Exit Code:  {}", code);
+        }
+        if daemon.throttled {
+            println!("Throttled:       ⚠ yes, the service manager is delaying restarts");
+        }
+    }
+    match config.incognito() {
+        Incognito::Paused => println!("Capture:         Paused until 'clippie resume'"),
+        Incognito::Off => println!("Capture:         Active"),
+        Incognito::Indefinite => println!("Capture:         Incognito until 'clippie resume'"),
+        Incognito::Until(until) => println!(
+            "Capture:         Incognito until {}",
+            until.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+        ),
+    }
+
+    if db_path.exists()
+        && let Ok(db) = Database::open(&db_path)
+    {
+        if let Ok(count) = db.count_entries() {
+            println!("Entries:         {}", count);
+        }
+        let now = Utc::now();
+        let since = |days| (now - chrono::Duration::days(days)).timestamp();
+        if let (Ok((day, latest)), Ok((week, _))) = (db.copy_activity(since(1)), db.copy_activity(since(ACTIVITY_DAYS))) {
+            if let Some(latest) = latest {
+                println!(
+                    "Last Capture:    {} ({} ago)",
+                    latest.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+                    format_duration((now - latest).num_seconds().max(0) as u64)
+                );
             }
+            println!(
+                "Captures/Day:    {} in the last 24h, {:.1} on average over {} days",
+                day,
+                week as f64 / ACTIVITY_DAYS as f64,
+                ACTIVITY_DAYS
+            );
+        }
+        if let Ok(size) = db.get_size() {
+            println!("Database Size:   {} KB", size / 1024);
+        }
+        let wal_path = format!("{}-wal", db_path.display());
+        if let Ok(wal) = std::fs::metadata(wal_path) {
+            println!("WAL Size:        {} KB", wal.len() / 1024);
         }
     }
 
@@ -37,10 +95,63 @@ pub async fn run_status() -> Result<()> {
     Ok(())
 }
 
-fn check_daemon_running() -> bool {
-    Command::new("launchctl")
-        .args(["list"])
-        .output()
-        .map(|o| String::from_utf8_lossy(&o.stdout).contains("clippie-daemon"))
-        .unwrap_or(false)
+/// Uptime and resident memory of the daemon process, from `ps`.
+fn print_process_usage(pid: Option<u32>) {
+    let Some(pid) = pid else {
+        return;
+    };
+    let Ok(output) = Command::new("ps").args(["-o", "etime=,rss=", "-p", &pid.to_string()]).output() else {
+        return;
+    };
+    let Some((uptime, rss_kb)) = parse_ps_usage(&String::from_utf8_lossy(&output.stdout)) else {
+        return;
+    };
+    println!("Uptime:          {}", format_duration(uptime));
+    println!("Memory:          {:.1} MB", rss_kb as f64 / 1024.0);
+}
+
+/// Parse `ps -o etime=,rss=`: elapsed time as `[[dd-]hh:]mm:ss` and resident size in KB.
+fn parse_ps_usage(output: &str) -> Option<(u64, u64)> {
+    let mut fields = output.split_whitespace();
+    let etime = fields.next()?;
+    let rss_kb = fields.next()?.parse().ok()?;
+
+    let (days, clock) = match etime.split_once('-') {
+        Some((days, clock)) => (days.parse::<u64>().ok()?, clock),
+        None => (0, etime),
+    };
+    let seconds = clock.split(':').try_fold(0, |total, part| Some(total * 60 + part.parse::<u64>().ok()?))?;
+    Some((days * 86400 + seconds, rss_kb))
+}
+
+/// `3d 4h`, `2h 5m`, `7m` or `12s`: the two largest units.
+pub(crate) fn format_duration(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+    match (days, hours, minutes) {
+        (0, 0, 0) => format!("{}s", secs),
+        (0, 0, m) => format!("{}m", m),
+        (0, h, m) => format!("{}h {}m", h, m),
+        (d, h, _) => format!("{}d {}h", d, h),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ps_usage() {
+        assert_eq!(parse_ps_usage("   05:03  10240\n"), Some((303, 10240)));
+        assert_eq!(parse_ps_usage("02:00:01 512"), Some((7201, 512)));
+        assert_eq!(parse_ps_usage("1-00:00:10 512"), Some((86410, 512)));
+        assert_eq!(parse_ps_usage(""), None);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(12), "12s");
+        assert_eq!(format_duration(7 * 60 + 5), "7m");
+        assert_eq!(format_duration(2 * 3600 + 5 * 60), "2h 5m");
+        assert_eq!(format_duration(3 * 86400 + 4 * 3600 + 59), "3d 4h");
+    }
 }
@@ -0,0 +1,75 @@
+use super::status::format_duration;
+use crate::cli::SyncAction;
+use crate::config::{generate_token, ConfigManager};
+use crate::error::Result;
+use crate::ipc;
+use chrono::Utc;
+use serde_json::Value;
+
+pub async fn run_sync(action: SyncAction) -> Result<()> {
+    match action {
+        SyncAction::Key => println!("{}", generate_token()?),
+        SyncAction::Status => {
+            let manager = ConfigManager::new()?;
+            if manager.load()?.sync.is_none() {
+                println!("Sync is off. Add a [sync] section with a key and peers to ~/.clippie/config.toml.");
+                return Ok(());
+            }
+            let Ok(response) = ipc::send(&manager, ipc::Request::Sync).await else {
+                println!("The daemon isn't running, and it does the syncing. Start it with 'clippie start'.");
+                return Ok(());
+            };
+            let status = &response["sync"];
+            if status.is_null() {
+                println!("The daemon started before sync was set up; run 'clippie restart'.");
+                return Ok(());
+            }
+            print_status(status);
+        }
+    }
+    Ok(())
+}
+
+fn print_status(status: &Value) {
+    let now = Utc::now().timestamp();
+    match (status["listen"].as_str(), status["listen_error"].as_str()) {
+        (Some(address), Some(error)) => println!("Listening:  ✗ {} ({})", address, error),
+        (Some(address), None) => println!("Listening:  {}, {}", address, describe(&status["inbound"], now)),
+        (None, _) => println!("Listening:  off, only connecting out"),
+    }
+    let peers = status["peers"].as_array().map(Vec::as_slice).unwrap_or_default();
+    if peers.is_empty() {
+        println!("Peers:      none; other machines connect to this one");
+    }
+    let width = peers.iter().filter_map(|peer| peer["address"].as_str()).map(str::len).max().unwrap_or(0);
+    for peer in peers {
+        println!("{:<width$}  {}", peer["address"].as_str().unwrap_or("?"), describe(peer, now), width = width);
+    }
+}
+
+/// `✓ 12s ago, 40 received, 12 sent` or `✗ connection refused, last synced 3m ago`.
+fn describe(peer: &Value, now: i64) -> String {
+    let ago = |key: &str| peer[key].as_i64().map(|at| format!("{} ago", format_duration((now - at).max(0) as u64)));
+    let counts = format!("{} received, {} sent", peer["received"], peer["sent"]);
+    match (peer["error"].as_str(), ago("last_sync")) {
+        (Some(error), Some(synced)) => format!("✗ {}, last synced {}; {}", error, synced, counts),
+        (Some(error), None) => format!("✗ {}", error),
+        (None, Some(synced)) => format!("✓ {}, {}", synced, counts),
+        (None, None) => "no exchange yet".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_describe() {
+        let peer = json!({ "last_sync": 940, "error": null, "received": 4, "sent": 2 });
+        assert_eq!(describe(&peer, 1000), "✓ 1m ago, 4 received, 2 sent");
+        let failing = json!({ "last_sync": null, "error": "Connection refused", "received": 0, "sent": 0 });
+        assert_eq!(describe(&failing, 1000), "✗ Connection refused");
+        assert_eq!(describe(&json!({ "received": 0, "sent": 0 }), 1000), "no exchange yet");
+    }
+}
@@ -0,0 +1,26 @@
+use super::get::resolve;
+use crate::clipboard;
+use crate::error::{CliError, Result};
+use crate::transform::Transform;
+use std::io::Write;
+
+/// Copy an entry rewritten by `op`, or with `print` write it to stdout. The stored entry stays
+/// as it is.
+pub async fn run_transform(target: usize, by_id: bool, op: &str, print: bool) -> Result<()> {
+    let op = parse_op(op)?;
+    let content = op.apply(&resolve(target, by_id)?)?;
+    if print {
+        let mut stdout = std::io::stdout();
+        stdout.write_all(content.as_bytes())?;
+        stdout.flush()?;
+        return Ok(());
+    }
+    clipboard::set_clipboard_content(&content)
+}
+
+fn parse_op(name: &str) -> Result<Transform> {
+    Transform::parse(name).ok_or_else(|| {
+        let names: Vec<&str> = Transform::ALL.iter().map(|op| op.name()).collect();
+        CliError::InvalidInput(format!("Unknown transform '{}'; expected one of {}", name, names.join(", ")))
+    })
+}
@@ -0,0 +1,41 @@
+use crate::config::ConfigManager;
+use crate::db::Database;
+use crate::error::Result;
+use std::path::Path;
+
+/// Compact the database after large deletes, which otherwise leave the file at its largest size.
+pub async fn run_vacuum() -> Result<()> {
+    let config = ConfigManager::new()?;
+    let db_path = config.get_db_path()?;
+    if !db_path.exists() {
+        eprintln!("Error: Database not found at {}", db_path.display());
+        return Ok(());
+    }
+
+    let before = disk_size(&db_path);
+    let db = Database::open(&db_path)?;
+    let truncated = db.vacuum()?;
+    let after = disk_size(&db_path);
+
+    println!(
+        "✓ Reclaimed {} KB ({} KB → {} KB)",
+        before.saturating_sub(after) / 1024,
+        before / 1024,
+        after / 1024
+    );
+    if !truncated {
+        println!("  The WAL is still in use by a reader; the daemon truncates it on its next checkpoint.");
+    }
+    println!();
+    Ok(())
+}
+
+/// Bytes on disk for the database and its WAL.
+pub(crate) fn disk_size(db_path: &Path) -> u64 {
+    let wal_path = format!("{}-wal", db_path.display());
+    [db_path, Path::new(&wal_path)]
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
@@ -0,0 +1,53 @@
+//! gzip for entries stored under the `store-compressed` size policy, using the system `gzip`.
+
+use crate::error::{CliError, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+pub fn deflate(text: &str) -> Result<Vec<u8>> {
+    gzip(&["-c"], text.as_bytes())
+}
+
+pub fn inflate(bytes: &[u8]) -> Result<String> {
+    String::from_utf8(gzip(&["-dc"], bytes)?)
+        .map_err(|_| CliError::CompressionError("entry is not UTF-8".to_string()))
+}
+
+fn gzip(args: &[&str], input: &[u8]) -> Result<Vec<u8>> {
+    let mut child = Command::new("gzip")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| CliError::CompressionError(format!("Failed to run gzip: {}", e)))?;
+
+    // Feed stdin from another thread: gzip blocks once its output fills the pipe.
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let output = std::thread::scope(|scope| {
+        scope.spawn(move || stdin.write_all(input));
+        child.wait_with_output()
+    })?;
+
+    if !output.status.success() {
+        return Err(CliError::CompressionError(format!(
+            "gzip failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let log = "GET /healthz 200\n".repeat(50_000);
+        let compressed = deflate(&log).unwrap();
+        assert!(compressed.len() < log.len() / 10);
+        assert_eq!(inflate(&compressed).unwrap(), log);
+        assert!(inflate(b"not gzip").is_err());
+    }
+}
@@ -1,5 +1,585 @@
 use crate::error::{CliError, Result};
-use std::path::PathBuf;
+use chrono::{DateTime, Local, NaiveTime, Utc};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// User settings persisted in `~/.clippie/config.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// History database of the `default` profile; defaults to `~/.clippie/clipboard.db`. A leading `~/` is expanded.
+    pub db_path: Option<PathBuf>,
+    /// Profile whose database is used when `--profile` isn't given; unset means `default`.
+    pub profile: Option<String>,
+    /// Named databases besides the default one, e.g. `work` and `personal`.
+    pub profiles: BTreeMap<String, Profile>,
+    /// Delete entries created more than this many days ago.
+    pub max_age_days: Option<u32>,
+    /// Keep at most this many entries, deleting the least recently copied first.
+    pub max_entries: Option<usize>,
+    /// Delete the least recently copied entries while the database holds more than this many megabytes.
+    pub max_db_size_mb: Option<u64>,
+    /// Captures larger than this are handled by `oversize_policy`.
+    pub max_entry_bytes: Option<usize>,
+    pub oversize_policy: OversizePolicy,
+    /// Databases switched to before profiles existed, most recent first. `clippie profile list`
+    /// suggests making profiles of them.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub recent_db_paths: Vec<PathBuf>,
+    /// Serve Prometheus metrics on `127.0.0.1:<port>/metrics` from the daemon.
+    pub metrics_port: Option<u16>,
+    /// How often the daemon checks the clipboard.
+    pub polling: PollingConfig,
+    /// Seconds between WAL checkpoints in the daemon (default 300, 0 disables).
+    pub wal_checkpoint_secs: Option<u64>,
+    /// Milliseconds between keystrokes when `clippie tui --type` types an entry out.
+    pub type_delay_ms: Option<u64>,
+    /// Terminal emulator used by `clippie popup`.
+    pub popup_terminal: Option<PopupTerminal>,
+    pub api: ApiConfig,
+    /// Encrypt entries that look like secrets instead of storing them in plain text.
+    pub secret_encryption: Option<SecretEncryption>,
+    /// What happens to captures that look like passwords, tokens or keys.
+    pub sensitive_entries: SensitivePolicy,
+    /// Clear the system clipboard a while after a secret, or anything, is copied.
+    pub auto_clear: Option<AutoClearConfig>,
+    /// How to handle items copied by 1Password, Bitwarden and similar tools.
+    pub password_manager: PasswordManagerConfig,
+    /// Bundle ids of apps whose copies are never recorded, e.g. `com.apple.keychainaccess`.
+    pub excluded_apps: Vec<String>,
+    /// Regexes for content the daemon never records, e.g. `^\d{6}$` for one-time codes.
+    pub ignore_patterns: Vec<String>,
+    /// Daily JSON snapshots written by the daemon.
+    pub backup: Option<BackupConfig>,
+    /// Exchange new entries with the daemons on other machines.
+    pub sync: Option<SyncConfig>,
+    /// Rewrite URLs (strip tracking parameters, normalize, expand short links) on capture or copy.
+    pub url_rules: Vec<UrlRule>,
+    /// Have the daemon fetch the page title of copied URLs, shown in the TUI list in their place.
+    pub fetch_url_titles: bool,
+    /// Which differences are ignored when deciding whether a copy repeats an existing entry.
+    pub duplicates: DuplicatePolicy,
+    /// Reach the clipboard of the terminal's machine with OSC 52 escape sequences, e.g. over SSH.
+    pub osc52: Osc52Config,
+    pub tui: TuiConfig,
+}
+
+/// A named database, switched to with `clippie profile switch` or `b` in the TUI.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    /// A leading `~/` is expanded.
+    pub db_path: PathBuf,
+}
+
+/// Name of the profile that uses the top-level `db_path`.
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TuiConfig {
+    /// Milliseconds between ticks, which drive the loading spinner and auto-refresh.
+    pub tick_rate_ms: u64,
+    /// Reload the list when the daemon captures something while the TUI is open.
+    pub auto_refresh: bool,
+    /// Palette to draw with; `auto` asks the terminal for its background color.
+    pub background: Background,
+    /// Built-in theme to draw with instead of the `background` palette.
+    pub theme: Option<Theme>,
+    /// How the list and preview share the screen.
+    pub layout: Layout,
+    /// Percent of the width, or height when stacked, the list takes; `<` and `>` change it.
+    pub list_percent: u16,
+    /// With `secret_encryption` on, lock the TUI again after this many idle seconds (0 never re-locks).
+    pub lock_after_secs: u64,
+    /// Salted hash of the passphrase that unlocks the TUI, chosen the first time it locks.
+    pub lock_passphrase: Option<String>,
+    /// What goes between marked entries when they're copied as one.
+    pub join_separator: JoinSeparator,
+    /// Extra keys for list actions, e.g. `down = "ctrl-n"`; the built-in keys keep working.
+    pub keymap: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JoinSeparator {
+    #[default]
+    Newline,
+    Space,
+    None,
+}
+
+impl JoinSeparator {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JoinSeparator::Newline => "\n",
+            JoinSeparator::Space => " ",
+            JoinSeparator::None => "",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            JoinSeparator::Newline => "newline",
+            JoinSeparator::Space => "space",
+            JoinSeparator::None => "none",
+        }
+    }
+
+    /// The next separator when cycling with `s` in the TUI.
+    pub fn next(self) -> Self {
+        match self {
+            JoinSeparator::Newline => JoinSeparator::Space,
+            JoinSeparator::Space => JoinSeparator::None,
+            JoinSeparator::None => JoinSeparator::Newline,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Background {
+    #[default]
+    Auto,
+    Dark,
+    Light,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    Dark,
+    Light,
+    Solarized,
+    HighContrast,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 4] = [Theme::Dark, Theme::Light, Theme::Solarized, Theme::HighContrast];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Dark => "dark",
+            Theme::Light => "light",
+            Theme::Solarized => "solarized",
+            Theme::HighContrast => "high-contrast",
+        }
+    }
+
+    /// The next theme when cycling with `C` in the TUI.
+    pub fn next(self) -> Self {
+        let i = Theme::ALL.iter().position(|&theme| theme == self).unwrap_or(0);
+        Theme::ALL[(i + 1) % Theme::ALL.len()]
+    }
+}
+
+/// Values `tui.list_percent` may take, leaving both panes usable.
+pub const LIST_PERCENT: std::ops::RangeInclusive<u16> = 20..=80;
+
+/// How the TUI arranges the list and preview.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Layout {
+    /// Side by side, or stacked when the terminal is too narrow for two columns.
+    #[default]
+    Auto,
+    /// List on the left, preview on the right.
+    Split,
+    /// List above the preview.
+    Stacked,
+    /// The list alone; `Tab` shows the preview.
+    ListOnly,
+}
+
+impl Default for TuiConfig {
+    fn default() -> Self {
+        TuiConfig {
+            tick_rate_ms: 250,
+            auto_refresh: true,
+            background: Background::Auto,
+            theme: None,
+            layout: Layout::Auto,
+            list_percent: 50,
+            lock_after_secs: 300,
+            lock_passphrase: None,
+            join_separator: JoinSeparator::Newline,
+            keymap: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PasswordManagerConfig {
+    pub policy: PasswordManagerPolicy,
+    /// With `expire`, entries are deleted after this many seconds, matching the manager's clear timeout.
+    pub clear_after_secs: u64,
+}
+
+impl Default for PasswordManagerConfig {
+    fn default() -> Self {
+        PasswordManagerConfig { policy: PasswordManagerPolicy::default(), clear_after_secs: 30 }
+    }
+}
+
+/// The daemon polls every `min_interval_ms` for a minute after the clipboard changes, then
+/// backs off toward `max_interval_ms` while it stays the same.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PollingConfig {
+    pub min_interval_ms: u64,
+    pub max_interval_ms: u64,
+}
+
+impl Default for PollingConfig {
+    fn default() -> Self {
+        PollingConfig { min_interval_ms: 500, max_interval_ms: 3000 }
+    }
+}
+
+/// When the daemon empties the clipboard behind the user.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoClearConfig {
+    /// Seconds after the copy; copying something else first cancels the clear.
+    pub after_secs: u64,
+    /// Clear after every copy, not only those `secrets::is_sensitive` flags.
+    pub all_copies: bool,
+}
+
+impl Default for AutoClearConfig {
+    fn default() -> Self {
+        AutoClearConfig { after_secs: 30, all_copies: false }
+    }
+}
+
+/// Normalization applied to content before hashing, so near-identical copies share an entry.
+/// The first copy's text is the one kept.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DuplicatePolicy {
+    /// "foo" and "foo\n" are the same entry.
+    pub trim_trailing_whitespace: bool,
+    /// Runs of spaces, tabs and newlines compare as a single space.
+    pub collapse_whitespace: bool,
+    pub ignore_case: bool,
+}
+
+impl Default for DuplicatePolicy {
+    fn default() -> Self {
+        DuplicatePolicy { trim_trailing_whitespace: true, collapse_whitespace: false, ignore_case: false }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Osc52Config {
+    /// When copies are written to the terminal instead of `pbcopy`.
+    pub copy: Osc52Mode,
+    /// Also read the clipboard through the terminal when copies go to it. Off by default, since
+    /// many terminals refuse the request or prompt for it.
+    pub paste: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Osc52Mode {
+    /// In SSH sessions, and wherever `pbcopy` isn't installed.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PasswordManagerPolicy {
+    /// Never store password manager items.
+    #[default]
+    Skip,
+    /// Store them, then delete them once the manager would have cleared the clipboard.
+    Expire,
+    /// Store them like any other copy.
+    Keep,
+}
+
+/// What the daemon does with a capture larger than `max_entry_bytes`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OversizePolicy {
+    /// Don't store it.
+    Skip,
+    /// Store only its first `max_entry_bytes`.
+    #[default]
+    Truncate,
+    /// Store it gzipped, with its first `max_entry_bytes` kept as text for lists and search.
+    StoreCompressed,
+}
+
+/// How captures flagged by `secrets::is_sensitive` are stored and shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SensitivePolicy {
+    /// Store them, masked in the TUI until revealed.
+    #[default]
+    Mask,
+    /// Store and show them like any other entry.
+    Show,
+    /// Don't store them.
+    Skip,
+}
+
+/// Tool and key used to encrypt secret-flagged entries.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SecretEncryption {
+    pub tool: EncryptionTool,
+    /// gpg key id/email or age public key.
+    pub recipient: String,
+    /// age identity file used for decryption; gpg uses its agent instead.
+    #[serde(default)]
+    pub identity: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EncryptionTool {
+    Gpg,
+    Age,
+}
+
+/// Where and when the daemon writes backup snapshots.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupConfig {
+    /// Directory for the snapshots. A leading `~/` is expanded.
+    pub dir: PathBuf,
+    /// Local time of day to write a snapshot, as `HH:MM`.
+    #[serde(default = "default_backup_at")]
+    pub at: String,
+    /// Number of snapshots kept; older ones are deleted.
+    #[serde(default = "default_backup_keep")]
+    pub keep: usize,
+}
+
+fn default_backup_at() -> String {
+    "02:00".to_string()
+}
+
+fn default_backup_keep() -> usize {
+    30
+}
+
+/// Peers the daemon exchanges entries with, and the key they share.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncConfig {
+    /// Address to accept peers on, e.g. `0.0.0.0:7312`; without it the daemon only connects out.
+    pub listen: Option<String>,
+    /// `host:port` of the daemons to exchange entries with.
+    #[serde(default)]
+    pub peers: Vec<String>,
+    /// Pre-shared key, the same on every machine; it encrypts and authenticates the traffic.
+    pub key: String,
+    /// Seconds between exchanges with each peer.
+    #[serde(default = "default_sync_interval")]
+    pub interval_secs: u64,
+}
+
+fn default_sync_interval() -> u64 {
+    30
+}
+
+/// One URL cleanup rule; every field but `when` defaults to doing nothing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UrlRule {
+    /// Hosts (and their subdomains) the rule applies to; empty means every host.
+    #[serde(default)]
+    pub hosts: Vec<String>,
+    /// Query parameters to remove; a trailing `*` matches a prefix, as in `utm_*`.
+    #[serde(default)]
+    pub strip_params: Vec<String>,
+    /// Lowercase scheme and host and drop default ports.
+    #[serde(default)]
+    pub normalize: bool,
+    /// Resolve short links by following their redirect with curl.
+    #[serde(default)]
+    pub expand: bool,
+    #[serde(default)]
+    pub when: UrlCleanupStage,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UrlCleanupStage {
+    /// Before the daemon stores a capture.
+    #[default]
+    Capture,
+    /// When an entry is restored to the clipboard.
+    Copy,
+    Both,
+}
+
+/// Settings for the local HTTP API served by `clippie serve`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    pub port: u16,
+    /// Bearer token allowed to read history.
+    pub read_token: Option<String>,
+    /// Bearer token allowed to read, add and delete history.
+    pub write_token: Option<String>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        ApiConfig { port: 7311, read_token: None, write_token: None }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PopupTerminal {
+    Kitty,
+    Alacritty,
+    Iterm,
+    Terminal,
+    /// `$TERMINAL`, else the `x-terminal-emulator` Debian and Ubuntu point at the desktop's own.
+    System,
+}
+
+/// Terminal.app on macOS, which always has it; whatever the desktop uses elsewhere.
+impl Default for PopupTerminal {
+    fn default() -> Self {
+        if cfg!(target_os = "macos") { PopupTerminal::Terminal } else { PopupTerminal::System }
+    }
+}
+
+impl Config {
+    /// Checks the types alone can't express. The error names the key to fix.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |key: &str, reason: &str| Err(CliError::ConfigError(format!("{}: {}", key, reason)));
+        if self.polling.min_interval_ms == 0 {
+            return invalid("polling.min_interval_ms", "must be at least 1");
+        }
+        if self.polling.max_interval_ms < self.polling.min_interval_ms {
+            return invalid("polling.max_interval_ms", "must not be less than polling.min_interval_ms");
+        }
+        if self.max_entry_bytes == Some(0) {
+            return invalid("max_entry_bytes", "must be at least 1");
+        }
+        if self.tui.tick_rate_ms == 0 {
+            return invalid("tui.tick_rate_ms", "must be at least 1");
+        }
+        if !LIST_PERCENT.contains(&self.tui.list_percent) {
+            return invalid("tui.list_percent", &format!("must be from {} to {}", LIST_PERCENT.start(), LIST_PERCENT.end()));
+        }
+        if let Some(name) = &self.profile
+            && name != DEFAULT_PROFILE
+            && !self.profiles.contains_key(name)
+        {
+            return invalid("profile", &format!("no profile named '{}' in [profiles]", name));
+        }
+        if let Some(name) = self.profiles.keys().find(|name| !valid_profile_name(name)) {
+            return invalid(&format!("profiles.{}", name), "profile names are letters, digits, '-' and '_'");
+        }
+        if let Some(backup) = &self.backup
+            && NaiveTime::parse_from_str(&backup.at, "%H:%M").is_err()
+        {
+            return invalid("backup.at", "must be a time of day as HH:MM");
+        }
+        if let Some(sync) = &self.sync {
+            if sync.key.len() < 16 {
+                return invalid("sync.key", "must be at least 16 characters; 'clippie sync key' makes one");
+            }
+            if sync.interval_secs == 0 {
+                return invalid("sync.interval_secs", "must be at least 1");
+            }
+            if let Some(peer) = sync.peers.iter().find(|peer| !valid_peer(peer)) {
+                return invalid("sync.peers", &format!("{:?} is not host:port", peer));
+            }
+        }
+        crate::tui::keymap::Keymap::new(&self.tui.keymap)?;
+        Ok(())
+    }
+}
+
+/// Parse and validate `config.toml`. Unknown keys are errors, so a typo isn't silently ignored.
+pub fn parse_config(text: &str) -> Result<Config> {
+    let mut unknown = Vec::new();
+    let mut record_unknown = |path: serde_ignored::Path| unknown.push(path.to_string());
+    let deserializer = serde_ignored::Deserializer::new(toml::Deserializer::new(text), &mut record_unknown);
+    let config: Config = serde_path_to_error::deserialize(deserializer).map_err(|e| {
+        let line = e.inner().span().map(|span| text[..span.start].matches('\n').count() + 1);
+        let key = e.path().to_string();
+        let location = match (line, key.as_str()) {
+            (Some(line), ".") => format!("line {}", line),
+            (Some(line), key) => format!("line {}, {}", line, key),
+            (None, key) => key.to_string(),
+        };
+        CliError::ConfigError(format!("{}: {}", location, e.inner().message()))
+    })?;
+    if let Some(key) = unknown.first() {
+        return Err(CliError::ConfigError(format!("{}: unknown key", key)));
+    }
+    config.validate()?;
+    Ok(config)
+}
+
+/// Profile names appear in paths and on the command line, so they're kept plain.
+/// `host:port`, with a port number.
+fn valid_peer(peer: &str) -> bool {
+    peer.rsplit_once(':').is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok())
+}
+
+pub fn valid_profile_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// `--profile`, when given; it wins over the saved `profile` for this process.
+static PROFILE_OVERRIDE: OnceCell<String> = OnceCell::new();
+
+/// Whether `key`, dotted as in `tui.background`, names a setting.
+pub fn is_known_key(key: &str) -> bool {
+    let mut value = toml::Value::Integer(0);
+    for part in key.rsplit('.') {
+        value = toml::Value::Table(toml::map::Map::from_iter([(part.to_string(), value)]));
+    }
+    // Only the key matters: a value of the wrong type fails after unknown keys are seen.
+    let mut unknown = false;
+    let _ = Config::deserialize(serde_ignored::Deserializer::new(value, &mut |_: serde_ignored::Path| unknown = true));
+    !unknown
+}
+
+fn write_config(path: &Path, config: &Config) -> Result<()> {
+    let text = toml::to_string_pretty(config).map_err(|e| CliError::ConfigError(e.to_string()))?;
+    write_private(path, &text)
+}
+
+fn write_private(path: &Path, text: &str) -> Result<()> {
+    std::fs::write(path, text)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600));
+    }
+    Ok(())
+}
+
+/// Rewrite the `legacy` JSON config as TOML at `path`, keeping the old file as `config.json.bak`.
+/// Without a legacy config, the defaults.
+fn migrate_json_config(legacy: &Path, path: &Path) -> Result<Config> {
+    let Ok(text) = std::fs::read_to_string(legacy) else {
+        return Ok(Config::default());
+    };
+    let config: Config = serde_json::from_str(&text)?;
+    write_config(path, &config)?;
+    match std::fs::rename(legacy, legacy.with_extension("json.bak")) {
+        // Another clippie process migrated it first.
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+    config.validate()?;
+    Ok(config)
+}
 
 pub struct ConfigManager;
 
@@ -15,17 +595,199 @@ impl ConfigManager {
     }
 
     pub fn get_db_path(&self) -> Result<PathBuf> {
+        let config = self.load()?;
+        self.profile_db_path(&config, &self.active_profile_in(&config))
+    }
+
+    /// Use the profile `name` instead of the saved one for the rest of this process.
+    pub fn use_profile(name: &str) {
+        let _ = PROFILE_OVERRIDE.set(name.to_string());
+    }
+
+    /// `--profile`, else the saved `profile`, else `default`.
+    pub fn active_profile(&self) -> Result<String> {
+        Ok(self.active_profile_in(&self.load()?))
+    }
+
+    fn active_profile_in(&self, config: &Config) -> String {
+        PROFILE_OVERRIDE
+            .get()
+            .or(config.profile.as_ref())
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+    }
+
+    fn profile_db_path(&self, config: &Config, name: &str) -> Result<PathBuf> {
+        if name == DEFAULT_PROFILE {
+            return match &config.db_path {
+                Some(path) => expand_home(path),
+                None => self.default_db_path(),
+            };
+        }
+        match config.profiles.get(name) {
+            Some(profile) => expand_home(&profile.db_path),
+            None => Err(CliError::ConfigError(format!(
+                "no profile named '{}'; 'clippie profile list' shows them",
+                name
+            ))),
+        }
+    }
+
+    /// Every profile and its database, `default` first.
+    pub fn profiles(&self) -> Result<Vec<(String, PathBuf)>> {
+        let config = self.load()?;
+        std::iter::once(DEFAULT_PROFILE)
+            .chain(config.profiles.keys().map(String::as_str))
+            .map(|name| Ok((name.to_string(), self.profile_db_path(&config, name)?)))
+            .collect()
+    }
+
+    /// Add the profile `name`, with its database at `db_path` or `~/.clippie/<name>.db`.
+    pub fn create_profile(&self, name: &str, db_path: Option<&Path>) -> Result<PathBuf> {
+        if !valid_profile_name(name) {
+            return Err(CliError::InvalidInput(format!(
+                "'{}' is not a valid profile name; use letters, digits, '-' and '_'",
+                name
+            )));
+        }
+        let mut config = self.load()?;
+        if name == DEFAULT_PROFILE || config.profiles.contains_key(name) {
+            return Err(CliError::InvalidInput(format!("Profile '{}' already exists", name)));
+        }
+        let db_path = match db_path {
+            Some(path) => path.to_path_buf(),
+            None => self.get_clippie_dir()?.join(format!("{}.db", name)),
+        };
+        config.profiles.insert(name.to_string(), Profile { db_path: db_path.clone() });
+        self.save(&config)?;
+        expand_home(&db_path)
+    }
+
+    /// Make `name` the profile used when `--profile` isn't given. Returns its database.
+    pub fn switch_profile(&self, name: &str) -> Result<PathBuf> {
+        let mut config = self.load()?;
+        let db_path = self.profile_db_path(&config, name)?;
+        config.profile = (name != DEFAULT_PROFILE).then(|| name.to_string());
+        self.save(&config)?;
+        Ok(db_path)
+    }
+
+    pub fn default_db_path(&self) -> Result<PathBuf> {
         Ok(self.get_clippie_dir()?.join("clipboard.db"))
     }
 
+    /// Where the daemon listens for `clippie pause`, `status` and friends.
+    /// Key for `secrets::keyed_hash`, generated into `~/.clippie/hash.key` on first use.
+    pub fn hash_key(&self) -> Result<String> {
+        let path = self.get_clippie_dir()?.join("hash.key");
+        match std::fs::read_to_string(&path) {
+            Ok(key) if !key.trim().is_empty() => Ok(key.trim().to_string()),
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => {
+                let key = generate_token()?;
+                std::fs::create_dir_all(self.get_clippie_dir()?)?;
+                write_private(&path, &key)?;
+                Ok(key)
+            }
+        }
+    }
+
+    pub fn get_socket_path(&self) -> Result<PathBuf> {
+        Ok(self.get_clippie_dir()?.join("daemon.sock"))
+    }
+
+    pub fn get_config_path(&self) -> Result<PathBuf> {
+        Ok(self.get_clippie_dir()?.join("config.toml"))
+    }
+
+    /// `config.json`, used before the config moved to TOML.
+    fn get_legacy_config_path(&self) -> Result<PathBuf> {
+        Ok(self.get_clippie_dir()?.join("config.json"))
+    }
+
+    pub fn load(&self) -> Result<Config> {
+        let path = self.get_config_path()?;
+        if !path.exists() {
+            return migrate_json_config(&self.get_legacy_config_path()?, &path);
+        }
+        parse_config(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, config: &Config) -> Result<()> {
+        std::fs::create_dir_all(self.get_clippie_dir()?)?;
+        write_config(&self.get_config_path()?, config)
+    }
+
+    /// Validate `text` and write it out as is, keeping comments and layout that `save` would drop.
+    pub fn save_text(&self, text: &str) -> Result<Config> {
+        let config = parse_config(text)?;
+        std::fs::create_dir_all(self.get_clippie_dir()?)?;
+        write_private(&self.get_config_path()?, text)?;
+        Ok(config)
+    }
+
+    /// Load the config, generating and saving API tokens if they are missing.
+    pub fn ensure_api_tokens(&self) -> Result<Config> {
+        let mut config = self.load()?;
+        if config.api.read_token.is_some() && config.api.write_token.is_some() {
+            return Ok(config);
+        }
+        config.api.read_token.get_or_insert(generate_token()?);
+        config.api.write_token.get_or_insert(generate_token()?);
+        self.save(&config)?;
+        Ok(config)
+    }
+
     pub fn exists(&self) -> bool {
         self.get_db_path().map(|p| p.exists()).unwrap_or(false)
     }
 
+    /// True while `clippie pause` or an unexpired `clippie incognito` is in effect.
     pub fn is_paused(&self) -> bool {
-        self.get_clippie_dir()
-            .map(|p| p.join("paused").exists())
-            .unwrap_or(false)
+        self.incognito() != Incognito::Off
+    }
+
+    /// Read the pause and incognito flags, removing the latter once its timer has run out.
+    pub fn incognito(&self) -> Incognito {
+        let Ok(dir) = self.get_clippie_dir() else {
+            return Incognito::Off;
+        };
+        if dir.join("paused").exists() {
+            return Incognito::Paused;
+        }
+        let path = dir.join("incognito");
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            return Incognito::Off;
+        };
+        match contents.trim().parse::<i64>().ok().and_then(|ts| DateTime::from_timestamp(ts, 0)) {
+            None => Incognito::Indefinite,
+            Some(until) if until > Utc::now() => Incognito::Until(until),
+            Some(_) => {
+                let _ = std::fs::remove_file(&path);
+                Incognito::Off
+            }
+        }
+    }
+
+    /// Turn incognito on until `until`, or until `clippie resume` when `None`.
+    pub fn set_incognito(&self, until: Option<DateTime<Utc>>) -> Result<()> {
+        let path = self.get_clippie_dir()?.join("incognito");
+        let contents = until.map(|t| t.timestamp().to_string()).unwrap_or_default();
+        std::fs::write(&path, contents)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+        }
+        Ok(())
+    }
+
+    pub fn clear_incognito(&self) -> Result<()> {
+        let path = self.get_clippie_dir()?.join("incognito");
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        Ok(())
     }
 
     pub fn set_paused(&self, paused: bool) -> Result<()> {
@@ -44,6 +806,52 @@ impl ConfigManager {
     }
 }
 
+/// Capture suppression set by `clippie pause` or `clippie incognito`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Incognito {
+    Off,
+    /// `clippie pause` without a timer.
+    Paused,
+    /// Until `clippie resume`.
+    Indefinite,
+    Until(DateTime<Utc>),
+}
+
+impl Incognito {
+    /// Badge for the TUI header, `None` when off.
+    pub fn label(&self) -> Option<String> {
+        match self {
+            Incognito::Off => None,
+            Incognito::Paused => Some("PAUSED".to_string()),
+            Incognito::Indefinite => Some("INCOGNITO".to_string()),
+            Incognito::Until(until) => Some(format!("INCOGNITO until {}", until.with_timezone(&Local).format("%H:%M"))),
+        }
+    }
+}
+
+/// Resolve a leading `~/` against the home directory.
+pub fn expand_home(path: &std::path::Path) -> Result<PathBuf> {
+    match path.strip_prefix("~") {
+        Ok(rest) => dirs::home_dir()
+            .map(|home| home.join(rest))
+            .ok_or(CliError::ConfigError("Could not determine home directory".to_string())),
+        Err(_) => Ok(path.to_path_buf()),
+    }
+}
+
+pub(crate) fn generate_token() -> Result<String> {
+    let mut bytes = [0u8; 24];
+    random_bytes(&mut bytes)?;
+    Ok(hex::encode(bytes))
+}
+
+pub(crate) fn random_bytes(bytes: &mut [u8]) -> Result<()> {
+    use std::io::Read;
+
+    std::fs::File::open("/dev/urandom")?.read_exact(bytes)?;
+    Ok(())
+}
+
 impl Default for ConfigManager {
     fn default() -> Self {
         Self::new().expect("Failed to initialize config manager")
@@ -58,4 +866,125 @@ mod tests {
     fn test_config_manager_creation() {
         assert!(ConfigManager::new().is_ok());
     }
+
+    #[test]
+    fn test_config_defaults_for_missing_keys() {
+        let config = parse_config("").unwrap();
+        assert_eq!(config, Config::default());
+        assert!(config.metrics_port.is_none());
+        assert_eq!(config.api.port, 7311);
+    }
+
+    #[test]
+    fn test_config_round_trips_through_toml() {
+        let config = Config {
+            db_path: Some(PathBuf::from("~/clip.db")),
+            max_entries: Some(5000),
+            secret_encryption: Some(SecretEncryption {
+                tool: EncryptionTool::Age,
+                recipient: "age1xyz".to_string(),
+                identity: None,
+            }),
+            url_rules: vec![UrlRule {
+                hosts: vec!["t.co".to_string()],
+                strip_params: vec![],
+                normalize: false,
+                expand: true,
+                when: UrlCleanupStage::Copy,
+            }],
+            tui: TuiConfig {
+                keymap: BTreeMap::from([("down".to_string(), "ctrl-n".to_string())]),
+                theme: Some(Theme::HighContrast),
+                layout: Layout::ListOnly,
+                ..TuiConfig::default()
+            },
+            ..Config::default()
+        };
+        let text = toml::to_string_pretty(&config).unwrap();
+        assert!(text.contains("theme = \"high-contrast\""), "{}", text);
+        assert!(text.contains("layout = \"list-only\""), "{}", text);
+        assert_eq!(parse_config(&text).unwrap(), config);
+    }
+
+    #[test]
+    fn test_theme_cycles_through_all() {
+        let mut theme = Theme::Dark;
+        let names: Vec<&str> = (0..Theme::ALL.len())
+            .map(|_| {
+                theme = theme.next();
+                theme.name()
+            })
+            .collect();
+        assert_eq!(names, ["light", "solarized", "high-contrast", "dark"]);
+    }
+
+    #[test]
+    fn test_config_errors_name_the_key() {
+        let error = |text: &str| parse_config(text).unwrap_err().to_string();
+        assert!(error("max_entries = 10\n[tui]\nbackground = \"blue\"").contains("line 3, tui.background: unknown variant `blue`"));
+        assert!(error("[polling]\nmin_interval_ms = \"fast\"").contains("polling.min_interval_ms: invalid type"));
+        assert!(error("[tui]\nauto_refrsh = false").contains("tui.auto_refrsh: unknown key"));
+        assert!(error("[polling]\nmin_interval_ms = 900\nmax_interval_ms = 800").contains("polling.max_interval_ms: must not be less"));
+        assert!(error("[backup]\ndir = \"/tmp\"\nat = \"2am\"").contains("backup.at: must be a time of day"));
+        assert!(error("[tui]\nlist_percent = 95").contains("tui.list_percent: must be from 20 to 80"));
+        assert!(error("max_entries = ").contains("line 1"));
+        assert!(error("[sync]\nkey = \"short\"").contains("sync.key: must be at least 16"));
+        assert!(error("[sync]\nkey = \"0123456789abcdef\"\npeers = [\"laptop\"]").contains("sync.peers: \"laptop\" is not host:port"));
+    }
+
+    #[test]
+    fn test_profiles_must_exist() {
+        let mut config = Config { profile: Some("work".to_string()), ..Config::default() };
+        assert!(config.validate().unwrap_err().to_string().contains("profile: no profile named 'work'"));
+        config.profiles.insert("work".to_string(), Profile { db_path: PathBuf::from("~/work.db") });
+        config.validate().unwrap();
+        config.profiles.insert("my work".to_string(), Profile { db_path: PathBuf::from("/tmp/w.db") });
+        assert!(config.validate().unwrap_err().to_string().contains("profiles.my work:"));
+
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(ConfigManager.profile_db_path(&config, "work").unwrap(), home.join("work.db"));
+        assert_eq!(ConfigManager.profile_db_path(&config, "default").unwrap(), home.join(".clippie/clipboard.db"));
+        assert!(ConfigManager.profile_db_path(&config, "home").is_err());
+    }
+
+    #[test]
+    fn test_is_known_key() {
+        assert!(is_known_key("max_entries"));
+        assert!(is_known_key("polling.min_interval_ms"));
+        assert!(is_known_key("backup.dir"));
+        assert!(is_known_key("tui.keymap.down"));
+        assert!(is_known_key("tui"));
+        assert!(!is_known_key("retention.max_days"));
+        assert!(!is_known_key("tui.colour"));
+    }
+
+    #[test]
+    fn test_json_config_is_migrated() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        std::fs::write(dir.join("config.json"), r#"{"max_entries": 50, "tui": {"background": "dark"}}"#).unwrap();
+
+        let config = migrate_json_config(&dir.join("config.json"), &dir.join("config.toml")).unwrap();
+        assert_eq!(config.max_entries, Some(50));
+        assert_eq!(config.tui.background, Background::Dark);
+        assert!(!dir.join("config.json").exists());
+        assert!(dir.join("config.json.bak").exists());
+        let toml = std::fs::read_to_string(dir.join("config.toml")).unwrap();
+        assert_eq!(parse_config(&toml).unwrap(), config);
+    }
+
+    #[test]
+    fn test_expand_home() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand_home(std::path::Path::new("~/x/clip.db")).unwrap(), home.join("x/clip.db"));
+        assert_eq!(expand_home(std::path::Path::new("/tmp/clip.db")).unwrap(), PathBuf::from("/tmp/clip.db"));
+    }
+
+    #[test]
+    fn test_generate_token_is_random_hex() {
+        let a = generate_token().unwrap();
+        let b = generate_token().unwrap();
+        assert_eq!(a.len(), 48);
+        assert_ne!(a, b);
+    }
 }
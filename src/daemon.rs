@@ -1,53 +1,551 @@
-use crate::clipboard::{get_clipboard_content, hash_content};
-use crate::config::ConfigManager;
-use crate::db::Database;
-use crate::error::Result;
-use std::time::Duration;
+use crate::backup::Backup;
+use crate::clipboard::{
+    duplicate_hash, frontmost_app, get_clipboard_content, get_pasteboard_files, get_pasteboard_flavors, set_clipboard_content, Flavor, MAX_FLAVOR_BYTES, RICH_TEXT_TYPES, SourceApp, get_pasteboard_types, hash_content, is_password_manager_item, FILE_URL_TYPE,
+};
+use crate::config::{
+    AutoClearConfig, Config, ConfigManager, DuplicatePolicy, OversizePolicy, PasswordManagerConfig, PasswordManagerPolicy, SecretEncryption,
+    PollingConfig, SensitivePolicy, UrlRule,
+};
+use crate::db::{Database, NewEntry};
+use crate::error::{CliError, Result};
+use crate::ipc::{self, Request};
+use crate::kind::Kind;
+use crate::metrics::{self, Metrics};
+use crate::notify::notify;
+use crate::sync::{self, SyncStatus};
+use crate::titles;
+use crate::{compress, secrets};
+use crate::urls;
+use chrono::Utc;
+use regex::RegexSet;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::mpsc::{channel, sync_channel, Receiver, RecvTimeoutError, Sender, SyncSender};
+use std::time::{Duration, Instant};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::broadcast;
 use tokio::time::sleep;
 
-const CHECK_INTERVAL: Duration = Duration::from_millis(500);
-const STABILITY_DELAY: Duration = Duration::from_millis(500);
+/// Polling stays at the minimum interval for this long after the clipboard changes.
+const ACTIVE_PERIOD: Duration = Duration::from_secs(60);
+/// Cap on the idle interval, well inside the window after which `/healthz` calls capture stalled.
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(metrics::HEALTH_STALE_AFTER_SECS as u64 / 2);
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(1);
+const CAPTURE_QUEUE_SIZE: usize = 64;
+const MAX_BATCH: usize = 64;
+const FAILURE_NOTIFY_THRESHOLD: u32 = 5;
+const DEFAULT_WAL_CHECKPOINT_SECS: u64 = 300;
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(4);
+/// Events kept for subscribers that are slow to read; older ones are dropped as `lagged`.
+const EVENT_BUFFER: usize = 16;
 
+/// Counts consecutive failures so a notification fires once per outage.
+#[derive(Debug, Default)]
+struct FailureTracker {
+    consecutive: u32,
+    notified: bool,
+}
+
+impl FailureTracker {
+    /// Returns true when the failure streak first reaches the threshold.
+    fn record_failure(&mut self) -> bool {
+        self.consecutive += 1;
+        if self.consecutive >= FAILURE_NOTIFY_THRESHOLD && !self.notified {
+            self.notified = true;
+            return true;
+        }
+        false
+    }
+
+    fn record_success(&mut self) {
+        self.consecutive = 0;
+        self.notified = false;
+    }
+}
+
+/// Bundle ids compare case-insensitively, since users type them by hand.
+fn is_excluded_app(app: Option<&str>, excluded: &[String]) -> bool {
+    app.is_some_and(|app| excluded.iter().any(|id| id.eq_ignore_ascii_case(app)))
+}
+
+/// Compile `ignore_patterns`, reporting and leaving out any that don't parse.
+pub(crate) fn ignore_set(patterns: &[String]) -> RegexSet {
+    let valid = patterns.iter().filter(|pattern| match regex::Regex::new(pattern) {
+        Ok(_) => true,
+        Err(e) => {
+            eprintln!("Ignoring invalid ignore pattern {:?}: {}", pattern, e);
+            false
+        }
+    });
+    RegexSet::new(valid).expect("patterns were checked one by one")
+}
+
+/// The longest prefix of `text` within `max` bytes that ends on a character boundary.
+fn truncate_bytes(text: &str, max: usize) -> &str {
+    let end = (0..=max.min(text.len())).rev().find(|&i| text.is_char_boundary(i)).unwrap_or(0);
+    &text[..end]
+}
+
+/// Poll interval: `min` while the clipboard is in use, doubling up to `max` once it's idle.
+#[derive(Debug)]
+struct Backoff {
+    min: Duration,
+    max: Duration,
+    current: Duration,
+    last_change: Instant,
+}
+
+impl Backoff {
+    fn new(settings: PollingConfig) -> Self {
+        let min = Duration::from_millis(settings.min_interval_ms.max(1)).min(MAX_POLL_INTERVAL);
+        let max = Duration::from_millis(settings.max_interval_ms).clamp(min, MAX_POLL_INTERVAL);
+        Backoff { min, max, current: min, last_change: Instant::now() }
+    }
+
+    fn changed(&mut self, now: Instant) {
+        self.last_change = now;
+        self.current = self.min;
+    }
+
+    /// How long to wait before the next poll at `now`.
+    fn next(&mut self, now: Instant) -> Duration {
+        self.current = if now.duration_since(self.last_change) < ACTIVE_PERIOD {
+            self.min
+        } else {
+            (self.current * 2).min(self.max)
+        };
+        self.current
+    }
+}
+
+/// Timer for `auto_clear`: armed by a copy it applies to, disarmed by any other change.
+#[derive(Debug)]
+struct AutoClear {
+    settings: AutoClearConfig,
+    deadline: Option<Instant>,
+}
+
+impl AutoClear {
+    /// New clipboard content appeared at `now`.
+    fn copied(&mut self, content: &str, now: Instant) {
+        self.deadline = (self.settings.all_copies || secrets::is_sensitive(content))
+            .then(|| now + Duration::from_secs(self.settings.after_secs));
+    }
+
+    /// Whether the clipboard should be cleared at `now`; true once per armed copy.
+    fn due(&mut self, now: Instant) -> bool {
+        self.deadline.take_if(|deadline| *deadline <= now).is_some()
+    }
+}
+
+/// A clipboard value that stayed put for a full poll interval.
+#[derive(Debug, Clone)]
+struct Capture {
+    content: String,
+    /// Files copied in Finder, with `content` holding their paths.
+    files: bool,
+    /// RTF or HTML of formatted text.
+    flavors: Vec<Flavor>,
+    from_password_manager: bool,
+    source: Option<SourceApp>,
+}
+
+/// Work for the writer thread, in the order it was queued.
+enum Job {
+    Capture(Capture),
+    /// Checkpoint once everything queued before it is written, then report whether that worked.
+    Flush(Sender<bool>),
+    /// Like `Flush`, then stop the writer thread.
+    Shutdown(Sender<bool>),
+}
+
+/// Polls the clipboard and hands stable values to the writer; never touches the database.
 pub struct DaemonState {
-    db: Database,
     last_hash: Option<String>,
+    /// Latest new value, queued once it's still on the clipboard at the next poll.
+    pending: Option<String>,
     config: ConfigManager,
+    password_manager: PasswordManagerPolicy,
+    excluded_apps: Vec<String>,
+    /// Captures matching any of these, once trimmed, aren't recorded.
+    ignore_patterns: RegexSet,
+    metrics: Arc<Metrics>,
+    clipboard_failures: FailureTracker,
+    captures: SyncSender<Job>,
+    auto_clear: Option<AutoClear>,
+    backoff: Backoff,
 }
 
 impl DaemonState {
-    pub fn new(db: Database, config: ConfigManager) -> Self {
-        DaemonState { db, last_hash: None, config }
+    fn new(config: ConfigManager, metrics: Arc<Metrics>, captures: SyncSender<Job>) -> Self {
+        let settings = config.load().unwrap_or_default();
+        DaemonState {
+            last_hash: None,
+            pending: None,
+            config,
+            password_manager: settings.password_manager.policy,
+            excluded_apps: settings.excluded_apps,
+            ignore_patterns: ignore_set(&settings.ignore_patterns),
+            metrics,
+            clipboard_failures: FailureTracker::default(),
+            captures,
+            auto_clear: settings.auto_clear.map(|settings| AutoClear { settings, deadline: None }),
+            backoff: Backoff::new(settings.polling),
+        }
     }
 
-    pub async fn run(&mut self) -> Result<()> {
+    /// Poll until `shutdown` resolves. A value seen but not yet queued gets one more poll
+    /// to finish its stability check, so quitting right after a copy doesn't lose it.
+    pub async fn run(&mut self, shutdown: impl Future<Output = ()>) -> Result<()> {
+        tokio::pin!(shutdown);
         loop {
-            if let Ok(Some(content)) = get_clipboard_content() {
+            self.poll().await;
+            let now = Instant::now();
+            let mut interval = self.backoff.next(now);
+            // Wake for a pending auto-clear rather than sleeping past it.
+            if let Some(deadline) = self.auto_clear.as_ref().and_then(|auto_clear| auto_clear.deadline) {
+                interval = interval.min(deadline.saturating_duration_since(now));
+            }
+            tokio::select! {
+                _ = &mut shutdown => break,
+                _ = sleep(interval) => {}
+            }
+        }
+        if self.pending.is_some() {
+            self.poll().await;
+        }
+        Ok(())
+    }
+
+    async fn poll(&mut self) {
+        // pbpaste and AppKit can stall; keep them off the async workers.
+        let polled = tokio::task::spawn_blocking(get_clipboard_content)
+            .await
+            .unwrap_or_else(|e| Err(CliError::ClipboardError(e.to_string())));
+        match polled {
+            Ok(Some(content)) => {
+                self.metrics.record_poll();
+                self.clipboard_failures.record_success();
                 let hash = hash_content(&content);
                 if self.last_hash.as_ref() != Some(&hash) {
+                    self.backoff.changed(Instant::now());
+                    if let Some(auto_clear) = &mut self.auto_clear {
+                        auto_clear.copied(&content, Instant::now());
+                    }
                     self.last_hash = Some(hash);
-                    self.try_save_content(&content).await;
+                    self.pending = Some(content);
+                } else if let Some(stable) = self.pending.take() {
+                    self.enqueue(stable).await;
+                }
+            }
+            Ok(None) => {
+                self.metrics.record_poll();
+                self.clipboard_failures.record_success();
+                self.pending = None;
+                if let Some(auto_clear) = &mut self.auto_clear {
+                    auto_clear.deadline = None;
+                }
+            }
+            Err(e) => {
+                self.metrics.record_error();
+                if self.clipboard_failures.record_failure() {
+                    notify("Clippie can't read the clipboard", &e.to_string());
                 }
             }
-            sleep(CHECK_INTERVAL).await;
+        }
+        if self.auto_clear.as_mut().is_some_and(|auto_clear| auto_clear.due(Instant::now())) {
+            self.clear_clipboard().await;
+        }
+    }
+
+    /// Empty the clipboard for `auto_clear`. Forgetting the cleared value lets copying it again re-arm the timer.
+    async fn clear_clipboard(&mut self) {
+        let cleared = tokio::task::spawn_blocking(|| set_clipboard_content(""))
+            .await
+            .unwrap_or_else(|e| Err(CliError::ClipboardError(e.to_string())));
+        match cleared {
+            Ok(()) => self.last_hash = None,
+            Err(_) => self.metrics.record_error(),
         }
     }
 
-    async fn try_save_content(&self, content: &str) {
-        if content.trim().is_empty() || self.config.is_paused() {
+    async fn enqueue(&mut self, content: String) {
+        if content.trim().is_empty() || self.config.is_paused() || self.ignore_patterns.is_match(content.trim()) {
+            return;
+        }
+
+        let (from_password_manager, files, flavors, source) = tokio::task::spawn_blocking(|| {
+            let types = get_pasteboard_types();
+            let has = |wanted: &[&str]| types.iter().any(|t| wanted.contains(&t.as_str()));
+            let files = if has(&[FILE_URL_TYPE]) { get_pasteboard_files() } else { Vec::new() };
+            let flavors = if files.is_empty() && has(&RICH_TEXT_TYPES) { get_pasteboard_flavors() } else { Vec::new() };
+            (is_password_manager_item(&types), files, flavors, frontmost_app())
+        })
+        .await
+        .unwrap_or_default();
+        if from_password_manager && self.password_manager == PasswordManagerPolicy::Skip {
+            return;
+        }
+        if is_excluded_app(source.as_ref().map(|app| app.bundle_id.as_str()), &self.excluded_apps) {
             return;
         }
 
-        sleep(STABILITY_DELAY).await;
+        // The text Finder puts on the pasteboard is only the file names; keep the full paths instead.
+        let (content, files) = if files.is_empty() { (content, false) } else { (files.join("\n"), true) };
+        // A full queue means the writer is wedged; dropping keeps polling responsive.
+        if self.captures.try_send(Job::Capture(Capture { content, files, flavors, from_password_manager, source })).is_err() {
+            self.metrics.record_error();
+        }
+    }
+}
+
+/// Owns the database connection: batches queued captures into transactions and runs maintenance.
+struct Writer {
+    db: Database,
+    encryption: Option<SecretEncryption>,
+    /// Key for the content hash of encrypted entries, loaded when `encryption` is set.
+    hash_key: Option<String>,
+    sensitive_entries: SensitivePolicy,
+    password_manager: PasswordManagerConfig,
+    checkpoint_interval: Option<Duration>,
+    last_checkpoint: Instant,
+    max_age_days: Option<u32>,
+    max_entries: Option<usize>,
+    max_db_size_mb: Option<u64>,
+    max_entry_bytes: Option<usize>,
+    oversize_policy: OversizePolicy,
+    backup: Option<Backup>,
+    url_rules: Vec<UrlRule>,
+    duplicates: DuplicatePolicy,
+    metrics: Arc<Metrics>,
+    db_failures: FailureTracker,
+    /// Tells control socket subscribers, like an open TUI, that new entries were written.
+    events: Option<broadcast::Sender<Value>>,
+}
+
+impl Writer {
+    fn new(db: Database, settings: Config, metrics: Arc<Metrics>) -> Self {
+        Writer {
+            db,
+            encryption: settings.secret_encryption,
+            hash_key: None,
+            sensitive_entries: settings.sensitive_entries,
+            password_manager: settings.password_manager,
+            checkpoint_interval: match settings.wal_checkpoint_secs.unwrap_or(DEFAULT_WAL_CHECKPOINT_SECS) {
+                0 => None,
+                secs => Some(Duration::from_secs(secs)),
+            },
+            last_checkpoint: Instant::now(),
+            max_age_days: settings.max_age_days,
+            max_entries: settings.max_entries,
+            max_db_size_mb: settings.max_db_size_mb,
+            max_entry_bytes: settings.max_entry_bytes,
+            oversize_policy: settings.oversize_policy,
+            backup: settings.backup.as_ref().and_then(|backup| match Backup::new(backup) {
+                Ok(backup) => Some(backup),
+                Err(e) => {
+                    eprintln!("Backups disabled: {}", e);
+                    None
+                }
+            }),
+            url_rules: settings.url_rules,
+            duplicates: settings.duplicates,
+            metrics,
+            db_failures: FailureTracker::default(),
+            events: None,
+        }
+    }
+
+    fn run(mut self, jobs: Receiver<Job>) {
+        loop {
+            match jobs.recv_timeout(MAINTENANCE_INTERVAL) {
+                Ok(first) => {
+                    let mut batch = Vec::new();
+                    let mut flushes = Vec::new();
+                    let mut shutdown = None;
+                    for job in std::iter::once(first).chain(jobs.try_iter().take(MAX_BATCH - 1)) {
+                        match job {
+                            Job::Capture(capture) => batch.push(capture),
+                            Job::Flush(done) => flushes.push(done),
+                            Job::Shutdown(done) => shutdown = Some(done),
+                        }
+                    }
+                    self.write_batch(batch);
+                    for done in flushes {
+                        let _ = done.send(self.flush());
+                    }
+                    if let Some(done) = shutdown {
+                        let _ = done.send(self.flush());
+                        return;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+            self.maintain();
+        }
+    }
+
+    fn write_batch(&mut self, batch: Vec<Capture>) {
+        let mut entries = Vec::with_capacity(batch.len());
+        for capture in batch {
+            match self.prepare(capture) {
+                Ok(Some(entry)) => entries.push(entry),
+                Ok(None) => {}
+                Err(e) => self.record_failure(&e),
+            }
+        }
+        if entries.is_empty() {
+            return;
+        }
 
-        if let Ok(Some(new_content)) = get_clipboard_content() {
-            if new_content == content {
-                let hash = hash_content(content);
-                let _ = self.db.insert_entry(content, &hash);
+        match self.db.insert_many(&entries) {
+            Ok(_) => {
+                entries.iter().for_each(|_| self.metrics.record_capture());
+                self.db_failures.record_success();
+                if let Some(events) = &self.events {
+                    let _ = events.send(json!({ "event": "capture", "count": entries.len() }));
+                }
             }
+            Err(e) => self.record_failure(&e),
+        }
+    }
+
+    /// Clean up URLs, apply the size limit, encrypt secrets, note the source app and stamp password
+    /// manager items with their expiry. `None` when the oversize or sensitive policy drops the capture.
+    fn prepare(&self, capture: Capture) -> Result<Option<NewEntry>> {
+        let content = urls::clean(&capture.content, &self.url_rules, urls::Stage::Capture);
+        if self.sensitive_entries == SensitivePolicy::Skip && secrets::is_sensitive(&content) {
+            return Ok(None);
+        }
+        let oversized = self.max_entry_bytes.filter(|&max| content.len() > max);
+        let content = match (oversized, self.oversize_policy) {
+            (Some(_), OversizePolicy::Skip) => return Ok(None),
+            (Some(max), OversizePolicy::Truncate) => truncate_bytes(&content, max).to_string(),
+            _ => content,
+        };
+        let mut entry = NewEntry::new(&content);
+        entry.content_hash = duplicate_hash(&content, &self.duplicates);
+        if capture.files {
+            entry.kind = Kind::Files;
+        }
+        // Formatting of text that was cut short no longer matches it.
+        if oversized.is_none() {
+            entry.flavors = capture.flavors.into_iter().filter(|flavor| flavor.data.len() <= MAX_FLAVOR_BYTES).collect();
+        }
+        if let Some(settings) = &self.encryption
+            && secrets::looks_like_secret(&content)
+        {
+            let key = self.hash_key.as_deref().ok_or_else(|| CliError::EncryptionError("No key to hash secrets with".to_string()))?;
+            entry.content_hash = secrets::keyed_hash(key, &entry.content_hash);
+            entry.content = secrets::encrypt(settings, &content)?;
+            entry.encrypted = true;
+            entry.language = None;
+            entry.kind = Kind::Text;
+            entry.flavors.clear();
+        }
+        if let Some(source) = capture.source {
+            entry.source_app = Some(source.name);
+            entry.source_bundle_id = Some(source.bundle_id);
+        }
+        if capture.from_password_manager && self.password_manager.policy == PasswordManagerPolicy::Expire {
+            entry.expires_at = Some(Utc::now().timestamp() + self.password_manager.clear_after_secs as i64);
+        }
+        // gpg and age compress before encrypting, so ciphertext is left as is.
+        if let Some(max) = oversized
+            && self.oversize_policy == OversizePolicy::StoreCompressed
+            && !entry.encrypted
+        {
+            entry.compressed = Some(compress::deflate(&content)?);
+            entry.content = truncate_bytes(&content, max).to_string();
+        }
+        Ok(Some(entry))
+    }
+
+    fn record_failure(&mut self, error: &CliError) {
+        self.metrics.record_error();
+        if self.db_failures.record_failure() {
+            notify("Clippie can't save clipboard history", &error.to_string());
+        }
+    }
+
+    fn flush(&mut self) -> bool {
+        self.last_checkpoint = Instant::now();
+        let flushed = self.db.checkpoint().is_ok();
+        if !flushed {
+            self.metrics.record_error();
+        }
+        flushed
+    }
+
+    /// Runs after every batch of captures, so the retention limits hold as soon as a write crosses them.
+    fn maintain(&mut self) {
+        let _ = self.db.delete_expired_entries();
+        if let Some(days) = self.max_age_days {
+            let _ = self.db.delete_entries_older_than_days(days as i64);
+        }
+        if let Some(max) = self.max_entries {
+            let _ = self.db.trim_to_count(max);
+        }
+        if let Some(mb) = self.max_db_size_mb {
+            let _ = self.db.trim_to_size(mb * 1024 * 1024);
+        }
+
+        if let Some(backup) = &mut self.backup
+            && backup.due(Utc::now())
+            && let Err(e) = backup.run(&self.db, Utc::now())
+        {
+            self.metrics.record_error();
+            notify("Clippie backup failed", &e.to_string());
+        }
+
+        // Keep the WAL from growing without bound while the daemon holds the DB open.
+        if let Some(interval) = self.checkpoint_interval
+            && self.last_checkpoint.elapsed() >= interval
+        {
+            self.flush();
         }
     }
 }
 
+/// Answer `clippie pause`, `resume`, `status`, `flush` and `sync status` sent over the control socket.
+fn handle_request(
+    request: Request,
+    config: &ConfigManager,
+    metrics: &Metrics,
+    jobs: &SyncSender<Job>,
+    sync: Option<&SyncStatus>,
+) -> Result<Value> {
+    match request {
+        Request::Pause { until: None } => config.set_paused(true)?,
+        Request::Pause { until: Some(until) } => {
+            let until = chrono::DateTime::from_timestamp(until, 0)
+                .ok_or_else(|| CliError::InvalidInput("Invalid pause end time".to_string()))?;
+            config.set_paused(false)?;
+            config.set_incognito(Some(until))?;
+        }
+        Request::Resume => {
+            config.set_paused(false)?;
+            config.clear_incognito()?;
+        }
+        Request::Stats | Request::Subscribe => {}
+        Request::Sync => return Ok(json!({ "sync": sync.map(SyncStatus::to_json) })),
+        Request::Flush => {
+            let (done, flushed) = channel();
+            jobs.try_send(Job::Flush(done))
+                .map_err(|_| CliError::InvalidInput("The capture queue is full".to_string()))?;
+            if !flushed.recv_timeout(FLUSH_TIMEOUT).unwrap_or(false) {
+                return Err(CliError::InvalidInput("Flushing the database failed".to_string()));
+            }
+        }
+    }
+    let mut stats = metrics.stats();
+    stats["pid"] = json!(std::process::id());
+    stats["paused"] = json!(config.is_paused());
+    Ok(stats)
+}
+
 pub async fn start_daemon() -> Result<()> {
     let config = ConfigManager::new()?;
 
@@ -57,9 +555,88 @@ pub async fn start_daemon() -> Result<()> {
     }
 
     let db_path = config.get_db_path()?;
+    let settings = config.load()?;
     let db = Database::open(&db_path)?;
-    let mut daemon = DaemonState::new(db, config);
-    daemon.run().await
+    if let Err(e) = db.rehash_duplicates(&settings.duplicates) {
+        eprintln!("Could not rehash entries for the duplicate policy: {}", e);
+    }
+    let hash_key = match settings.secret_encryption {
+        Some(_) => Some(config.hash_key()?),
+        None => None,
+    };
+    if let Some(key) = &hash_key
+        && let Err(e) = db.key_encrypted_hashes(key)
+    {
+        eprintln!("Could not rehash encrypted entries: {}", e);
+    }
+    let metrics = Arc::new(Metrics::default());
+    let metrics_port = settings.metrics_port;
+    let sync_settings = settings.sync.clone().map(|sync| (sync, settings.duplicates));
+    let fetch_url_titles = settings.fetch_url_titles;
+
+    let socket_path = config.get_socket_path()?;
+    // Registered before anything starts, so an early SIGTERM still shuts down cleanly.
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut interrupt = signal(SignalKind::interrupt())?;
+    // Bound before capturing starts: a second daemon would record every copy twice.
+    let listener = ipc::bind(&socket_path).await?;
+
+    let (captures, queue) = sync_channel(CAPTURE_QUEUE_SIZE);
+    let (events, _) = broadcast::channel(EVENT_BUFFER);
+    let mut writer = Writer::new(db, settings, Arc::clone(&metrics));
+    writer.events = Some(events.clone());
+    writer.hash_key = hash_key;
+    std::thread::spawn(move || writer.run(queue));
+
+    if fetch_url_titles {
+        titles::start(db_path.clone(), &events);
+    }
+    let sync_status = sync_settings.map(|(sync, duplicates)| sync::start(sync, duplicates, db_path.clone(), events.clone()));
+
+    let (control_config, control_metrics, control_jobs) = (ConfigManager::new()?, Arc::clone(&metrics), captures.clone());
+    tokio::spawn(async move {
+        let handle = move |request| {
+            handle_request(request, &control_config, &control_metrics, &control_jobs, sync_status.as_deref())
+        };
+        if let Err(e) = ipc::serve(listener, handle, events).await {
+            eprintln!("Control socket failed: {}", e);
+        }
+    });
+
+    let shutdown_jobs = captures.clone();
+    let mut daemon = DaemonState::new(config, Arc::clone(&metrics), captures);
+
+    if let Some(port) = metrics_port {
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve(port, metrics, db_path).await {
+                eprintln!("Metrics endpoint failed: {}", e);
+            }
+        });
+    }
+
+    // launchd and systemd stop the daemon with SIGTERM; Ctrl-C sends SIGINT when it runs in a terminal.
+    daemon
+        .run(async move {
+            tokio::select! {
+                _ = terminate.recv() => {}
+                _ = interrupt.recv() => {}
+            }
+        })
+        .await?;
+
+    println!("Shutting down");
+    let flushed = tokio::task::spawn_blocking(move || {
+        let (done, flushed) = channel();
+        shutdown_jobs.send(Job::Shutdown(done)).is_ok() && flushed.recv_timeout(FLUSH_TIMEOUT).unwrap_or(false)
+    })
+    .await
+    .unwrap_or(false);
+    if !flushed {
+        eprintln!("Could not checkpoint the database before exiting");
+    }
+    // Only reached after binding it above; a daemon that found another listening never gets here.
+    let _ = std::fs::remove_file(&socket_path);
+    Ok(())
 }
 
 #[cfg(test)]
@@ -69,9 +646,227 @@ mod tests {
 
     #[tokio::test]
     async fn test_daemon_state_creation() {
+        let config = ConfigManager::new().unwrap();
+        let (captures, _queue) = sync_channel(1);
+        let _state = DaemonState::new(config, Arc::default(), captures);
+    }
+
+    #[test]
+    fn test_writer_batches_captures() {
         let tmp = NamedTempFile::new().unwrap();
         let db = Database::open(tmp.path()).unwrap();
-        let config = ConfigManager::new().unwrap();
-        let _state = DaemonState::new(db, config);
+        let metrics = Arc::new(Metrics::default());
+        let mut writer = Writer::new(db, Config::default(), Arc::clone(&metrics));
+
+        let capture = |content: &str, from_password_manager| Capture { content: content.to_string(), files: false, flavors: Vec::new(), from_password_manager, source: None };
+        writer.write_batch(vec![capture("one", false), capture("two", true), capture("one", false)]);
+
+        let entries = writer.db.get_all_entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(metrics.render(0, 0).contains("clippie_captures_total 3\n"));
+
+        writer.write_batch(vec![capture("two\n", false)]);
+        assert_eq!(writer.db.get_all_entries().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_password_manager_items_expire_only_when_configured() {
+        let tmp = NamedTempFile::new().unwrap();
+        let capture = Capture { content: "hunter2".to_string(), files: false, flavors: Vec::new(), from_password_manager: true, source: None };
+        let writer = |policy| {
+            let settings = Config {
+                password_manager: PasswordManagerConfig { policy, clear_after_secs: 30 },
+                ..Config::default()
+            };
+            Writer::new(Database::open(tmp.path()).unwrap(), settings, Arc::default())
+        };
+
+        assert!(writer(PasswordManagerPolicy::Expire).prepare(capture.clone()).unwrap().unwrap().expires_at.is_some());
+        assert!(writer(PasswordManagerPolicy::Keep).prepare(capture).unwrap().unwrap().expires_at.is_none());
+    }
+
+    #[test]
+    fn test_oversize_policies() {
+        let tmp = NamedTempFile::new().unwrap();
+        let log = "é log line\n".repeat(1000);
+        let capture = Capture { content: log.clone(), files: false, flavors: Vec::new(), from_password_manager: false, source: None };
+        let writer = |oversize_policy| {
+            let settings = Config { max_entry_bytes: Some(100), oversize_policy, ..Config::default() };
+            Writer::new(Database::open(tmp.path()).unwrap(), settings, Arc::default())
+        };
+
+        assert!(writer(OversizePolicy::Skip).prepare(capture.clone()).unwrap().is_none());
+        let truncated = writer(OversizePolicy::Truncate).prepare(capture.clone()).unwrap().unwrap();
+        assert!(truncated.content.len() <= 100 && log.starts_with(&truncated.content));
+        assert!(truncated.compressed.is_none());
+
+        let mut compressed = writer(OversizePolicy::StoreCompressed);
+        compressed.write_batch(vec![capture.clone()]);
+        let summary = &compressed.db.get_entries_page(500, 0, 1).unwrap()[0];
+        assert!(summary.truncated && summary.content.len() <= 100);
+        assert_eq!(compressed.db.get_entry(summary.id).unwrap().unwrap().content, log);
+        let listed = compressed.db.get_all_entries().unwrap();
+        assert!(listed[0].truncated && listed[0].content.len() <= 100);
+        assert_eq!(compressed.db.with_full_content(listed).unwrap()[0].content, log);
+
+        // Another long capture starting the same way is a new entry, not a repeat of the first.
+        let other = format!("{}tail", log);
+        compressed.write_batch(vec![Capture { content: other.clone(), ..capture }]);
+        let summaries = compressed.db.get_entries_page(500, 0, 2).unwrap();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].content, summaries[1].content);
+        assert_eq!(compressed.db.get_entry(summaries[0].id).unwrap().unwrap().content, other);
+    }
+
+    #[test]
+    fn test_sensitive_policies() {
+        let tmp = NamedTempFile::new().unwrap();
+        let capture = Capture { content: "password=hunter2".to_string(), files: false, flavors: Vec::new(), from_password_manager: false, source: None };
+        let writer = |sensitive_entries| {
+            let settings = Config { sensitive_entries, ..Config::default() };
+            Writer::new(Database::open(tmp.path()).unwrap(), settings, Arc::default())
+        };
+
+        assert!(writer(SensitivePolicy::Skip).prepare(capture.clone()).unwrap().is_none());
+        assert!(writer(SensitivePolicy::Mask).prepare(capture).unwrap().unwrap().sensitive);
+    }
+
+    #[test]
+    fn test_file_captures() {
+        let tmp = NamedTempFile::new().unwrap();
+        let writer = Writer::new(Database::open(tmp.path()).unwrap(), Config::default(), Arc::default());
+        let capture = |files| Capture { content: "/Users/ann/a.txt\n/Users/ann/b.pdf".to_string(), files, flavors: Vec::new(), from_password_manager: false, source: None };
+
+        assert_eq!(writer.prepare(capture(true)).unwrap().unwrap().kind, Kind::Files);
+        assert_ne!(writer.prepare(capture(false)).unwrap().unwrap().kind, Kind::Files);
+    }
+
+    #[test]
+    fn test_flavors_are_kept_for_whole_captures() {
+        let tmp = NamedTempFile::new().unwrap();
+        let html = Flavor { uti: "public.html".to_string(), data: b"<b>bold</b>".to_vec() };
+        let huge = Flavor { uti: "public.rtf".to_string(), data: vec![b' '; MAX_FLAVOR_BYTES + 1] };
+        let capture = |content: &str| Capture { content: content.to_string(), files: false, flavors: vec![html.clone(), huge.clone()], from_password_manager: false, source: None };
+        let writer = |max_entry_bytes| {
+            let settings = Config { max_entry_bytes, oversize_policy: OversizePolicy::Truncate, ..Config::default() };
+            Writer::new(Database::open(tmp.path()).unwrap(), settings, Arc::default())
+        };
+
+        let kept = writer(None).prepare(capture("bold")).unwrap().unwrap().flavors;
+        assert_eq!(kept.iter().map(|flavor| flavor.uti.as_str()).collect::<Vec<_>>(), ["public.html"]);
+        assert!(writer(Some(2)).prepare(capture("bold")).unwrap().unwrap().flavors.is_empty());
+    }
+
+    #[test]
+    fn test_backoff() {
+        let mut backoff = Backoff::new(PollingConfig { min_interval_ms: 500, max_interval_ms: 3000 });
+        let start = backoff.last_change;
+        assert_eq!(backoff.next(start + Duration::from_secs(30)), Duration::from_millis(500));
+
+        let idle = start + ACTIVE_PERIOD;
+        let intervals: Vec<u128> = (0..4).map(|_| backoff.next(idle).as_millis()).collect();
+        assert_eq!(intervals, [1000, 2000, 3000, 3000]);
+
+        backoff.changed(idle);
+        assert_eq!(backoff.next(idle), Duration::from_millis(500));
+
+        // Intervals past the health check's staleness window are capped.
+        let backoff = Backoff::new(PollingConfig { min_interval_ms: 0, max_interval_ms: 60_000 });
+        assert_eq!((backoff.min, backoff.max), (Duration::from_millis(1), MAX_POLL_INTERVAL));
+    }
+
+    #[test]
+    fn test_auto_clear_timer() {
+        let start = Instant::now();
+        let mut auto_clear = AutoClear { settings: AutoClearConfig { after_secs: 10, all_copies: false }, deadline: None };
+
+        auto_clear.copied("ls -la", start);
+        assert!(!auto_clear.due(start + Duration::from_secs(60)));
+
+        auto_clear.copied("api_key=abc123", start);
+        assert!(!auto_clear.due(start + Duration::from_secs(9)));
+        assert!(auto_clear.due(start + Duration::from_secs(10)));
+        assert!(!auto_clear.due(start + Duration::from_secs(11)));
+
+        // Copying something else before the deadline cancels the clear.
+        auto_clear.copied("api_key=abc123", start);
+        auto_clear.copied("ls -la", start + Duration::from_secs(5));
+        assert!(!auto_clear.due(start + Duration::from_secs(60)));
+
+        auto_clear.settings.all_copies = true;
+        auto_clear.copied("ls -la", start);
+        assert!(auto_clear.due(start + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_truncate_bytes() {
+        assert_eq!(truncate_bytes("héllo", 2), "h");
+        assert_eq!(truncate_bytes("héllo", 3), "hé");
+        assert_eq!(truncate_bytes("hi", 10), "hi");
+    }
+
+    #[test]
+    fn test_flush_waits_for_queued_captures() {
+        let tmp = NamedTempFile::new().unwrap();
+        let writer = Writer::new(Database::open(tmp.path()).unwrap(), Config::default(), Arc::default());
+        let (jobs, queue) = sync_channel(4);
+        std::thread::spawn(move || writer.run(queue));
+
+        let capture = Capture { content: "queued".to_string(), files: false, flavors: Vec::new(), from_password_manager: false, source: None };
+        jobs.send(Job::Capture(capture)).unwrap();
+        let (done, flushed) = channel();
+        jobs.send(Job::Flush(done)).unwrap();
+        assert!(flushed.recv_timeout(FLUSH_TIMEOUT).unwrap());
+        assert_eq!(Database::open(tmp.path()).unwrap().count_entries().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_shutdown_writes_queued_captures_and_stops() {
+        let tmp = NamedTempFile::new().unwrap();
+        let writer = Writer::new(Database::open(tmp.path()).unwrap(), Config::default(), Arc::default());
+        let (jobs, queue) = sync_channel(4);
+        let thread = std::thread::spawn(move || writer.run(queue));
+
+        let capture = Capture { content: "last copy".to_string(), files: false, flavors: Vec::new(), from_password_manager: false, source: None };
+        jobs.send(Job::Capture(capture)).unwrap();
+        let (done, flushed) = channel();
+        jobs.send(Job::Shutdown(done)).unwrap();
+        assert!(flushed.recv_timeout(FLUSH_TIMEOUT).unwrap());
+        thread.join().unwrap();
+        assert_eq!(Database::open(tmp.path()).unwrap().count_entries().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_returns_on_shutdown() {
+        let (captures, _queue) = sync_channel(1);
+        let mut state = DaemonState::new(ConfigManager::new().unwrap(), Arc::default(), captures);
+        state.run(async {}).await.unwrap();
+    }
+
+    #[test]
+    fn test_excluded_apps() {
+        let excluded = vec!["com.1password.1password".to_string(), "com.apple.keychainaccess".to_string()];
+        assert!(is_excluded_app(Some("com.apple.KeychainAccess"), &excluded));
+        assert!(!is_excluded_app(Some("com.apple.Terminal"), &excluded));
+        assert!(!is_excluded_app(None, &excluded));
+    }
+
+    #[test]
+    fn test_ignore_patterns() {
+        let patterns = ignore_set(&[r"^\d{6}$".to_string(), "(unclosed".to_string(), r"\bJIRA-\d+".to_string()]);
+        assert_eq!(patterns.len(), 2);
+        assert!(patterns.is_match("482913"));
+        assert!(patterns.is_match("see JIRA-42 for details"));
+        assert!(!patterns.is_match("4829134"));
+    }
+
+    #[test]
+    fn test_failure_tracker_notifies_once_per_streak() {
+        let mut tracker = FailureTracker::default();
+        let fired: Vec<bool> = (0..FAILURE_NOTIFY_THRESHOLD + 2).map(|_| tracker.record_failure()).collect();
+        assert_eq!(fired.iter().filter(|f| **f).count(), 1);
+
+        tracker.record_success();
+        assert!(!tracker.record_failure());
     }
 }
@@ -18,6 +18,25 @@ pub enum CliError {
     #[error("JSON error: {0}")]
     JsonError(#[from] serde_json::Error),
 
+    #[error("Export error: {0}")]
+    ExportError(String),
+
+    #[error("Encryption error: {0}")]
+    EncryptionError(String),
+
+    #[error("Compression error: {0}")]
+    CompressionError(String),
+
+    #[error("Service error: {0}")]
+    ServiceError(String),
+
+    #[error("{0}")]
+    InvalidInput(String),
+
+    #[allow(dead_code)]
+    #[error("Not supported on this platform: {0}")]
+    UnsupportedPlatform(String),
+
     #[allow(dead_code)]
     #[error("Config not found. Run 'clippie setup' to configure the database location.")]
     ConfigNotFound,
@@ -0,0 +1,186 @@
+//! Control channel between the CLI and a running daemon over `~/.clippie/daemon.sock`.
+//!
+//! A client writes one JSON line such as `{"command":"pause"}` and reads one JSON line back:
+//! the handler's fields plus `"ok": true`, or `{"ok": false, "error": ...}`. After `subscribe`
+//! the connection stays open and receives one line per daemon event, e.g. `{"event":"capture"}`.
+
+use crate::config::ConfigManager;
+use crate::error::{CliError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::broadcast;
+
+/// Long enough for a flush to checkpoint the WAL, short enough that a wedged daemon doesn't hang the CLI.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "lowercase")]
+pub enum Request {
+    /// Until the given Unix time, or until resumed.
+    Pause {
+        #[serde(default)]
+        until: Option<i64>,
+    },
+    Resume,
+    Stats,
+    /// Write out queued captures and checkpoint the WAL.
+    Flush,
+    /// Keep the connection open and stream events to it.
+    Subscribe,
+    /// Peers, counts and errors of `[sync]`.
+    Sync,
+}
+
+/// Ask the running daemon to handle `request`. Fails when no daemon is listening.
+pub async fn send(config: &ConfigManager, request: Request) -> Result<Value> {
+    send_to(&config.get_socket_path()?, request).await
+}
+
+async fn send_to(path: &Path, request: Request) -> Result<Value> {
+    let exchange = async {
+        let mut stream = UnixStream::connect(path).await?;
+        stream.write_all(format!("{}\n", serde_json::to_string(&request)?).as_bytes()).await?;
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).await?;
+        Ok::<Value, CliError>(serde_json::from_str(&line)?)
+    };
+    let response = tokio::time::timeout(REQUEST_TIMEOUT, exchange)
+        .await
+        .map_err(|_| CliError::InvalidInput("The daemon did not answer".to_string()))??;
+
+    if response["ok"] == true {
+        Ok(response)
+    } else {
+        Err(CliError::InvalidInput(response["error"].as_str().unwrap_or("Daemon request failed").to_string()))
+    }
+}
+
+/// Take over the control socket at `path`. Fails, leaving the socket alone, when another
+/// daemon is listening on it.
+pub async fn bind(path: &Path) -> Result<UnixListener> {
+    // A socket left behind by a crashed daemon refuses connections; one that accepts belongs to a live daemon.
+    if path.exists() {
+        if UnixStream::connect(path).await.is_ok() {
+            return Err(CliError::InvalidInput(format!("Another daemon is listening on {}", path.display())));
+        }
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+/// Answer requests on `listener` until the daemon exits, forwarding `events` to subscribers.
+/// `handle` may block, so it runs off the async workers.
+pub async fn serve<F>(listener: UnixListener, handle: F, events: broadcast::Sender<Value>) -> Result<()>
+where
+    F: Fn(Request) -> Result<Value> + Send + Sync + 'static,
+{
+    let handle = Arc::new(handle);
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let handle = Arc::clone(&handle);
+        let mut subscription = events.subscribe();
+
+        tokio::spawn(async move {
+            let (reader, mut writer) = stream.into_split();
+            let mut line = String::new();
+            if BufReader::new(reader).read_line(&mut line).await.is_err() {
+                return;
+            }
+            if let Ok(Request::Subscribe) = serde_json::from_str::<Request>(&line) {
+                let mut next = Ok(json!({ "ok": true }));
+                loop {
+                    let event = match next {
+                        Ok(event) => event,
+                        // A subscriber that fell behind only needs to know something changed.
+                        Err(broadcast::error::RecvError::Lagged(_)) => json!({ "event": "lagged" }),
+                        Err(broadcast::error::RecvError::Closed) => return,
+                    };
+                    if writer.write_all(format!("{}\n", event).as_bytes()).await.is_err() {
+                        return;
+                    }
+                    next = subscription.recv().await;
+                }
+            }
+            let response = match serde_json::from_str::<Request>(&line) {
+                Ok(request) => tokio::task::spawn_blocking(move || handle(request))
+                    .await
+                    .unwrap_or_else(|e| Err(CliError::InvalidInput(e.to_string()))),
+                Err(e) => Err(CliError::InvalidInput(format!("invalid request: {}", e))),
+            };
+            let body = match response {
+                Ok(mut body) => {
+                    if !body.is_object() {
+                        body = json!({});
+                    }
+                    body["ok"] = json!(true);
+                    body
+                }
+                Err(e) => json!({ "ok": false, "error": e.to_string() }),
+            };
+            let _ = writer.write_all(format!("{}\n", body).as_bytes()).await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::FileTypeExt;
+
+    #[tokio::test]
+    async fn test_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon.sock");
+        // Stand in for a socket left behind by a crashed daemon.
+        std::fs::write(&path, "").unwrap();
+
+        let listener = bind(&path).await.unwrap();
+        let error = bind(&path).await.unwrap_err();
+        assert!(error.to_string().starts_with("Another daemon is listening"));
+        assert!(std::fs::metadata(&path).unwrap().file_type().is_socket());
+
+        let (events, _) = broadcast::channel(4);
+        let server_events = events.clone();
+        tokio::spawn(async move {
+            let handle = |request| match request {
+                Request::Stats => Ok(json!({ "captures_total": 3 })),
+                _ => Err(CliError::InvalidInput("not now".to_string())),
+            };
+            serve(listener, handle, server_events).await
+        });
+
+        let stats = send_to(&path, Request::Stats).await.unwrap();
+        assert_eq!(stats["captures_total"], 3);
+        let error = send_to(&path, Request::Pause { until: None }).await.unwrap_err();
+        assert_eq!(error.to_string(), "not now");
+
+        let mut stream = UnixStream::connect(&path).await.unwrap();
+        stream.write_all(b"{\"command\":\"subscribe\"}\n").await.unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), r#"{"ok":true}"#);
+        events.send(json!({ "event": "capture" })).unwrap();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), r#"{"event":"capture"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_send_fails_without_daemon() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(send_to(&dir.path().join("daemon.sock"), Request::Stats).await.is_err());
+    }
+
+    #[test]
+    fn test_request_encoding() {
+        assert_eq!(serde_json::to_string(&Request::Flush).unwrap(), r#"{"command":"flush"}"#);
+        assert_eq!(serde_json::from_str::<Request>(r#"{"command":"resume"}"#).unwrap(), Request::Resume);
+        assert_eq!(serde_json::from_str::<Request>(r#"{"command":"pause"}"#).unwrap(), Request::Pause { until: None });
+    }
+}
@@ -0,0 +1,174 @@
+//! Sorting captures into kinds such as URLs, paths or colors, shown and filtered on in the TUI.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Digits a phone number has at least and at most (E.164 allows 15).
+const PHONE_DIGITS: std::ops::RangeInclusive<usize> = 7..=15;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Url,
+    Email,
+    Path,
+    Json,
+    Code,
+    Color,
+    Phone,
+    /// Files copied in Finder, one path per line. Only the daemon sets it, from the pasteboard types.
+    Files,
+    #[default]
+    Text,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 9] =
+        [Kind::Url, Kind::Email, Kind::Path, Kind::Json, Kind::Code, Kind::Color, Kind::Phone, Kind::Files, Kind::Text];
+
+    /// Name stored in the `kind` column and typed after `:` in a search.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Kind::Url => "url",
+            Kind::Email => "email",
+            Kind::Path => "path",
+            Kind::Json => "json",
+            Kind::Code => "code",
+            Kind::Color => "color",
+            Kind::Phone => "phone",
+            Kind::Files => "files",
+            Kind::Text => "text",
+        }
+    }
+
+    /// The kind named `name`, or by a common alias such as `mail` or `file`.
+    pub fn parse(name: &str) -> Option<Kind> {
+        match name.to_ascii_lowercase().as_str() {
+            "url" | "link" => Some(Kind::Url),
+            "email" | "mail" => Some(Kind::Email),
+            "path" | "file" => Some(Kind::Path),
+            "json" => Some(Kind::Json),
+            "code" => Some(Kind::Code),
+            "color" | "colour" => Some(Kind::Color),
+            "phone" | "tel" => Some(Kind::Phone),
+            "files" => Some(Kind::Files),
+            "text" => Some(Kind::Text),
+            _ => None,
+        }
+    }
+
+    /// Short tag for the TUI list; plain text has none.
+    pub fn label(self) -> &'static str {
+        match self {
+            Kind::Url => "url",
+            Kind::Email => "mail",
+            Kind::Path => "path",
+            Kind::Json => "json",
+            Kind::Code => "code",
+            Kind::Color => "color",
+            Kind::Phone => "tel",
+            Kind::Files => "files",
+            Kind::Text => "",
+        }
+    }
+}
+
+static URL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^([a-z][a-z0-9+.-]*://\S+|www\.\S+\.\S+)$").expect("url pattern is valid"));
+static EMAIL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^(mailto:)?[\w.%+-]+@[\w-]+(\.[\w-]+)*\.[a-z]{2,}$").expect("email pattern is valid"));
+static PHONE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\+?(\(\d{1,4}\)|\d{1,4})([\s.-]?(\(\d{1,4}\)|\d{2,5})){1,5}$").expect("phone pattern is valid"));
+static PATH: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^((~|\.{1,2})?/[^/\s]|~$|[A-Za-z]:\\)").expect("path pattern is valid"));
+
+/// The kind of `content`, given the language `language::detect` found in it.
+pub fn classify(content: &str, language: Option<&str>) -> Kind {
+    let text = content.trim();
+    if text.is_empty() {
+        return Kind::Text;
+    }
+    if !text.contains('\n') {
+        if crate::color::parse(text).is_some() {
+            return Kind::Color;
+        }
+        if EMAIL.is_match(text) {
+            return Kind::Email;
+        }
+        if URL.is_match(text) {
+            return Kind::Url;
+        }
+        if is_phone(text) {
+            return Kind::Phone;
+        }
+        if PATH.is_match(text) && !text.contains('\t') {
+            return Kind::Path;
+        }
+    }
+    if text.starts_with(['{', '[']) && serde_json::from_str::<serde::de::IgnoredAny>(text).is_ok() {
+        return Kind::Json;
+    }
+    if language.is_some() {
+        return Kind::Code;
+    }
+    Kind::Text
+}
+
+/// Numbers like `+47 912 34 567` or `(555) 123-4567`; dates and IP addresses look alike and aren't.
+fn is_phone(text: &str) -> bool {
+    let digits = text.chars().filter(char::is_ascii_digit).count();
+    PHONE_DIGITS.contains(&digits)
+        && PHONE.is_match(text)
+        && text.parse::<std::net::Ipv4Addr>().is_err()
+        && chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d").is_err()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(content: &str) -> Kind {
+        classify(content, crate::language::detect(content))
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(kind("https://example.com/a?b=c"), Kind::Url);
+        assert_eq!(kind("www.example.com"), Kind::Url);
+        assert_eq!(kind("ann@example.co.uk"), Kind::Email);
+        assert_eq!(kind("mailto:ann@example.com"), Kind::Email);
+        assert_eq!(kind("/usr/local/bin"), Kind::Path);
+        assert_eq!(kind("~/Documents/notes.txt"), Kind::Path);
+        assert_eq!(kind("C:\\Users\\ann"), Kind::Path);
+        assert_eq!(kind("{\"a\": [1, 2]}"), Kind::Json);
+        assert_eq!(kind("[1, 2, 3]"), Kind::Json);
+        assert_eq!(kind("SELECT id FROM users WHERE active = 1"), Kind::Code);
+        assert_eq!(kind("#ff8800"), Kind::Color);
+        assert_eq!(kind("#FFF"), Kind::Color);
+        assert_eq!(kind("hsl(32, 100%, 50%)"), Kind::Color);
+        assert_eq!(kind("+47 912 34 567"), Kind::Phone);
+        assert_eq!(kind("(555) 123-4567"), Kind::Phone);
+        assert_eq!(kind("hello world"), Kind::Text);
+        assert_eq!(kind(""), Kind::Text);
+    }
+
+    #[test]
+    fn test_lookalikes_stay_text() {
+        assert_eq!(kind("2024-10-15"), Kind::Text);
+        assert_eq!(kind("192.168.10.10"), Kind::Text);
+        assert_eq!(kind("12345"), Kind::Text);
+        assert_eq!(kind("#hashtag"), Kind::Text);
+        assert_eq!(kind("// a comment"), Kind::Text);
+        assert_eq!(kind("{not json"), Kind::Text);
+        assert_eq!(kind("see https://example.com"), Kind::Text);
+    }
+
+    #[test]
+    fn test_parse_names_and_aliases() {
+        for kind in Kind::ALL {
+            assert_eq!(Kind::parse(kind.as_str()), Some(kind));
+        }
+        assert_eq!(Kind::parse("MAIL"), Some(Kind::Email));
+        assert_eq!(Kind::parse("file"), Some(Kind::Path));
+        assert_eq!(Kind::parse("nope"), None);
+    }
+}
@@ -0,0 +1,180 @@
+//! Lightweight programming-language detection used to tag captures.
+
+use once_cell::sync::Lazy;
+use regex::RegexSet;
+
+/// Only the start of long captures is inspected.
+const SCAN_CHARS: usize = 4096;
+/// Minimum pattern score before a language is assigned.
+const THRESHOLD: u32 = 2;
+
+struct Language {
+    name: &'static str,
+    patterns: RegexSet,
+    /// Score added by each pattern in `patterns` that matches.
+    weights: Vec<u32>,
+}
+
+fn language(name: &'static str, rules: &[(&str, u32)]) -> Language {
+    Language {
+        name,
+        patterns: RegexSet::new(rules.iter().map(|(pattern, _)| *pattern)).expect("language patterns are valid"),
+        weights: rules.iter().map(|(_, weight)| *weight).collect(),
+    }
+}
+
+static LANGUAGES: Lazy<Vec<Language>> = Lazy::new(|| {
+    vec![
+        language("rust", &[
+            (r"\bfn\s+\w+\s*[<(]", 2),
+            (r"\blet\s+mut\b", 2),
+            (r"\bimpl\b.*\{", 1),
+            (r"\bpub\s+(fn|struct|enum|mod|trait)\b", 2),
+            (r"(?m)^\s*use\s+\w+(::\w+)+", 1),
+            (r"#\[derive\(", 2),
+            (r"\w+!\(", 1),
+            (r"->\s*[A-Z&]", 1),
+        ]),
+        language("sql", &[
+            (r"(?is)\bselect\b.+\bfrom\b", 2),
+            (r"(?i)\binsert\s+into\b", 2),
+            (r"(?i)\bupdate\s+\w+\s+set\b", 2),
+            (r"(?i)\bdelete\s+from\b", 2),
+            (r"(?i)\b(create|alter|drop)\s+(table|index|view)\b", 2),
+            (r"(?i)\b(where|group\s+by|order\s+by|left\s+join|inner\s+join)\b", 1),
+        ]),
+        language("shell", &[
+            (r"^\s*(sudo|cd|ls|grep|export|echo|cat|curl|wget|git|docker|kubectl|brew|npm|npx|yarn|cargo|ssh|scp|chmod|chown|mkdir|rm|mv|cp|tar|find|sed|awk|make|pip|apt|systemctl|launchctl)\s", 2),
+            (r"\|\s*(grep|awk|sed|xargs|sort|head|tail|jq|wc)\b", 2),
+            (r"\s&&\s", 1),
+            (r"\$\{?[A-Z_]+", 1),
+            (r"\s--?[a-z][\w-]*", 1),
+        ]),
+        language("python", &[
+            (r"(?m)^\s*def\s+\w+\(.*\)\s*(->.*)?:\s*$", 2),
+            (r"(?m)^\s*(import\s+\w+|from\s+[\w.]+\s+import\b)", 2),
+            (r"\bself\.\w+", 1),
+            (r"(?m)^\s*(elif|except|class\s+\w+.*):", 2),
+            (r"\bprint\(", 1),
+        ]),
+        language("javascript", &[
+            (r"\b(const|let)\s+\w+\s*=", 1),
+            (r"\)\s*=>|\w\s*=>\s*[{(\w]", 2),
+            (r"\bfunction\s*\w*\s*\(", 2),
+            (r"\bconsole\.\w+\(", 2),
+            (r"\brequire\(|(?m)^\s*import\s+.*\bfrom\s+['\x22]", 2),
+            (r"===|!==", 1),
+        ]),
+        language("go", &[
+            (r"(?m)^package\s+\w+", 2),
+            (r"\bfunc\s+(\(\w+\s+\*?\w+\)\s*)?\w+\(", 2),
+            (r"\w\s*:=\s*", 1),
+            (r"\bfmt\.\w+\(", 2),
+            (r"\bif\s+err\s*!=\s*nil\b", 2),
+        ]),
+        language("yaml", &[
+            (r"(?m)^---\s*$", 2),
+            (r"(?m)^\s*- [\w-]+:\s", 2),
+            (r"(?m)^[\w-]+:\s*\n\s+[\w-]+:", 2),
+            (r"(?m)^[\w-]+:\s+\S", 1),
+        ]),
+        language("html", &[
+            (r"(?i)<(!doctype|html|head|body|div|span|script|style|a|p|ul|li|table)[\s>]", 2),
+            (r"</\w+>", 1),
+        ]),
+    ]
+});
+
+/// Best guess at the language of `content`, or `None` for prose and other plain text.
+pub fn detect(content: &str) -> Option<&'static str> {
+    let text: String = content.trim().chars().take(SCAN_CHARS).collect();
+    if text.is_empty() {
+        return None;
+    }
+
+    if let Some(language) = from_fence(&text).or_else(|| from_shebang(&text)) {
+        return Some(language);
+    }
+    if (text.starts_with('{') || text.starts_with('['))
+        && text.len() > 2
+        && serde_json::from_str::<serde_json::Value>(content.trim()).is_ok()
+    {
+        return Some("json");
+    }
+
+    // Earlier languages win ties.
+    let mut best: Option<(u32, &'static str)> = None;
+    for language in LANGUAGES.iter() {
+        let score: u32 = language.patterns.matches(&text).iter().map(|i| language.weights[i]).sum();
+        if score >= THRESHOLD && best.is_none_or(|(top, _)| score > top) {
+            best = Some((score, language.name));
+        }
+    }
+    best.map(|(_, name)| name)
+}
+
+/// Markdown code fence info string, e.g. "```sql".
+fn from_fence(text: &str) -> Option<&'static str> {
+    let info = text.strip_prefix("```")?.lines().next()?.trim().to_lowercase();
+    normalize(&info)
+}
+
+fn from_shebang(text: &str) -> Option<&'static str> {
+    let mut parts = text.strip_prefix("#!")?.lines().next()?.split_whitespace();
+    let mut program = parts.next()?;
+    if program.ends_with("/env") {
+        program = parts.find(|part| !part.starts_with('-'))?;
+    }
+    let name = program.rsplit('/').next()?;
+    normalize(name.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.'))
+}
+
+/// Map a fence tag or interpreter name onto a language tag.
+pub fn normalize(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "rust" | "rs" => "rust",
+        "sql" | "psql" | "sqlite" | "mysql" | "postgresql" => "sql",
+        "sh" | "bash" | "zsh" | "shell" | "console" | "fish" => "shell",
+        "json" | "jsonc" => "json",
+        "python" | "py" => "python",
+        "javascript" | "js" | "node" | "typescript" | "ts" | "jsx" | "tsx" | "deno" => "javascript",
+        "go" | "golang" => "go",
+        "html" | "htm" => "html",
+        "yaml" | "yml" => "yaml",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_common_languages() {
+        assert_eq!(detect("SELECT id, name FROM users WHERE active = 1 ORDER BY name"), Some("sql"));
+        assert_eq!(detect("fn main() {\n    let mut x = 1;\n    println!(\"{}\", x);\n}"), Some("rust"));
+        assert_eq!(detect("git log --oneline | grep fix"), Some("shell"));
+        assert_eq!(detect("docker ps -a"), Some("shell"));
+        assert_eq!(detect("{\"name\": \"clippie\", \"tags\": [1, 2]}"), Some("json"));
+        assert_eq!(detect("def greet(name):\n    print(name)"), Some("python"));
+        assert_eq!(detect("const add = (a, b) => a + b;"), Some("javascript"));
+        assert_eq!(detect("if err != nil {\n\treturn fmt.Errorf(\"x\")\n}"), Some("go"));
+        assert_eq!(detect("<div class=\"x\">hi</div>"), Some("html"));
+        assert_eq!(detect("services:\n  web:\n    image: nginx"), Some("yaml"));
+    }
+
+    #[test]
+    fn test_fences_and_shebangs() {
+        assert_eq!(detect("```sql\nselect 1\n```"), Some("sql"));
+        assert_eq!(detect("#!/usr/bin/env python3\nprint('x')"), Some("python"));
+        assert_eq!(detect("#!/bin/bash\necho hi"), Some("shell"));
+    }
+
+    #[test]
+    fn test_prose_is_untagged() {
+        assert_eq!(detect("Let's meet at noon tomorrow, I'll bring the slides."), None);
+        assert_eq!(detect("https://example.com/page?id=3"), None);
+        assert_eq!(detect("[citation needed]"), None);
+        assert_eq!(detect(""), None);
+    }
+}
@@ -1,19 +1,37 @@
+mod backup;
 mod cli;
 mod clipboard;
+mod color;
 mod commands;
+mod compress;
 mod config;
 mod daemon;
 mod db;
 mod error;
+mod http;
+mod ipc;
+mod kind;
+mod language;
+mod menubar;
+mod metrics;
+mod notify;
+mod osc52;
+mod secrets;
+mod service;
+mod sync;
+mod titles;
+mod transform;
 mod tui;
+mod urls;
 
 use cli::{Cli, Commands};
 use config::ConfigManager;
-use db::Database;
 use error::Result;
+use service::ServiceManager;
+use std::io::Write;
+use std::path::PathBuf;
 use std::process;
-
-const DAEMON_PLIST: &str = "Library/LaunchAgents/no.bechsor.clippie-daemon.plist";
+use std::time::Duration;
 
 #[tokio::main]
 async fn main() {
@@ -25,137 +43,336 @@ async fn main() {
 
 async fn run() -> Result<()> {
     let cli = Cli::parse_args();
+    let colors = tui::theme::ColorSupport::detect(cli.no_color);
+    if let Some(profile) = &cli.profile {
+        ConfigManager::use_profile(profile);
+    }
 
     match cli.command {
-        None | Some(Commands::Tui) => launch_tui().await,
+        None => launch_tui(PickAction::Print, colors).await,
+        Some(Commands::Tui { paste: true, .. }) => launch_tui(PickAction::Paste, colors).await,
+        Some(Commands::Tui { type_out: true, .. }) => launch_tui(PickAction::Type, colors).await,
+        Some(Commands::Tui { .. }) => launch_tui(PickAction::Print, colors).await,
+        Some(Commands::Zle) => launch_tui(PickAction::Insert, colors).await,
         Some(Commands::Setup) => commands::run_setup().await,
         Some(Commands::Start) => cmd_start().await,
         Some(Commands::Stop) => cmd_stop().await,
+        Some(Commands::Restart) => cmd_restart().await,
         Some(Commands::Status) => commands::run_status().await,
+        Some(Commands::Health) => commands::run_health().await,
+        Some(Commands::Doctor) => commands::run_doctor().await,
+        Some(Commands::Vacuum) => commands::run_vacuum().await,
+        Some(Commands::Stats { json }) => commands::run_stats(json).await,
         Some(Commands::Clear { all }) => commands::run_clear(all).await,
+        Some(Commands::Popup { type_out }) => commands::run_popup(type_out).await,
+        Some(Commands::List { limit, format, json, tsv, query, language, kind, since }) => {
+            let format = match (json, tsv) {
+                (true, _) => cli::ListFormat::Json,
+                (_, true) => cli::ListFormat::Tsv,
+                _ => format,
+            };
+            commands::run_list(limit, format, query.as_deref(), language.as_deref(), kind.as_deref(), since.as_deref()).await
+        }
+        Some(Commands::Export { format, out, limit, since, until }) => {
+            commands::run_export(format, &out, limit, since.as_deref(), until.as_deref()).await
+        }
+        Some(Commands::Import { file, format }) => commands::run_import(&file, format).await,
+        Some(Commands::Get { target, id }) => commands::run_get(target, id).await,
+        Some(Commands::Copy { stdin: true, .. }) => commands::run_copy_stdin().await,
+        Some(Commands::Copy { target, id, .. }) => commands::run_copy(target.unwrap_or(1), id).await,
+        Some(Commands::Transform { target, id, op, print }) => commands::run_transform(target, id, &op, print).await,
+        Some(Commands::Pick { with_index, limit, query }) => commands::run_pick(with_index, limit, query.as_deref()).await,
+        Some(Commands::Pop { paste, clear }) => commands::run_pop(paste, clear).await,
+        Some(Commands::Snippet { action }) => commands::run_snippet(action).await,
+        Some(Commands::Config { action }) => commands::run_config(action).await,
+        Some(Commands::Profile { action }) => commands::run_profile(action).await,
+        Some(Commands::Sync { action }) => commands::run_sync(action).await,
+        Some(Commands::Add { file, copy }) => commands::run_add(file.as_deref(), copy).await,
+        Some(Commands::Preview { id, width }) => commands::run_preview(id, width, colors != tui::theme::ColorSupport::Monochrome).await,
+        Some(Commands::Rpc) => commands::run_rpc().await,
+        Some(Commands::Serve { port }) => commands::run_serve(port).await,
         Some(Commands::Install) => commands::run_install().await,
+        Some(Commands::Uninstall) => commands::run_uninstall().await,
         Some(Commands::Daemon) => daemon::start_daemon().await,
-        Some(Commands::Pause) => cmd_pause().await,
+        Some(Commands::Pause { duration }) => cmd_pause(duration).await,
         Some(Commands::Resume) => cmd_resume().await,
+        Some(Commands::Incognito { duration }) => commands::run_incognito(duration).await,
+        Some(Commands::Menubar) => menubar::run_menubar().await,
     }
 }
 
-async fn launch_tui() -> Result<()> {
+/// What to do with the entry chosen in the TUI.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PickAction {
+    /// Copy it and echo it to stdout.
+    Print,
+    /// Copy it and paste into the app that regains focus.
+    Paste,
+    /// Type it into the app that regains focus, leaving the clipboard alone.
+    Type,
+    /// Write only the entry to stdout, for a ZLE widget to splice into the command line.
+    Insert,
+}
+
+impl PickAction {
+    /// The TUI draws on stderr when stdout is reserved for the selection.
+    fn draws_on_stderr(self) -> bool {
+        self == PickAction::Insert
+    }
+
+    fn tui_output(self) -> Box<dyn Write> {
+        if self.draws_on_stderr() {
+            Box::new(std::io::stderr())
+        } else {
+            Box::new(std::io::stdout())
+        }
+    }
+}
+
+async fn launch_tui(action: PickAction, colors: tui::theme::ColorSupport) -> Result<()> {
     let config = ConfigManager::new()?;
-    if !config.exists() {
-        println!("Welcome to Clippie! Let's set it up first.\n");
-        commands::run_setup().await?;
-        println!("\n");
+    if !config.exists() && action == PickAction::Insert {
+        eprintln!("Error: Clippie not configured. Run 'clippie setup' first.");
+        process::exit(1);
     }
+    let onboarding = !config.exists();
 
     let db_path = config.get_db_path()?;
-    if !db_path.exists() {
+    if !onboarding && !db_path.exists() {
         eprintln!("Error: Clipboard history database not found.");
         eprintln!("Expected at: {}", db_path.display());
         eprintln!("Make sure the daemon is running or run 'clippie setup'.");
         process::exit(1);
     }
 
-    let db = Database::open(&db_path)?;
-    let entries = db.get_all_entries()?;
-    let db_path_str = db_path.to_string_lossy().to_string();
+    // Restores the terminal on return, on error and (via its panic hook) on panic.
+    let _guard = tui::terminal::TerminalGuard::enter(action.draws_on_stderr())?;
+    // Raw mode is on and input isn't being read yet, so the terminal can be queried.
+    let tui_config = config.load().unwrap_or_default().tui;
+    tui::theme::init(tui_config.background, tui_config.theme, colors);
+    let backend = ratatui::backend::CrosstermBackend::new(action.tui_output());
+    let mut terminal = ratatui::Terminal::new(backend)?;
+
+    let mut welcome = None;
+    let db_path = if onboarding {
+        let Some((db_path, message)) = run_onboarding(&mut terminal).await? else {
+            return Ok(());
+        };
+        welcome = Some(message);
+        db_path
+    } else {
+        db_path
+    };
+
+    run_tui(terminal, db_path.to_string_lossy().to_string(), action, welcome).await
+}
 
-    let mut stdout = std::io::stdout();
-    crossterm::terminal::enable_raw_mode()?;
-    crossterm::execute!(stdout, crossterm::terminal::EnterAlternateScreen)?;
+/// Walk a first-time user through setup. Returns the database path and a status message,
+/// or `None` if they quit.
+async fn run_onboarding(
+    terminal: &mut ratatui::Terminal<ratatui::backend::CrosstermBackend<Box<dyn Write>>>,
+) -> Result<Option<(PathBuf, String)>> {
+    let default_path = ConfigManager::new()?.default_db_path()?;
+    let default_display = match dirs::home_dir().and_then(|home| default_path.strip_prefix(home).ok().map(PathBuf::from)) {
+        Some(relative) => format!("~/{}", relative.display()),
+        None => default_path.display().to_string(),
+    };
+    let mut wizard = tui::onboarding::Onboarding::new(default_display);
 
-    let backend = ratatui::backend::CrosstermBackend::new(stdout);
-    let terminal = ratatui::Terminal::new(backend)?;
-    let result = run_tui(terminal, entries, db_path_str).await;
+    let mut events = tui::EventHandler::new(Duration::from_secs(1));
+    events.set_ticking(false);
+    loop {
+        terminal.draw(|f| {
+            tui::onboarding::draw_onboarding(f, &wizard);
+            tui::theme::adapt(f.buffer_mut());
+        })?;
+        let Some(tui::events::Event::Key(key)) = events.next().await else {
+            continue;
+        };
+        match wizard.handle_key(key) {
+            tui::onboarding::Outcome::Continue => {}
+            tui::onboarding::Outcome::Quit => return Ok(None),
+            tui::onboarding::Outcome::Done => break,
+        }
+    }
+    events.stop();
+    terminal.clear()?;
 
-    crossterm::terminal::disable_raw_mode()?;
-    crossterm::execute!(std::io::stdout(), crossterm::terminal::LeaveAlternateScreen)?;
+    let db_path = commands::setup::configure(|settings| {
+        settings.db_path = wizard.custom_db_path().map(PathBuf::from);
+        settings.max_age_days = wizard.max_age_days();
+    })?;
 
-    result
+    let message = if !wizard.install_daemon {
+        "Setup complete. Run 'clippie install' to capture history at login.".to_string()
+    } else {
+        match commands::install::install_service() {
+            Ok((manager, Ok(()))) => {
+                format!("Daemon installed with {}. Run 'clippie start' to begin capturing.", manager.name())
+            }
+            Ok((_, Err(e))) | Err(e) => format!("Daemon install failed: {}", e),
+        }
+    };
+    Ok(Some((db_path, message)))
 }
 
 async fn run_tui(
-    mut terminal: ratatui::Terminal<ratatui::backend::CrosstermBackend<std::io::Stdout>>,
-    entries: Vec<db::ClipboardEntry>,
+    mut terminal: ratatui::Terminal<ratatui::backend::CrosstermBackend<Box<dyn Write>>>,
     db_path: String,
+    action: PickAction,
+    message: Option<String>,
 ) -> Result<()> {
     let (w, h) = crossterm::terminal::size()
         .map(|(w, h)| (w as usize, h as usize))
         .unwrap_or((80, 24));
 
-    let mut app = tui::App::new(entries, db_path, w, h);
-    let mut event_handler = tui::EventHandler::new();
+    let manager = ConfigManager::new()?;
+    let config = manager.load().unwrap_or_default();
+    let settings = config.tui;
+    let mut app = tui::App::new(Vec::new(), db_path, w, h);
+    if let Ok(profile) = manager.active_profile() {
+        app.profile = profile;
+    }
+    app.auto_refresh = settings.auto_refresh;
+    app.join_separator = settings.join_separator;
+    app.set_layout(settings.layout);
+    app.list_percent = settings.list_percent;
+    app.mask_sensitive = config.sensitive_entries == config::SensitivePolicy::Mask;
+    app.keymap = tui::keymap::Keymap::new(&settings.keymap).unwrap_or_default();
+    app.refresh_incognito();
+    if config.secret_encryption.is_some() {
+        app.lock = Some(tui::lock::Lock::new(settings.lock_passphrase, settings.lock_after_secs));
+    }
+    app.message = message;
+    let mut event_handler = tui::EventHandler::new(Duration::from_millis(settings.tick_rate_ms.max(1)));
+    if let Err(e) = app.load_first_page() {
+        app.show_message(format!("Loading failed: {}", e));
+    }
+    if app.auto_refresh {
+        event_handler.subscribe(ConfigManager::new()?.get_socket_path()?);
+    }
 
     loop {
-        terminal.draw(|f| tui::draw(f, &mut app))?;
+        if app.needs_redraw {
+            terminal.draw(|f| tui::draw(f, &mut app))?;
+            app.needs_redraw = false;
+        }
 
-        if let Some(event) = event_handler.next().await {
-            if tui::handlers::EventHandler::handle(&event, &mut app) {
-                break;
-            }
+        // Handlers hit SQLite synchronously; let the runtime move other tasks off this worker.
+        if let Some(event) = event_handler.next().await
+            && tokio::task::block_in_place(|| tui::handlers::EventHandler::handle(&event, &mut app))
+        {
+            break;
+        }
+        if std::mem::take(&mut app.load_rest) {
+            let before = app.entries.last().map(|e| (e.last_copied.timestamp(), e.id));
+            event_handler.load_entries(&app.db_path, before, app.load_generation);
         }
+        event_handler.set_ticking(app.needs_ticks());
     }
 
-    if let Some(content) = &app.selected_entry {
-        clipboard::set_clipboard_content(content)?;
-        println!("{}", content);
+    event_handler.stop();
+
+    if let Some(entry) = &app.selected_entry {
+        let content = if entry.encrypted {
+            // Leave the alternate screen so gpg/age can prompt for a passphrase.
+            tui::terminal::restore()?;
+            let Some(settings) = ConfigManager::new()?.load()?.secret_encryption else {
+                eprintln!("Error: Entry is encrypted but \"secret_encryption\" is not configured.");
+                return Ok(());
+            };
+            secrets::decrypt(&settings, &entry.content)?
+        } else {
+            urls::clean(&entry.content, &config.url_rules, urls::Stage::Copy)
+        };
+        let flavors = db::Database::open(&app.db_path).and_then(|db| db.flavors_for(entry.id, &content)).unwrap_or_default();
+        match action {
+            PickAction::Print => {
+                clipboard::set_clipboard_entry(&content, entry.kind, &flavors)?;
+                println!("{}", content);
+            }
+            PickAction::Paste => {
+                clipboard::set_clipboard_entry(&content, entry.kind, &flavors)?;
+                clipboard::paste_into_focused_app();
+            }
+            PickAction::Type => {
+                let delay = config.type_delay_ms.unwrap_or(clipboard::DEFAULT_TYPE_DELAY_MS);
+                clipboard::type_into_focused_app(&content, Duration::from_millis(delay))?;
+            }
+            PickAction::Insert => {
+                print!("{}", content);
+                std::io::stdout().flush()?;
+            }
+        }
     }
 
-    event_handler.stop();
     Ok(())
 }
 
-fn get_plist_path() -> std::path::PathBuf {
-    dirs::home_dir().unwrap_or_default().join(DAEMON_PLIST)
-}
-
 async fn cmd_start() -> Result<()> {
     println!("\nStarting the clipboard daemon...\n");
 
-    let plist_path = get_plist_path();
-    if !plist_path.exists() {
-        eprintln!("Error: Daemon not installed. Run 'clippie setup' and choose to install the daemon.");
-        return Ok(());
-    }
-
-    let output = std::process::Command::new("launchctl")
-        .args(["load", "-F"])
-        .arg(&plist_path)
-        .output()?;
-
-    if output.status.success() {
-        println!("✓ Daemon started\n");
-    } else {
-        eprintln!("Failed to start daemon: {}", String::from_utf8_lossy(&output.stderr));
+    let manager = ServiceManager::detect();
+    // An agent left under an old label would keep a second daemon running, or none at all.
+    if service::remove_legacy_agents()? > 0 {
+        println!("✓ Removed the launch agent an older version installed under another label");
+        if !manager.is_installed() {
+            commands::install::install_service()?.1?;
+        }
     }
-
+    manager.start()?;
+    println!("✓ Daemon started\n");
     Ok(())
 }
 
 async fn cmd_stop() -> Result<()> {
     println!("\nStopping the clipboard daemon...\n");
 
-    let output = std::process::Command::new("launchctl")
-        .args(["unload", "-F"])
-        .arg(get_plist_path())
-        .output()?;
+    ServiceManager::detect().stop()?;
+    println!("✓ Daemon stopped\n");
+    Ok(())
+}
 
-    if output.status.success() {
-        println!("✓ Daemon stopped\n");
-    } else {
-        eprintln!("Failed to stop daemon");
-    }
+async fn cmd_restart() -> Result<()> {
+    println!("\nRestarting the clipboard daemon...\n");
 
+    ServiceManager::detect().restart()?;
+    println!("✓ Daemon restarted\n");
     Ok(())
 }
 
-async fn cmd_pause() -> Result<()> {
+async fn cmd_pause(duration: Option<String>) -> Result<()> {
     let config = ConfigManager::new()?;
-    if config.is_paused() {
-        println!("Clipboard monitoring is already paused.");
-    } else {
-        config.set_paused(true)?;
-        println!("Clipboard monitoring paused. New items will not be saved.");
+    let until = match duration {
+        Some(duration) => Some(
+            chrono::Utc::now()
+                + chrono::Duration::from_std(commands::incognito::parse_duration(&duration)?)
+                    .map_err(|_| error::CliError::InvalidInput("Duration is too long".to_string()))?,
+        ),
+        None if config.is_paused() => {
+            println!("Clipboard monitoring is already paused.");
+            return Ok(());
+        }
+        None => None,
+    };
+
+    // A running daemon records the pause itself; otherwise leave the flags for it to find.
+    let request = ipc::Request::Pause { until: until.map(|t| t.timestamp()) };
+    if ipc::send(&config, request).await.is_err() {
+        match until {
+            Some(until) => {
+                config.set_paused(false)?;
+                config.set_incognito(Some(until))?;
+            }
+            None => config.set_paused(true)?,
+        }
+    }
+    match until {
+        Some(until) => println!(
+            "Clipboard monitoring paused until {}. New items will not be saved.",
+            until.with_timezone(&chrono::Local).format("%H:%M")
+        ),
+        None => println!("Clipboard monitoring paused. New items will not be saved."),
     }
     Ok(())
 }
@@ -164,9 +381,12 @@ async fn cmd_resume() -> Result<()> {
     let config = ConfigManager::new()?;
     if !config.is_paused() {
         println!("Clipboard monitoring is not paused.");
-    } else {
+        return Ok(());
+    }
+    if ipc::send(&config, ipc::Request::Resume).await.is_err() {
         config.set_paused(false)?;
-        println!("Clipboard monitoring resumed.");
+        config.clear_incognito()?;
     }
+    println!("Clipboard monitoring resumed.");
     Ok(())
 }
@@ -0,0 +1,239 @@
+use crate::error::Result;
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+const MENU_TITLE_WIDTH: usize = 40;
+
+/// Single-line, truncated label for an entry in the status menu.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn menu_title(content: &str) -> String {
+    let line = content.trim().replace('\n', "↵").replace('\r', "");
+    if line.chars().count() > MENU_TITLE_WIDTH {
+        let truncated: String = line.chars().take(MENU_TITLE_WIDTH - 1).collect();
+        format!("{truncated}…")
+    } else {
+        line
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub async fn run_menubar() -> Result<()> {
+    Err(crate::error::CliError::UnsupportedPlatform(
+        "the menu bar companion requires macOS".to_string(),
+    ))
+}
+
+#[cfg(target_os = "macos")]
+pub async fn run_menubar() -> Result<()> {
+    macos::run()
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::menu_title;
+    use crate::clipboard::set_clipboard_content;
+    use crate::config::{ConfigManager, SensitivePolicy};
+    use crate::db::Database;
+    use crate::error::Result;
+    use crate::service::ServiceManager;
+    use objc2::rc::Retained;
+    use objc2::runtime::{AnyObject, Bool, NSObject, Sel};
+    use objc2::{class, declare_class, msg_send, msg_send_id, mutability, sel, ClassType, DeclaredClass};
+    use objc2_foundation::NSString;
+    use std::ptr;
+
+    #[link(name = "AppKit", kind = "framework")]
+    unsafe extern "C" {}
+
+    const RECENT_ENTRIES: usize = 10;
+    const NS_VARIABLE_STATUS_ITEM_LENGTH: f64 = -1.0;
+    const NS_APPLICATION_ACTIVATION_POLICY_ACCESSORY: isize = 1;
+    const REFRESH_INTERVAL_SECS: f64 = 5.0;
+
+    pub struct Ivars {
+        status_item: Retained<AnyObject>,
+    }
+
+    declare_class!(
+        pub struct MenuTarget;
+
+        // SAFETY:
+        // - NSObject has no subclassing requirements.
+        // - Interior mutability is a safe default.
+        // - MenuTarget does not implement Drop.
+        unsafe impl ClassType for MenuTarget {
+            type Super = NSObject;
+            type Mutability = mutability::InteriorMutable;
+            const NAME: &'static str = "ClippieMenuTarget";
+        }
+
+        impl DeclaredClass for MenuTarget {
+            type Ivars = Ivars;
+        }
+
+        unsafe impl MenuTarget {
+            #[method(copyEntry:)]
+            fn copy_entry(&self, sender: *mut AnyObject) {
+                let id: isize = unsafe { msg_send![sender, tag] };
+                copy_entry_by_id(id as i64);
+            }
+
+            #[method(openTui:)]
+            fn open_tui(&self, _sender: *mut AnyObject) {
+                if let Ok(binary) = std::env::current_exe() {
+                    let _ = std::process::Command::new("open")
+                        .args(["-a", "Terminal"])
+                        .arg(binary)
+                        .spawn();
+                }
+            }
+
+            #[method(refresh:)]
+            fn refresh(&self, _timer: *mut AnyObject) {
+                self.rebuild();
+            }
+        }
+    );
+
+    impl MenuTarget {
+        fn new(status_item: Retained<AnyObject>) -> Retained<Self> {
+            let this = Self::alloc().set_ivars(Ivars { status_item });
+            unsafe { msg_send_id![super(this), init] }
+        }
+
+        /// Refresh the status icon and rebuild the menu from the database.
+        fn rebuild(&self) {
+            let status = capture_status();
+            let icon = if status == "Capturing" { "📋" } else { "⏸" };
+
+            unsafe {
+                let item = &*self.ivars().status_item;
+                let button: *mut AnyObject = msg_send![item, button];
+                if !button.is_null() {
+                    let _: () = msg_send![button, setTitle: &*NSString::from_str(icon)];
+                }
+
+                let menu: Retained<AnyObject> = msg_send_id![msg_send_id![class!(NSMenu), alloc], init];
+
+                let header = menu_item(status, None, self);
+                let _: () = msg_send![&*header, setEnabled: Bool::NO];
+                let _: () = msg_send![&*menu, addItem: &*header];
+                add_separator(&menu);
+
+                let entries = ConfigManager::new()
+                    .and_then(|c| c.get_db_path())
+                    .and_then(Database::open)
+                    .and_then(|db| db.get_recent_previews(RECENT_ENTRIES))
+                    .unwrap_or_default();
+                // There's no key to reveal them here, so sensitive entries stay masked.
+                let show_sensitive = ConfigManager::new()
+                    .and_then(|c| c.load())
+                    .is_ok_and(|c| c.sensitive_entries == SensitivePolicy::Show);
+
+                if entries.is_empty() {
+                    let empty = menu_item("No clipboard history", None, self);
+                    let _: () = msg_send![&*empty, setEnabled: Bool::NO];
+                    let _: () = msg_send![&*menu, addItem: &*empty];
+                }
+                for entry in entries {
+                    let title = if show_sensitive { entry.display_content().into() } else { entry.masked_content() };
+                    let item = menu_item(&menu_title(&title), Some(sel!(copyEntry:)), self);
+                    let _: () = msg_send![&*item, setTag: entry.id as isize];
+                    let _: () = msg_send![&*menu, addItem: &*item];
+                }
+
+                add_separator(&menu);
+                let open = menu_item("Open Clippie…", Some(sel!(openTui:)), self);
+                let _: () = msg_send![&*menu, addItem: &*open];
+
+                let quit = menu_item("Quit", Some(sel!(terminate:)), self);
+                let _: () = msg_send![&*quit, setTarget: ptr::null_mut::<AnyObject>()];
+                let _: () = msg_send![&*menu, addItem: &*quit];
+
+                let _: () = msg_send![item, setMenu: &*menu];
+            }
+        }
+    }
+
+    fn capture_status() -> &'static str {
+        let paused = ConfigManager::new().map(|c| c.is_paused()).unwrap_or(false);
+        if paused {
+            "Paused"
+        } else if ServiceManager::detect().status().running {
+            "Capturing"
+        } else {
+            "Daemon not running"
+        }
+    }
+
+    fn copy_entry_by_id(id: i64) {
+        let entry = ConfigManager::new()
+            .and_then(|c| c.get_db_path())
+            .and_then(Database::open)
+            .and_then(|db| db.get_entry(id));
+        if let Ok(Some(entry)) = entry {
+            let rules = ConfigManager::new().and_then(|c| c.load()).map(|c| c.url_rules).unwrap_or_default();
+            let _ = set_clipboard_content(&crate::urls::clean(&entry.content, &rules, crate::urls::Stage::Copy));
+        }
+    }
+
+    unsafe fn menu_item(title: &str, action: Option<Sel>, target: &MenuTarget) -> Retained<AnyObject> {
+        let title = NSString::from_str(title);
+        let key = NSString::from_str("");
+        let item: Retained<AnyObject> = msg_send_id![
+            msg_send_id![class!(NSMenuItem), alloc],
+            initWithTitle: &*title,
+            action: action,
+            keyEquivalent: &*key
+        ];
+        let _: () = msg_send![&*item, setTarget: target];
+        item
+    }
+
+    unsafe fn add_separator(menu: &AnyObject) {
+        let separator: Retained<AnyObject> = msg_send_id![class!(NSMenuItem), separatorItem];
+        let _: () = msg_send![menu, addItem: &*separator];
+    }
+
+    pub fn run() -> Result<()> {
+        unsafe {
+            let app: Retained<AnyObject> = msg_send_id![class!(NSApplication), sharedApplication];
+            let _: Bool = msg_send![&*app, setActivationPolicy: NS_APPLICATION_ACTIVATION_POLICY_ACCESSORY];
+
+            let status_bar: Retained<AnyObject> = msg_send_id![class!(NSStatusBar), systemStatusBar];
+            let status_item: Retained<AnyObject> =
+                msg_send_id![&*status_bar, statusItemWithLength: NS_VARIABLE_STATUS_ITEM_LENGTH];
+
+            let target = MenuTarget::new(status_item);
+            target.rebuild();
+
+            let _: Retained<AnyObject> = msg_send_id![
+                class!(NSTimer),
+                scheduledTimerWithTimeInterval: REFRESH_INTERVAL_SECS,
+                target: &*target,
+                selector: sel!(refresh:),
+                userInfo: ptr::null_mut::<AnyObject>(),
+                repeats: Bool::YES
+            ];
+
+            let _: () = msg_send![&*app, run];
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_menu_title_truncates() {
+        let title = menu_title(&"a".repeat(100));
+        assert_eq!(title.chars().count(), MENU_TITLE_WIDTH);
+        assert!(title.ends_with('…'));
+    }
+
+    #[test]
+    fn test_menu_title_single_line() {
+        assert_eq!(menu_title("  one\ntwo  "), "one↵two");
+    }
+}
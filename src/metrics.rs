@@ -0,0 +1,166 @@
+use crate::db::Database;
+use crate::error::Result;
+use crate::http::{self, Response};
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+/// Capture is considered stalled when the clipboard hasn't been polled for this long.
+pub const HEALTH_STALE_AFTER_SECS: i64 = 10;
+
+/// Counters updated by the daemon loop and read by the metrics endpoint.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    captures_total: AtomicU64,
+    errors_total: AtomicU64,
+    last_capture_timestamp: AtomicI64,
+    last_poll_timestamp: AtomicI64,
+    recent_captures: Mutex<VecDeque<i64>>,
+}
+
+impl Metrics {
+    pub fn record_capture(&self) {
+        let now = Utc::now().timestamp();
+        self.captures_total.fetch_add(1, Ordering::Relaxed);
+        self.last_capture_timestamp.store(now, Ordering::Relaxed);
+        if let Ok(mut recent) = self.recent_captures.lock() {
+            recent.push_back(now);
+            prune_older_than(&mut recent, now - 60);
+        }
+    }
+
+    pub fn record_poll(&self) {
+        self.last_poll_timestamp.store(Utc::now().timestamp(), Ordering::Relaxed);
+    }
+
+    pub fn record_error(&self) {
+        self.errors_total.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn captures_per_minute(&self) -> usize {
+        let now = Utc::now().timestamp();
+        self.recent_captures
+            .lock()
+            .map(|mut recent| {
+                prune_older_than(&mut recent, now - 60);
+                recent.len()
+            })
+            .unwrap_or(0)
+    }
+
+    /// Capture liveness for `/healthz`; healthy while the poll loop keeps succeeding.
+    pub fn health(&self) -> (bool, Value) {
+        let last_poll = self.last_poll_timestamp.load(Ordering::Relaxed);
+        let since_poll = (last_poll > 0).then(|| Utc::now().timestamp() - last_poll);
+        let healthy = since_poll.is_some_and(|secs| secs <= HEALTH_STALE_AFTER_SECS);
+
+        let body = json!({
+            "status": if healthy { "ok" } else { "stale" },
+            "seconds_since_last_poll": since_poll,
+            "last_capture_timestamp": self.last_capture_timestamp.load(Ordering::Relaxed),
+        });
+        (healthy, body)
+    }
+
+    /// Counters for `clippie status`, read over the control socket.
+    pub fn stats(&self) -> Value {
+        json!({
+            "captures_total": self.captures_total.load(Ordering::Relaxed),
+            "captures_per_minute": self.captures_per_minute(),
+            "errors_total": self.errors_total.load(Ordering::Relaxed),
+            "last_capture_timestamp": self.last_capture_timestamp.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Render the Prometheus text exposition format.
+    pub fn render(&self, entries_total: i64, db_size_bytes: u64) -> String {
+        let samples = [
+            ("clippie_entries_total", "gauge", "Entries stored in the history database", entries_total.to_string()),
+            ("clippie_captures_total", "counter", "Clipboard captures since the daemon started", self.captures_total.load(Ordering::Relaxed).to_string()),
+            ("clippie_captures_per_minute", "gauge", "Captures during the last 60 seconds", self.captures_per_minute().to_string()),
+            ("clippie_db_size_bytes", "gauge", "Size of the history database in bytes", db_size_bytes.to_string()),
+            ("clippie_errors_total", "counter", "Clipboard or database errors since the daemon started", self.errors_total.load(Ordering::Relaxed).to_string()),
+            ("clippie_last_capture_timestamp", "gauge", "Unix time of the last successful capture", self.last_capture_timestamp.load(Ordering::Relaxed).to_string()),
+        ];
+
+        samples
+            .iter()
+            .map(|(name, kind, help, value)| format!("# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n"))
+            .collect()
+    }
+}
+
+fn prune_older_than(recent: &mut VecDeque<i64>, cutoff: i64) {
+    while recent.front().is_some_and(|ts| *ts < cutoff) {
+        recent.pop_front();
+    }
+}
+
+/// Serve `/metrics` and `/healthz` on localhost until the daemon exits.
+pub async fn serve(port: u16, metrics: Arc<Metrics>, db_path: PathBuf) -> Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let metrics = Arc::clone(&metrics);
+        let db_path = db_path.clone();
+
+        tokio::spawn(async move {
+            let response = match http::read_request(&mut stream).await {
+                Some(request) if request.method == "GET" && request.path == "/metrics" => {
+                    let (entries, size) = tokio::task::spawn_blocking(move || {
+                        Database::open(&db_path)
+                            .map(|db| (db.count_entries().unwrap_or(0), db.get_size().unwrap_or(0)))
+                            .unwrap_or((0, 0))
+                    })
+                    .await
+                    .unwrap_or((0, 0));
+                    Response::text(200, "text/plain; version=0.0.4", metrics.render(entries, size))
+                }
+                Some(request) if request.method == "GET" && request.path == "/healthz" => {
+                    let (healthy, body) = metrics.health();
+                    Response::json(if healthy { 200 } else { 503 }, body)
+                }
+                _ => Response::error(404, "not found"),
+            };
+            http::write_response(&mut stream, &response).await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_includes_all_metrics() {
+        let metrics = Metrics::default();
+        metrics.record_capture();
+        metrics.record_error();
+
+        let output = metrics.render(42, 4096);
+        assert!(output.contains("clippie_entries_total 42\n"));
+        assert!(output.contains("clippie_db_size_bytes 4096\n"));
+        assert!(output.contains("clippie_captures_total 1\n"));
+        assert!(output.contains("clippie_captures_per_minute 1\n"));
+        assert!(output.contains("clippie_errors_total 1\n"));
+        assert!(output.contains("# TYPE clippie_last_capture_timestamp gauge"));
+    }
+
+    #[test]
+    fn test_health_requires_recent_poll() {
+        let metrics = Metrics::default();
+        let (healthy, body) = metrics.health();
+        assert!(!healthy);
+        assert_eq!(body["status"], "stale");
+
+        metrics.record_poll();
+        let (healthy, body) = metrics.health();
+        assert!(healthy);
+        assert_eq!(body["seconds_since_last_poll"], 0);
+    }
+}
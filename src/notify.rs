@@ -0,0 +1,29 @@
+use std::process::Command;
+
+/// Post a desktop notification, falling back to stderr (the daemon log).
+pub fn notify(title: &str, message: &str) {
+    eprintln!("{}: {}", title, message);
+
+    if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification \"{}\" with title \"{}\"",
+            escape_applescript(message),
+            escape_applescript(title)
+        );
+        let _ = Command::new("osascript").args(["-e", &script]).output();
+    }
+}
+
+fn escape_applescript(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_applescript() {
+        assert_eq!(escape_applescript(r#"say "hi" \ bye"#), r#"say \"hi\" \\ bye"#);
+    }
+}
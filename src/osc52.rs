@@ -0,0 +1,114 @@
+//! Clipboard access through the terminal with OSC 52 escape sequences, for SSH sessions where
+//! `pbcopy` and `pbpaste` reach the remote machine's clipboard, or none at all.
+
+use crate::error::{CliError, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use std::io::Write;
+
+/// How long to wait for the terminal to answer a paste request; some ask the user first.
+const PASTE_TIMEOUT_MS: u64 = 500;
+
+/// Whether this process runs in an SSH session.
+pub fn over_ssh() -> bool {
+    ["SSH_TTY", "SSH_CONNECTION", "SSH_CLIENT"].iter().any(|var| std::env::var_os(var).is_some())
+}
+
+/// Sequence that sets the clipboard to `content`.
+fn copy_sequence(content: &str) -> String {
+    format!("\x1b]52;c;{}\x07", STANDARD.encode(content))
+}
+
+/// Have the terminal put `content` on its clipboard. The terminal doesn't confirm, so this only
+/// fails when there's no terminal to write to.
+pub fn copy(content: &str) -> Result<()> {
+    let mut tty = open_tty()?;
+    tty.write_all(copy_sequence(content).as_bytes())
+        .and_then(|_| tty.flush())
+        .map_err(|e| CliError::ClipboardError(format!("Failed to write OSC 52 to the terminal: {}", e)))
+}
+
+/// Ask the terminal for its clipboard. `None` when it's empty or the terminal doesn't answer.
+pub fn paste() -> Result<Option<String>> {
+    let was_raw = crossterm::terminal::is_raw_mode_enabled().unwrap_or(false);
+    // The reply arrives as input, so echo and line buffering have to be off to read it.
+    if !was_raw {
+        crossterm::terminal::enable_raw_mode()?;
+    }
+    let reply = query_clipboard();
+    if !was_raw {
+        crossterm::terminal::disable_raw_mode()?;
+    }
+    Ok(reply.as_deref().and_then(parse_reply).filter(|content| !content.is_empty()))
+}
+
+fn open_tty() -> Result<std::fs::File> {
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .map_err(|e| CliError::ClipboardError(format!("No terminal for OSC 52: {}", e)))
+}
+
+#[cfg(unix)]
+fn query_clipboard() -> Option<Vec<u8>> {
+    use std::io::Read;
+    use std::os::fd::AsRawFd;
+    use std::time::{Duration, Instant};
+
+    let mut tty = open_tty().ok()?;
+    tty.write_all(b"\x1b]52;c;?\x07").ok()?;
+    tty.flush().ok()?;
+
+    let deadline = Instant::now() + Duration::from_millis(PASTE_TIMEOUT_MS);
+    let mut reply = Vec::new();
+    let mut buf = [0u8; 4096];
+    while !(reply.ends_with(b"\x07") || reply.ends_with(b"\x1b\\")) {
+        let remaining = deadline.saturating_duration_since(Instant::now()).as_millis() as i32;
+        let mut pollfd = libc::pollfd { fd: tty.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+        if remaining == 0 || unsafe { libc::poll(&mut pollfd, 1, remaining) } <= 0 {
+            return None;
+        }
+        let n = tty.read(&mut buf).ok()?;
+        if n == 0 {
+            return None;
+        }
+        reply.extend_from_slice(&buf[..n]);
+    }
+    Some(reply)
+}
+
+#[cfg(not(unix))]
+fn query_clipboard() -> Option<Vec<u8>> {
+    None
+}
+
+/// Decode a reply such as `ESC ]52;c;aGVsbG8= BEL`, terminated by BEL or ST.
+fn parse_reply(reply: &[u8]) -> Option<String> {
+    let start = reply.windows(4).position(|w| w == b"\x1b]52")?;
+    let body = reply[start + 4..].strip_prefix(b";")?;
+    let body = body.strip_suffix(b"\x07").or_else(|| body.strip_suffix(b"\x1b\\"))?;
+    // Skip the selection letters, e.g. `c` or `p`.
+    let data = &body[body.iter().position(|&b| b == b';')? + 1..];
+    String::from_utf8(STANDARD.decode(data).ok()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_sequence() {
+        assert_eq!(copy_sequence("hello"), "\x1b]52;c;aGVsbG8=\x07");
+        assert_eq!(copy_sequence(""), "\x1b]52;c;\x07");
+    }
+
+    #[test]
+    fn test_parse_reply() {
+        assert_eq!(parse_reply(b"\x1b]52;c;aGVsbG8=\x07").as_deref(), Some("hello"));
+        assert_eq!(parse_reply(b"\x1b]52;p;w6ljaG8=\x1b\\").as_deref(), Some("écho"));
+        assert_eq!(parse_reply(b"\x1b]52;c;\x07").as_deref(), Some(""));
+        assert_eq!(parse_reply(b"\x1b]52;c;?\x07"), None);
+        assert_eq!(parse_reply(b"\x1b]11;rgb:0/0/0\x07"), None);
+    }
+}
@@ -0,0 +1,536 @@
+use crate::error::{CliError, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Where launch agent plists go, under the home directory.
+const LAUNCH_AGENTS_DIR: &str = "Library/LaunchAgents";
+
+pub const LAUNCHD_LABEL: &str = "no.bechsor.clippie-daemon";
+/// Label `brew services` derives from the formula name.
+pub const HOMEBREW_LABEL: &str = "homebrew.mxcl.clippie";
+pub const HOMEBREW_FORMULA: &str = "clippie";
+pub const SYSTEMD_UNIT: &str = "clippie-daemon.service";
+/// Labels older versions installed their launch agent under.
+const LEGACY_LAUNCHD_LABELS: &[&str] = &["no.bechsor.clippy-daemon"];
+
+/// Runtime state of the daemon as reported by the init system.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ServiceStatus {
+    pub running: bool,
+    pub state: Option<String>,
+    pub pid: Option<u32>,
+    pub last_exit_code: Option<String>,
+    pub throttled: bool,
+}
+
+/// The init system responsible for keeping the daemon alive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ServiceManager {
+    Launchd,
+    /// launchd, but the plist is owned by `brew services`.
+    Homebrew,
+    Systemd,
+}
+
+impl ServiceManager {
+    pub fn detect() -> Self {
+        if !cfg!(target_os = "macos") {
+            return Self::Systemd;
+        }
+        let brew_installed = std::env::current_exe()
+            .ok()
+            .and_then(|p| p.canonicalize().ok())
+            .is_some_and(|p| is_homebrew_path(&p));
+        if brew_installed || Self::Homebrew.is_installed() {
+            Self::Homebrew
+        } else {
+            Self::Launchd
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Launchd => "launchd",
+            Self::Homebrew => "brew services",
+            Self::Systemd => "systemd",
+        }
+    }
+
+    fn launchd_label(&self) -> &'static str {
+        match self {
+            Self::Homebrew => HOMEBREW_LABEL,
+            _ => LAUNCHD_LABEL,
+        }
+    }
+
+    pub fn service_path(&self) -> Result<PathBuf> {
+        let home = home_dir()?;
+        Ok(match self {
+            Self::Launchd | Self::Homebrew => home
+                .join(LAUNCH_AGENTS_DIR)
+                .join(format!("{}.plist", self.launchd_label())),
+            Self::Systemd => home.join(".config/systemd/user").join(SYSTEMD_UNIT),
+        })
+    }
+
+    pub fn is_installed(&self) -> bool {
+        self.service_path().map(|p| p.exists()).unwrap_or(false)
+    }
+
+    /// The daemon binary named by the installed service definition.
+    pub fn installed_binary(&self) -> Option<PathBuf> {
+        parse_service_binary(&fs::read_to_string(self.service_path().ok()?).ok()?)
+    }
+
+    /// Write the service definition and register it with the init system, replacing a loaded
+    /// launch agent so the new plist takes effect.
+    pub fn install(&self, binary: &Path, log_dir: &Path) -> Result<()> {
+        if *self == Self::Homebrew {
+            // brew writes its own plist from the formula's `service` block.
+            return succeeded(brew(&["services", "start", HOMEBREW_FORMULA])?);
+        }
+
+        let path = self.service_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, self.render(binary, log_dir))?;
+
+        match self {
+            Self::Launchd | Self::Homebrew => {
+                if self.is_loaded() {
+                    launchctl_run(&["bootout", &self.service_target()])?;
+                }
+                launchctl_run(&["bootstrap", &gui_domain(), &path.to_string_lossy()])
+            }
+            Self::Systemd => {
+                succeeded(systemctl(&["daemon-reload"])?)?;
+                succeeded(systemctl(&["enable", SYSTEMD_UNIT])?)
+            }
+        }
+    }
+
+    /// Load and start the daemon; a loaded launch agent is kicked to make sure it's running.
+    pub fn start(&self) -> Result<()> {
+        match self {
+            Self::Launchd if self.is_loaded() => launchctl_run(&["kickstart", &self.service_target()]),
+            Self::Launchd => {
+                self.ensure_installed()?;
+                launchctl_run(&["bootstrap", &gui_domain(), &self.service_path()?.to_string_lossy()])
+            }
+            Self::Homebrew => succeeded(brew(&["services", "start", HOMEBREW_FORMULA])?),
+            Self::Systemd => {
+                self.ensure_installed()?;
+                succeeded(systemctl(&["start", SYSTEMD_UNIT])?)
+            }
+        }
+    }
+
+    pub fn stop(&self) -> Result<()> {
+        match self {
+            Self::Launchd => launchctl_run(&["bootout", &self.service_target()]),
+            Self::Homebrew => succeeded(brew(&["services", "stop", HOMEBREW_FORMULA])?),
+            Self::Systemd => succeeded(systemctl(&["stop", SYSTEMD_UNIT])?),
+        }
+    }
+
+    /// Stop the daemon and remove its service definition.
+    pub fn uninstall(&self) -> Result<()> {
+        match self {
+            Self::Launchd => {
+                if self.is_loaded() {
+                    launchctl_run(&["bootout", &self.service_target()])?;
+                }
+            }
+            // brew deletes its plist itself.
+            Self::Homebrew => return succeeded(brew(&["services", "stop", HOMEBREW_FORMULA])?),
+            Self::Systemd => {
+                if self.is_installed() {
+                    succeeded(systemctl(&["disable", "--now", SYSTEMD_UNIT])?)?;
+                }
+            }
+        }
+        let path = self.service_path()?;
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        if *self == Self::Systemd {
+            succeeded(systemctl(&["daemon-reload"])?)?;
+        }
+        Ok(())
+    }
+
+    /// Kill and relaunch the daemon, or start it if it isn't loaded.
+    pub fn restart(&self) -> Result<()> {
+        match self {
+            Self::Launchd if self.is_loaded() => launchctl_run(&["kickstart", "-k", &self.service_target()]),
+            Self::Launchd => self.start(),
+            Self::Homebrew => succeeded(brew(&["services", "restart", HOMEBREW_FORMULA])?),
+            Self::Systemd => {
+                self.ensure_installed()?;
+                succeeded(systemctl(&["restart", SYSTEMD_UNIT])?)
+            }
+        }
+    }
+
+    /// Whether launchd knows the agent, running or not.
+    fn is_loaded(&self) -> bool {
+        launchctl(&["print", &self.service_target()]).is_ok_and(|o| o.status.success())
+    }
+
+    fn ensure_installed(&self) -> Result<()> {
+        if self.is_installed() {
+            Ok(())
+        } else {
+            Err(CliError::ServiceError("the daemon is not installed; run 'clippie install'".to_string()))
+        }
+    }
+
+    pub fn status(&self) -> ServiceStatus {
+        match self {
+            Self::Launchd | Self::Homebrew => launchctl(&["print", &self.service_target()])
+                .ok()
+                .filter(|o| o.status.success())
+                .map(|o| parse_launchctl_print(&String::from_utf8_lossy(&o.stdout)))
+                .unwrap_or_default(),
+            Self::Systemd => systemctl(&[
+                "show",
+                SYSTEMD_UNIT,
+                "--property=ActiveState,SubState,MainPID,ExecMainStatus",
+            ])
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| parse_systemctl_show(&String::from_utf8_lossy(&o.stdout)))
+            .unwrap_or_default(),
+        }
+    }
+
+    pub fn render(&self, binary: &Path, log_dir: &Path) -> String {
+        let log = log_dir.join("daemon.log");
+        let err = log_dir.join("daemon.err");
+        match self {
+            Self::Launchd | Self::Homebrew => format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+        <string>daemon</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <true/>
+    <key>StandardOutPath</key>
+    <string>{}</string>
+    <key>StandardErrorPath</key>
+    <string>{}</string>
+</dict>
+</plist>"#,
+                self.launchd_label(),
+                escape_xml(&binary.display().to_string()),
+                escape_xml(&log.display().to_string()),
+                escape_xml(&err.display().to_string())
+            ),
+            Self::Systemd => format!(
+                "[Unit]
+Description=Clippie clipboard history daemon
+
+[Service]
+ExecStart={} daemon
+Restart=always
+RestartSec=2
+StandardOutput=append:{}
+StandardError=append:{}
+
+[Install]
+WantedBy=default.target
+",
+                quote_systemd(&binary.display().to_string()),
+                escape_specifiers(&log.display().to_string()),
+                escape_specifiers(&err.display().to_string())
+            ),
+        }
+    }
+
+    fn service_target(&self) -> String {
+        format!("{}/{}", gui_domain(), self.launchd_label())
+    }
+}
+
+/// Launch agent plists left by older versions under a label that's no longer used.
+pub fn legacy_agents() -> Vec<PathBuf> {
+    match home_dir() {
+        Ok(home) if cfg!(target_os = "macos") => legacy_agents_in(&home.join(LAUNCH_AGENTS_DIR)),
+        _ => vec![],
+    }
+}
+
+fn legacy_agents_in(dir: &Path) -> Vec<PathBuf> {
+    LEGACY_LAUNCHD_LABELS
+        .iter()
+        .map(|label| dir.join(format!("{}.plist", label)))
+        .filter(|path| path.exists())
+        .collect()
+}
+
+/// Unload and delete the agents from `legacy_agents`, which would run a second daemon or none.
+/// Returns how many were removed.
+pub fn remove_legacy_agents() -> Result<usize> {
+    let agents = legacy_agents();
+    for path in &agents {
+        if let Some(label) = path.file_stem() {
+            // Fails when it isn't loaded, which is fine.
+            let _ = launchctl(&["bootout", &format!("{}/{}", gui_domain(), label.to_string_lossy())]);
+        }
+        fs::remove_file(path)?;
+    }
+    Ok(agents.len())
+}
+
+/// Binaries under a Homebrew prefix are managed through `brew services`.
+fn is_homebrew_path(path: &Path) -> bool {
+    let path = path.to_string_lossy();
+    path.contains("/Cellar/") || path.starts_with("/opt/homebrew/")
+}
+
+/// The program in a unit's `ExecStart=` or a plist's `ProgramArguments`.
+fn parse_service_binary(definition: &str) -> Option<PathBuf> {
+    if let Some(exec) = definition.lines().find_map(|line| line.strip_prefix("ExecStart=")) {
+        return unquote_systemd(exec).map(PathBuf::from);
+    }
+    let arguments = definition.split("<key>ProgramArguments</key>").nth(1)?;
+    let start = arguments.find("<string>")? + "<string>".len();
+    let end = start + arguments[start..].find("</string>")?;
+    let program = arguments[start..end].replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&");
+    Some(PathBuf::from(program))
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// A double-quoted `ExecStart=` word, so paths with spaces stay one argument; `%` is doubled so
+/// systemd doesn't read it as a specifier.
+fn quote_systemd(word: &str) -> String {
+    let escaped = escape_specifiers(word).replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{}\"", escaped)
+}
+
+/// Settings like `StandardOutput=append:` take the rest of the line as is, bar `%` specifiers.
+fn escape_specifiers(text: &str) -> String {
+    text.replace('%', "%%")
+}
+
+/// The first word of an `ExecStart=` line, quoted or not.
+fn unquote_systemd(exec: &str) -> Option<String> {
+    let Some(quoted) = exec.strip_prefix('"') else {
+        return exec.split_whitespace().next().map(|word| word.replace("%%", "%"));
+    };
+    let mut word = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some(word.replace("%%", "%")),
+            '\\' => word.push(chars.next()?),
+            c => word.push(c),
+        }
+    }
+    None
+}
+
+/// Parse the `key = value` lines of `launchctl print gui/<uid>/<label>`.
+fn parse_launchctl_print(output: &str) -> ServiceStatus {
+    let mut status = ServiceStatus::default();
+    for line in output.lines() {
+        let Some((key, value)) = line.trim().split_once(" = ") else {
+            continue;
+        };
+        match key {
+            "state" if status.state.is_none() => status.state = Some(value.to_string()),
+            "pid" => status.pid = value.parse().ok(),
+            "last exit code" => status.last_exit_code = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    status.running = status.state.as_deref() == Some("running");
+    status.throttled = status.state.as_deref() == Some("spawn scheduled")
+        || output.contains("(throttled)");
+    status
+}
+
+/// Parse the `Key=Value` lines of `systemctl --user show`.
+fn parse_systemctl_show(output: &str) -> ServiceStatus {
+    let mut status = ServiceStatus::default();
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match key {
+            "SubState" => {
+                status.throttled = value == "auto-restart";
+                status.state = Some(value.to_string());
+            }
+            "ActiveState" => status.running = value == "active",
+            "MainPID" => status.pid = value.parse().ok().filter(|pid| *pid != 0),
+            "ExecMainStatus" => status.last_exit_code = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    status
+}
+
+fn gui_domain() -> String {
+    // SAFETY: getuid has no preconditions and cannot fail.
+    format!("gui/{}", unsafe { libc::getuid() })
+}
+
+fn launchctl(args: &[&str]) -> Result<Output> {
+    Ok(Command::new("launchctl").args(args).output()?)
+}
+
+/// Run launchctl, turning a failure into an error that says what to do about it.
+fn launchctl_run(args: &[&str]) -> Result<()> {
+    let output = launchctl(args)?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(CliError::ServiceError(launchctl_failure(
+        output.status.code(),
+        &String::from_utf8_lossy(&output.stderr),
+    )))
+}
+
+/// Explain a launchctl exit code; the stderr alone ("Input/output error") rarely helps.
+fn launchctl_failure(code: Option<i32>, stderr: &str) -> String {
+    match code {
+        Some(3 | 113) => "the launch agent is not loaded; run 'clippie start'".to_string(),
+        Some(5 | 37) => "launchd could not load the agent; it may already be loaded ('clippie restart' relaunches it) \
+                         or its plist may be broken ('clippie doctor' checks it)"
+            .to_string(),
+        Some(112 | 125) => "there is no GUI login session to run the agent in; \
+                            run clippie from a logged-in desktop session, not over SSH or with sudo"
+            .to_string(),
+        _ => format!("launchctl failed: {}", stderr.trim()),
+    }
+}
+
+/// `Ok` if the command succeeded, else an error carrying its stderr.
+fn succeeded(output: Output) -> Result<()> {
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(CliError::ServiceError(String::from_utf8_lossy(&output.stderr).trim().to_string()))
+    }
+}
+
+fn brew(args: &[&str]) -> Result<Output> {
+    Ok(Command::new("brew").args(args).output()?)
+}
+
+fn systemctl(args: &[&str]) -> Result<Output> {
+    Ok(Command::new("systemctl").arg("--user").args(args).output()?)
+}
+
+fn home_dir() -> Result<PathBuf> {
+    dirs::home_dir().ok_or(CliError::ConfigError("Could not determine home directory".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_plist() {
+        let plist = ServiceManager::Launchd.render(Path::new("/bin/clippie"), Path::new("/logs"));
+        assert!(plist.contains("<string>no.bechsor.clippie-daemon</string>"));
+        assert!(plist.contains("<string>/bin/clippie</string>"));
+        assert!(plist.contains("<string>/logs/daemon.err</string>"));
+
+        let plist = ServiceManager::Launchd.render(Path::new("/Apps/Tom & Jerry/<bin>/clippie"), Path::new("/logs"));
+        assert!(plist.contains("<string>/Apps/Tom &amp; Jerry/&lt;bin&gt;/clippie</string>"));
+    }
+
+    #[test]
+    fn test_homebrew_label() {
+        let path = ServiceManager::Homebrew.service_path().unwrap();
+        assert!(path.ends_with("Library/LaunchAgents/homebrew.mxcl.clippie.plist"));
+        assert!(is_homebrew_path(Path::new("/opt/homebrew/Cellar/clippie/2.0.5/bin/clippie")));
+        assert!(is_homebrew_path(Path::new("/usr/local/Cellar/clippie/2.0.5/bin/clippie")));
+        assert!(!is_homebrew_path(Path::new("/Users/me/.cargo/bin/clippie")));
+    }
+
+    #[test]
+    fn test_render_systemd_unit() {
+        let unit = ServiceManager::Systemd.render(Path::new("/bin/clippie"), Path::new("/logs"));
+        assert!(unit.contains("ExecStart=\"/bin/clippie\" daemon"));
+        assert!(unit.contains("StandardOutput=append:/logs/daemon.log"));
+
+        let unit = ServiceManager::Systemd.render(Path::new("/opt/my \"apps\"/100%/clippie"), Path::new("/my logs"));
+        assert!(unit.contains(r#"ExecStart="/opt/my \"apps\"/100%%/clippie" daemon"#));
+        assert!(unit.contains("StandardError=append:/my logs/daemon.err"));
+        assert!(unit.contains("WantedBy=default.target"));
+    }
+
+    #[test]
+    fn test_parse_service_binary() {
+        for manager in [ServiceManager::Launchd, ServiceManager::Systemd] {
+            for binary in ["/usr/local/bin/clippie", "/Users/me/My $Tools & 100%/clippie \"dev\""] {
+                let definition = manager.render(Path::new(binary), Path::new("/logs"));
+                assert_eq!(parse_service_binary(&definition), Some(PathBuf::from(binary)));
+            }
+        }
+        assert_eq!(parse_service_binary("ExecStart=/bin/clippie daemon"), Some(PathBuf::from("/bin/clippie")));
+        assert_eq!(parse_service_binary("<plist></plist>"), None);
+    }
+
+    #[test]
+    fn test_legacy_agents() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("no.bechsor.clippie-daemon.plist"), "").unwrap();
+        assert!(legacy_agents_in(dir.path()).is_empty());
+
+        std::fs::write(dir.path().join("no.bechsor.clippy-daemon.plist"), "").unwrap();
+        assert_eq!(legacy_agents_in(dir.path()), [dir.path().join("no.bechsor.clippy-daemon.plist")]);
+    }
+
+    #[test]
+    fn test_launchctl_failure() {
+        assert!(launchctl_failure(Some(113), "Could not find service").contains("not loaded"));
+        assert!(launchctl_failure(Some(5), "Bootstrap failed: 5: Input/output error").contains("already be loaded"));
+        assert!(launchctl_failure(Some(125), "Domain does not support specified action").contains("no GUI login session"));
+        assert_eq!(launchctl_failure(Some(1), "Unrecognized subcommand\n"), "launchctl failed: Unrecognized subcommand");
+    }
+
+    #[test]
+    fn test_parse_launchctl_print() {
+        let output = "gui/501/no.bechsor.clippie-daemon = {
+\tactive count = 1
+\tpath = /Users/me/Library/LaunchAgents/no.bechsor.clippie-daemon.plist
+\tstate = running
+\tprogram = /usr/local/bin/clippie
+\tpid = 4242
+\tlast exit code = 0
+}";
+        let status = parse_launchctl_print(output);
+        assert!(status.running);
+        assert_eq!(status.pid, Some(4242));
+        assert_eq!(status.last_exit_code.as_deref(), Some("0"));
+        assert!(!status.throttled);
+    }
+
+    #[test]
+    fn test_parse_systemctl_show_throttled() {
+        let output = "ActiveState=activating\nSubState=auto-restart\nMainPID=0\nExecMainStatus=1\n";
+        let status = parse_systemctl_show(output);
+        assert!(!status.running);
+        assert!(status.throttled);
+        assert_eq!(status.pid, None);
+        assert_eq!(status.last_exit_code.as_deref(), Some("1"));
+    }
+}
@@ -0,0 +1,488 @@
+//! Exchange of entries between the daemons on several machines, configured under `[sync]`.
+//!
+//! Each daemon connects to its `peers` every `interval_secs`, pulls the entries copied there since
+//! the last exchange and pushes its own. Entries merge by content hash, keeping the earliest
+//! `created_at`, the latest `last_copied` and the larger copy count, so receiving an entry twice,
+//! or from two directions, changes nothing.
+//!
+//! Connections start with a handshake of random nonces; both sides derive a session key from
+//! them and `sync.key`, and every frame after it is ChaCha20-Poly1305 sealed with that key. A peer
+//! without the key can't read the traffic or get its first frame accepted, and frames can't be
+//! replayed into another connection.
+
+use crate::clipboard::duplicate_hash;
+use crate::config::{random_bytes, DuplicatePolicy, SyncConfig};
+use crate::db::{Database, NewEntry};
+use crate::error::{CliError, Result};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+
+/// Sent first by both sides; a different value means another program or protocol version.
+const MAGIC: &[u8; 8] = b"CLIPSYN1";
+/// Entries per frame; a backlog goes over in several.
+const BATCH: usize = 200;
+/// Frames larger than this are refused, so a bad length can't exhaust memory.
+const MAX_FRAME: usize = 64 * 1024 * 1024;
+/// Longest wait to connect, or for a peer's next frame.
+const IO_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Position in a machine's entries, in copy order. Only the machine it came from compares it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Cursor {
+    last_copied: i64,
+    id: i64,
+}
+
+/// An entry as it travels between machines.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SyncEntry {
+    content: String,
+    created_at: i64,
+    last_copied: i64,
+    copy_count: i64,
+    template: bool,
+    source_app: Option<String>,
+}
+
+impl SyncEntry {
+    /// Hashed with this machine's duplicate policy, so it merges like a local copy would.
+    fn into_new_entry(self, duplicates: &DuplicatePolicy) -> NewEntry {
+        let timestamp = |secs| chrono::DateTime::from_timestamp(secs, 0).unwrap_or_default();
+        NewEntry {
+            content_hash: duplicate_hash(&self.content, duplicates),
+            created_at: timestamp(self.created_at),
+            last_copied: timestamp(self.last_copied),
+            copy_count: self.copy_count,
+            template: self.template,
+            source_app: self.source_app,
+            ..NewEntry::new(&self.content)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Message {
+    /// Ask for entries after `after`.
+    Pull { after: Cursor },
+    /// Answer to `Pull`; `more` means another pull from `next` has more.
+    Entries { entries: Vec<SyncEntry>, next: Cursor, more: bool },
+    Push { entries: Vec<SyncEntry> },
+    /// Answer to `Push`, with how many of the entries were new.
+    Merged { inserted: usize },
+}
+
+/// A connection after the handshake: length-prefixed sealed frames, each with its own nonce.
+struct Channel<S> {
+    stream: S,
+    cipher: ChaCha20Poly1305,
+    /// 0 for the side that connected, 1 for the one that accepted; keeps the two directions' nonces apart.
+    direction: u8,
+    sent: u64,
+    received: u64,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Channel<S> {
+    async fn handshake(mut stream: S, key: &str, connecting: bool) -> Result<Self> {
+        let mut ours = [0u8; 32];
+        random_bytes(&mut ours)?;
+        stream.write_all(MAGIC).await?;
+        stream.write_all(&ours).await?;
+        stream.flush().await?;
+
+        let mut magic = [0u8; 8];
+        let mut theirs = [0u8; 32];
+        within_timeout(stream.read_exact(&mut magic)).await?;
+        if &magic != MAGIC {
+            return Err(CliError::InvalidInput("Not a clippie sync peer, or a different version".to_string()));
+        }
+        within_timeout(stream.read_exact(&mut theirs)).await?;
+
+        let (first, second) = if connecting { (ours, theirs) } else { (theirs, ours) };
+        let session_key = session_key(key, &first, &second);
+        Ok(Channel {
+            stream,
+            cipher: ChaCha20Poly1305::new(Key::from_slice(&session_key)),
+            direction: if connecting { 0 } else { 1 },
+            sent: 0,
+            received: 0,
+        })
+    }
+
+    async fn send(&mut self, message: &Message) -> Result<()> {
+        let nonce = frame_nonce(self.direction, self.sent);
+        self.sent += 1;
+        let sealed = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), serde_json::to_vec(message)?.as_slice())
+            .map_err(|_| CliError::EncryptionError("Sealing a sync frame failed".to_string()))?;
+        self.stream.write_all(&(sealed.len() as u32).to_be_bytes()).await?;
+        self.stream.write_all(&sealed).await?;
+        self.stream.flush().await?;
+        Ok(())
+    }
+
+    /// The next message, or `None` when the peer closed the connection between messages.
+    async fn recv(&mut self) -> Result<Option<Message>> {
+        let mut length = [0u8; 4];
+        match within_timeout(self.stream.read_exact(&mut length)).await {
+            Err(CliError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            result => result?,
+        };
+        let length = u32::from_be_bytes(length) as usize;
+        if length > MAX_FRAME {
+            return Err(CliError::InvalidInput(format!("Sync frame of {} bytes is too large", length)));
+        }
+        let mut sealed = vec![0u8; length];
+        within_timeout(self.stream.read_exact(&mut sealed)).await?;
+
+        let nonce = frame_nonce(1 - self.direction, self.received);
+        self.received += 1;
+        let plain = self.cipher.decrypt(Nonce::from_slice(&nonce), sealed.as_slice()).map_err(|_| {
+            CliError::EncryptionError("A sync frame failed to authenticate; check that sync.key matches on both machines".to_string())
+        })?;
+        Ok(Some(serde_json::from_slice(&plain)?))
+    }
+
+    async fn expect(&mut self) -> Result<Message> {
+        self.recv()
+            .await?
+            .ok_or_else(|| CliError::InvalidInput("The peer closed the connection".to_string()))
+    }
+}
+
+async fn within_timeout<T>(io: impl std::future::Future<Output = std::io::Result<T>>) -> Result<T> {
+    Ok(tokio::time::timeout(IO_TIMEOUT, io)
+        .await
+        .map_err(|_| CliError::InvalidInput("The sync peer stopped answering".to_string()))??)
+}
+
+fn session_key(key: &str, connecting: &[u8; 32], accepting: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(MAGIC);
+    hasher.update(key.as_bytes());
+    hasher.update(connecting);
+    hasher.update(accepting);
+    hasher.finalize().into()
+}
+
+fn frame_nonce(direction: u8, counter: u64) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[0] = direction;
+    nonce[4..].copy_from_slice(&counter.to_be_bytes());
+    nonce
+}
+
+/// Exchange counts and the last outcome for one peer, or for everyone who connected to us.
+#[derive(Debug, Default, Clone, Serialize)]
+struct PeerStatus {
+    last_sync: Option<i64>,
+    last_attempt: Option<i64>,
+    error: Option<String>,
+    received: u64,
+    sent: u64,
+}
+
+impl PeerStatus {
+    fn record(&mut self, outcome: &Result<(u64, u64)>) {
+        let now = Utc::now().timestamp();
+        self.last_attempt = Some(now);
+        match outcome {
+            Ok((received, sent)) => {
+                self.last_sync = Some(now);
+                self.error = None;
+                self.received += received;
+                self.sent += sent;
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+}
+
+/// What `clippie sync status` shows, kept up to date by the sync tasks.
+#[derive(Debug, Default)]
+pub struct SyncStatus {
+    listen: Option<String>,
+    listen_error: Mutex<Option<String>>,
+    peers: Mutex<BTreeMap<String, PeerStatus>>,
+    inbound: Mutex<PeerStatus>,
+}
+
+impl SyncStatus {
+    pub fn to_json(&self) -> Value {
+        let peers: Vec<Value> = self
+            .peers
+            .lock()
+            .map(|peers| {
+                peers
+                    .iter()
+                    .map(|(address, status)| {
+                        let mut value = json!(status);
+                        value["address"] = json!(address);
+                        value
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        json!({
+            "listen": self.listen,
+            "listen_error": self.listen_error.lock().ok().and_then(|error| error.clone()),
+            "peers": peers,
+            "inbound": self.inbound.lock().map(|inbound| json!(*inbound)).unwrap_or_default(),
+        })
+    }
+
+    fn record_peer(&self, address: &str, outcome: &Result<(u64, u64)>) {
+        if let Ok(mut peers) = self.peers.lock() {
+            peers.entry(address.to_string()).or_default().record(outcome);
+        }
+    }
+}
+
+/// Settings and handles every sync task shares.
+struct Context {
+    key: String,
+    db_path: PathBuf,
+    duplicates: DuplicatePolicy,
+    status: Arc<SyncStatus>,
+    /// Tells an open TUI that received entries were written.
+    events: broadcast::Sender<Value>,
+}
+
+impl Context {
+    /// Up to `BATCH` of this machine's entries after `after`, the cursor past them, and whether more follow.
+    async fn outgoing(&self, after: Cursor) -> Result<(Vec<SyncEntry>, Cursor, bool)> {
+        let db_path = self.db_path.clone();
+        let rows = tokio::task::spawn_blocking(move || Database::open(&db_path)?.sync_entries_after(after.last_copied, after.id, BATCH))
+            .await
+            .map_err(|e| CliError::InvalidInput(e.to_string()))??;
+        let next = rows
+            .last()
+            .map_or(after, |(entry, _)| Cursor { last_copied: entry.last_copied.timestamp(), id: entry.id });
+        let more = rows.len() == BATCH;
+        let entries = rows
+            .into_iter()
+            .map(|(entry, copy_count)| SyncEntry {
+                content: entry.content,
+                created_at: entry.created_at.timestamp(),
+                last_copied: entry.last_copied.timestamp(),
+                copy_count,
+                template: entry.template,
+                source_app: entry.source_app,
+            })
+            .collect();
+        Ok((entries, next, more))
+    }
+
+    /// Merge received entries; returns how many were new here.
+    async fn merge(&self, entries: Vec<SyncEntry>) -> Result<usize> {
+        if entries.is_empty() {
+            return Ok(0);
+        }
+        let db_path = self.db_path.clone();
+        let entries: Vec<NewEntry> = entries.into_iter().map(|entry| entry.into_new_entry(&self.duplicates)).collect();
+        let stats = tokio::task::spawn_blocking(move || Database::open(&db_path)?.merge_synced(&entries))
+            .await
+            .map_err(|e| CliError::InvalidInput(e.to_string()))??;
+        if stats.inserted > 0 {
+            let _ = self.events.send(json!({ "event": "capture", "count": stats.inserted }));
+        }
+        Ok(stats.inserted)
+    }
+}
+
+/// Where this daemon is with one peer; starts over at every daemon start, which merging makes harmless.
+#[derive(Debug, Default)]
+struct Cursors {
+    pulled: Cursor,
+    pushed: Cursor,
+}
+
+/// Start listening and exchanging with peers in the background. The returned status is what
+/// `clippie sync status` reads over the control socket.
+pub fn start(
+    settings: SyncConfig,
+    duplicates: DuplicatePolicy,
+    db_path: PathBuf,
+    events: broadcast::Sender<Value>,
+) -> Arc<SyncStatus> {
+    let status = Arc::new(SyncStatus { listen: settings.listen.clone(), ..SyncStatus::default() });
+    let context = Arc::new(Context { key: settings.key, db_path, duplicates, status: Arc::clone(&status), events });
+
+    if let Some(address) = settings.listen {
+        let context = Arc::clone(&context);
+        tokio::spawn(async move {
+            if let Err(e) = listen(&address, Arc::clone(&context)).await {
+                eprintln!("Sync listener on {} failed: {}", address, e);
+                if let Ok(mut error) = context.status.listen_error.lock() {
+                    *error = Some(e.to_string());
+                }
+            }
+        });
+    }
+    let interval = Duration::from_secs(settings.interval_secs.max(1));
+    for peer in settings.peers {
+        let context = Arc::clone(&context);
+        tokio::spawn(async move {
+            let mut cursors = Cursors::default();
+            loop {
+                let outcome = exchange(&peer, &context, &mut cursors).await;
+                context.status.record_peer(&peer, &outcome);
+                tokio::time::sleep(interval).await;
+            }
+        });
+    }
+    status
+}
+
+async fn listen(address: &str, context: Arc<Context>) -> Result<()> {
+    let listener = TcpListener::bind(address).await?;
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let context = Arc::clone(&context);
+        tokio::spawn(async move {
+            let outcome = answer(stream, &context).await;
+            if let Ok(mut inbound) = context.status.inbound.lock() {
+                inbound.record(&outcome);
+            }
+        });
+    }
+}
+
+/// Serve one connected peer's pulls and pushes. Returns the entries received and sent.
+async fn answer<S: AsyncRead + AsyncWrite + Unpin>(stream: S, context: &Context) -> Result<(u64, u64)> {
+    let mut channel = Channel::handshake(stream, &context.key, false).await?;
+    let (mut received, mut sent) = (0, 0);
+    while let Some(message) = channel.recv().await? {
+        match message {
+            Message::Pull { after } => {
+                let (entries, next, more) = context.outgoing(after).await?;
+                sent += entries.len() as u64;
+                channel.send(&Message::Entries { entries, next, more }).await?;
+            }
+            Message::Push { entries } => {
+                let inserted = context.merge(entries).await?;
+                received += inserted as u64;
+                channel.send(&Message::Merged { inserted }).await?;
+            }
+            Message::Entries { .. } | Message::Merged { .. } => {
+                return Err(CliError::InvalidInput("Unexpected sync message".to_string()));
+            }
+        }
+    }
+    Ok((received, sent))
+}
+
+async fn exchange(peer: &str, context: &Context, cursors: &mut Cursors) -> Result<(u64, u64)> {
+    let stream = within_timeout(TcpStream::connect(peer)).await?;
+    exchange_over(stream, context, cursors).await
+}
+
+/// Pull everything new from the peer, then push everything new here. Returns the entries
+/// that were new here and the entries sent.
+async fn exchange_over<S: AsyncRead + AsyncWrite + Unpin>(stream: S, context: &Context, cursors: &mut Cursors) -> Result<(u64, u64)> {
+    let mut channel = Channel::handshake(stream, &context.key, true).await?;
+    let (mut received, mut sent) = (0, 0);
+    loop {
+        channel.send(&Message::Pull { after: cursors.pulled }).await?;
+        let Message::Entries { entries, next, more } = channel.expect().await? else {
+            return Err(CliError::InvalidInput("Unexpected sync message".to_string()));
+        };
+        received += context.merge(entries).await? as u64;
+        cursors.pulled = next;
+        if !more {
+            break;
+        }
+    }
+    loop {
+        let (entries, next, more) = context.outgoing(cursors.pushed).await?;
+        if entries.is_empty() {
+            break;
+        }
+        sent += entries.len() as u64;
+        channel.send(&Message::Push { entries }).await?;
+        let Message::Merged { .. } = channel.expect().await? else {
+            return Err(CliError::InvalidInput("Unexpected sync message".to_string()));
+        };
+        cursors.pushed = next;
+        if !more {
+            break;
+        }
+    }
+    Ok((received, sent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(db_path: PathBuf, key: &str) -> Context {
+        Context {
+            key: key.to_string(),
+            db_path,
+            duplicates: DuplicatePolicy::default(),
+            status: Arc::default(),
+            events: broadcast::channel(4).0,
+        }
+    }
+
+    fn contents(db_path: &std::path::Path) -> Vec<String> {
+        let mut contents: Vec<String> =
+            Database::open(db_path).unwrap().get_all_entries().unwrap().into_iter().map(|e| e.content).collect();
+        contents.sort();
+        contents
+    }
+
+    #[tokio::test]
+    async fn test_exchange_merges_both_ways() {
+        let dir = tempfile::tempdir().unwrap();
+        let (here, there) = (dir.path().join("here.db"), dir.path().join("there.db"));
+        let db = Database::open(&here).unwrap();
+        db.insert_entry("shared", &crate::clipboard::hash_content("shared")).unwrap();
+        db.insert_entry("only here", &crate::clipboard::hash_content("only here")).unwrap();
+        let db = Database::open(&there).unwrap();
+        db.insert_entry("shared", &crate::clipboard::hash_content("shared")).unwrap();
+        db.insert_entry("only there", &crate::clipboard::hash_content("only there")).unwrap();
+
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let remote = context(there.clone(), "0123456789abcdef");
+        let serving = tokio::spawn(async move { answer(server, &remote).await });
+        let mut cursors = Cursors::default();
+        let (received, sent) = exchange_over(client, &context(here.clone(), "0123456789abcdef"), &mut cursors).await.unwrap();
+        assert_eq!(serving.await.unwrap().unwrap(), (1, 2));
+
+        assert_eq!((received, sent), (1, 3));
+        assert_eq!(contents(&here), ["only here", "only there", "shared"]);
+        assert_eq!(contents(&there), ["only here", "only there", "shared"]);
+    }
+
+    #[tokio::test]
+    async fn test_wrong_key_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let remote = context(dir.path().join("there.db"), "0123456789abcdef");
+        let serving = tokio::spawn(async move { answer(server, &remote).await });
+        let error = exchange_over(client, &context(dir.path().join("here.db"), "fedcba9876543210"), &mut Cursors::default()).await;
+        let server_error = serving.await.unwrap().unwrap_err().to_string();
+        assert!(server_error.contains("sync.key"), "{}", server_error);
+        assert!(error.is_err());
+    }
+
+    #[test]
+    fn test_frame_nonces_differ_by_direction_and_counter() {
+        assert_ne!(frame_nonce(0, 1), frame_nonce(1, 1));
+        assert_ne!(frame_nonce(0, 1), frame_nonce(0, 2));
+        assert_ne!(session_key("k", &[1; 32], &[2; 32]), session_key("k", &[2; 32], &[1; 32]));
+    }
+}
@@ -0,0 +1,39 @@
+//! Page titles for URL entries, fetched by the daemon in the background when
+//! `fetch_url_titles` is on.
+
+use crate::db::Database;
+use crate::error::Result;
+use crate::urls;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tokio::sync::broadcast;
+
+/// URL entries titled per round, newest first; older ones wait for the next capture.
+const BATCH_SIZE: usize = 20;
+
+/// Fetch titles for untitled URL entries now and again after every capture.
+pub fn start(db_path: PathBuf, events: &broadcast::Sender<Value>) {
+    let mut captures = events.subscribe();
+    tokio::spawn(async move {
+        loop {
+            let path = db_path.clone();
+            if let Ok(Err(e)) = tokio::task::spawn_blocking(move || fetch_pending(&path)).await {
+                eprintln!("Fetching URL titles failed: {}", e);
+            }
+            // A lagged receiver still means there was a capture.
+            if let Err(broadcast::error::RecvError::Closed) = captures.recv().await {
+                return;
+            }
+        }
+    });
+}
+
+/// Each page is tried once; one that can't be fetched or has no title is stored with an empty one.
+fn fetch_pending(db_path: &Path) -> Result<()> {
+    let db = Database::open(db_path)?;
+    for (id, url) in db.untitled_urls(BATCH_SIZE)? {
+        let title = urls::fetch_title(url.trim()).unwrap_or_default();
+        db.set_title(id, &title)?;
+    }
+    Ok(())
+}
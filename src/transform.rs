@@ -0,0 +1,317 @@
+//! Rewrites applied to an entry on its way to the clipboard, such as changing case or decoding
+//! base64. The stored entry is left as it was.
+
+use crate::error::{CliError, Result};
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    Upper,
+    Lower,
+    Camel,
+    Snake,
+    Trim,
+    Base64Encode,
+    Base64Decode,
+    UrlEncode,
+    UrlDecode,
+    JsonPretty,
+    JsonMinify,
+    CleanUrl,
+}
+
+impl Transform {
+    /// In the order the TUI menu lists them.
+    pub const ALL: [Transform; 12] = [
+        Transform::Upper,
+        Transform::Lower,
+        Transform::Camel,
+        Transform::Snake,
+        Transform::Trim,
+        Transform::Base64Encode,
+        Transform::Base64Decode,
+        Transform::UrlEncode,
+        Transform::UrlDecode,
+        Transform::JsonPretty,
+        Transform::JsonMinify,
+        Transform::CleanUrl,
+    ];
+
+    /// Name given to `clippie transform --op`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Transform::Upper => "upper",
+            Transform::Lower => "lower",
+            Transform::Camel => "camel",
+            Transform::Snake => "snake",
+            Transform::Trim => "trim",
+            Transform::Base64Encode => "base64-encode",
+            Transform::Base64Decode => "base64-decode",
+            Transform::UrlEncode => "url-encode",
+            Transform::UrlDecode => "url-decode",
+            Transform::JsonPretty => "json-pretty",
+            Transform::JsonMinify => "json-minify",
+            Transform::CleanUrl => "clean-url",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Transform> {
+        Transform::ALL.into_iter().find(|op| op.name().eq_ignore_ascii_case(name))
+    }
+
+    /// What the TUI menu shows for it.
+    pub fn label(self) -> &'static str {
+        match self {
+            Transform::Upper => "UPPER CASE",
+            Transform::Lower => "lower case",
+            Transform::Camel => "camelCase",
+            Transform::Snake => "snake_case",
+            Transform::Trim => "Trim whitespace",
+            Transform::Base64Encode => "Base64 encode",
+            Transform::Base64Decode => "Base64 decode",
+            Transform::UrlEncode => "URL encode",
+            Transform::UrlDecode => "URL decode",
+            Transform::JsonPretty => "JSON pretty-print",
+            Transform::JsonMinify => "JSON minify",
+            Transform::CleanUrl => "Clean URL",
+        }
+    }
+
+    /// `text` rewritten, or an error saying why it can't be, e.g. for base64 that doesn't decode.
+    pub fn apply(self, text: &str) -> Result<String> {
+        Ok(match self {
+            Transform::Upper => text.to_uppercase(),
+            Transform::Lower => text.to_lowercase(),
+            Transform::Camel => per_line(text, camel_case),
+            Transform::Snake => per_line(text, |line| words(line).join("_").to_lowercase()),
+            Transform::Trim => text.trim().to_string(),
+            Transform::Base64Encode => STANDARD.encode(text),
+            Transform::Base64Decode => base64_decode(text)?,
+            Transform::UrlEncode => url_encode(text),
+            Transform::UrlDecode => url_decode(text)?,
+            Transform::JsonPretty => reformat_json(text, true)?,
+            Transform::JsonMinify => reformat_json(text, false)?,
+            Transform::CleanUrl => crate::urls::strip_tracking(text),
+        })
+    }
+}
+
+fn per_line(text: &str, f: impl Fn(&str) -> String) -> String {
+    text.split('\n').map(|line| f(line.trim_end_matches('\r'))).collect::<Vec<_>>().join("\n")
+}
+
+/// Words of an identifier or phrase: `parseHTTPResponse` and `parse-http response` both give
+/// parse, HTTP/http, Response/response.
+fn words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let chars: Vec<char> = text.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1);
+        // A capital starts a word after a lowercase letter or digit, or ends an acronym before one.
+        let boundary = c.is_uppercase()
+            && prev.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit()
+                || (p.is_uppercase() && next.is_some_and(|n| n.is_lowercase())));
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.push(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn camel_case(text: &str) -> String {
+    words(text)
+        .iter()
+        .enumerate()
+        .map(|(i, word)| {
+            let lower = word.to_lowercase();
+            if i == 0 {
+                return lower;
+            }
+            let mut chars = lower.chars();
+            chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Bytes of standard or URL-safe base64, padded or not, ignoring line breaks.
+pub(crate) fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let data: String = text.split_whitespace().collect();
+    [STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD].iter().find_map(|engine| engine.decode(&data).ok())
+}
+
+fn base64_decode(text: &str) -> Result<String> {
+    let bytes = decode_base64(text).ok_or_else(|| CliError::InvalidInput("Not valid base64".to_string()))?;
+    String::from_utf8(bytes).map_err(|_| CliError::InvalidInput("Base64 decodes to binary data, not text".to_string()))
+}
+
+/// Percent-encodes everything but the unreserved characters of RFC 3986.
+fn url_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Decodes `%XX` escapes; a `%` not followed by two hex digits is kept as it is.
+fn url_decode(text: &str) -> Result<String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escape {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| CliError::InvalidInput("URL decodes to binary data, not text".to_string()))
+}
+
+/// Re-indent JSON with two spaces, or strip its whitespace. Keys keep their order and numbers
+/// their spelling, which a round trip through `serde_json::Value` wouldn't.
+pub(crate) fn reformat_json(text: &str, pretty: bool) -> Result<String> {
+    serde_json::from_str::<serde::de::IgnoredAny>(text).map_err(|e| CliError::InvalidInput(format!("Not valid JSON: {}", e)))?;
+    let mut out = String::with_capacity(text.len());
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut chars = text.trim().chars().peekable();
+    let newline = |out: &mut String, depth: usize| {
+        out.push('\n');
+        out.push_str(&"  ".repeat(depth));
+    };
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            c if c.is_whitespace() => {}
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '{' | '[' => {
+                out.push(c);
+                // Skip to the next token to keep `{}` and `[]` on one line.
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                if chars.peek().is_some_and(|&next| next == '}' || next == ']') {
+                    out.push(chars.next().expect("peeked"));
+                } else {
+                    depth += 1;
+                    if pretty {
+                        newline(&mut out, depth);
+                    }
+                }
+            }
+            '}' | ']' => {
+                depth -= 1;
+                if pretty {
+                    newline(&mut out, depth);
+                }
+                out.push(c);
+            }
+            ',' => {
+                out.push(c);
+                if pretty {
+                    newline(&mut out, depth);
+                }
+            }
+            ':' => out.push_str(if pretty { ": " } else { ":" }),
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(op: Transform, text: &str) -> String {
+        op.apply(text).unwrap()
+    }
+
+    #[test]
+    fn test_case_transforms() {
+        assert_eq!(apply(Transform::Upper, "Straße"), "STRASSE");
+        assert_eq!(apply(Transform::Lower, "HeLLo"), "hello");
+        assert_eq!(apply(Transform::Camel, "user account-id"), "userAccountId");
+        assert_eq!(apply(Transform::Camel, "parse_HTTP_response"), "parseHttpResponse");
+        assert_eq!(apply(Transform::Snake, "parseHTTPResponse"), "parse_http_response");
+        assert_eq!(apply(Transform::Snake, "userId2\nHello World"), "user_id2\nhello_world");
+        assert_eq!(apply(Transform::Trim, "  \n padded \t\n"), "padded");
+    }
+
+    #[test]
+    fn test_encodings() {
+        assert_eq!(apply(Transform::Base64Encode, "hello"), "aGVsbG8=");
+        assert_eq!(apply(Transform::Base64Decode, "aGVs\nbG8="), "hello");
+        assert_eq!(apply(Transform::Base64Decode, "aGVsbG8"), "hello");
+        assert_eq!(apply(Transform::Base64Decode, "Pz8-"), "??>");
+        assert!(Transform::Base64Decode.apply("not base64!").is_err());
+        assert!(Transform::Base64Decode.apply("//79").is_err());
+
+        assert_eq!(apply(Transform::UrlEncode, "a b&c=d/é"), "a%20b%26c%3Dd%2F%C3%A9");
+        assert_eq!(apply(Transform::UrlDecode, "a%20b%26c%3dd%2F%C3%A9"), "a b&c=d/é");
+        assert_eq!(apply(Transform::UrlDecode, "100%"), "100%");
+        assert!(Transform::UrlDecode.apply("%FF").is_err());
+    }
+
+    #[test]
+    fn test_json() {
+        let text = r#"{ "b": 1.0, "a": [1, {"s": "x, \"y\" :{"}], "e": {}, "l": [ ] }"#;
+        assert_eq!(apply(Transform::JsonMinify, text), r#"{"b":1.0,"a":[1,{"s":"x, \"y\" :{"}],"e":{},"l":[]}"#);
+        assert_eq!(
+            apply(Transform::JsonPretty, text),
+            "{\n  \"b\": 1.0,\n  \"a\": [\n    1,\n    {\n      \"s\": \"x, \\\"y\\\" :{\"\n    }\n  ],\n  \"e\": {},\n  \"l\": []\n}"
+        );
+        assert!(Transform::JsonPretty.apply("{not json").is_err());
+    }
+
+    #[test]
+    fn test_clean_url() {
+        assert_eq!(apply(Transform::CleanUrl, "https://example.com/a?id=1&utm_source=x&fbclid=y"), "https://example.com/a?id=1");
+    }
+
+    #[test]
+    fn test_parse() {
+        for op in Transform::ALL {
+            assert_eq!(Transform::parse(op.name()), Some(op));
+        }
+        assert_eq!(Transform::parse("nope"), None);
+    }
+}
@@ -1,9 +1,18 @@
 pub mod app;
 pub mod components;
+pub mod decode;
 pub mod events;
 pub mod fuzzy;
 pub mod handlers;
+pub mod keymap;
+pub mod lock;
+pub mod onboarding;
+pub mod syntax;
+pub mod template;
+pub mod terminal;
+pub mod theme;
 pub mod ui;
+pub mod viewer;
 
 pub use app::App;
 pub use events::EventHandler;
@@ -1,5 +1,96 @@
-use crate::db::{ClipboardEntry, Database};
+use crate::config::{ConfigManager, Incognito, JoinSeparator, LIST_PERCENT, Layout};
+use crate::db::{ClipboardEntry, Database, Snippet};
+use crate::kind::Kind;
+use crate::transform::Transform;
+use crate::tui::components::{PreviewCache, format_absolute_date};
+use crate::tui::decode;
 use crate::tui::fuzzy;
+use crate::tui::keymap::Keymap;
+use crate::tui::lock::Lock;
+use crate::tui::template::TemplateForm;
+use crate::tui::theme;
+use crate::tui::viewer::Viewer;
+use crate::urls;
+use ratatui::layout::{Constraint, Direction, Rect};
+use std::borrow::Cow;
+use std::cell::{Ref, RefCell};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// Characters of each entry kept in memory for the list, search and row rendering.
+pub const LIST_PREFIX_CHARS: usize = 4096;
+/// Rows fetched at a time as the list is scrolled; the first page fills the screen at startup.
+pub const PAGE_SIZE: usize = 500;
+/// How often ticks check the database for new captures.
+const AUTO_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+/// Terminals narrower than this stack the preview under the list with `tui.layout = "auto"`.
+const STACK_BELOW_COLUMNS: usize = 80;
+/// Terminals narrower than this show the list alone; the preview is still a `z` away.
+const COLLAPSE_BELOW_COLUMNS: usize = 40;
+/// Percent `<` and `>` move the split by.
+const RESIZE_STEP: i16 = 5;
+
+/// Profiles offered by the `b` popup, as name and database path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DbPicker {
+    pub profiles: Vec<(String, String)>,
+    pub index: usize,
+}
+
+impl DbPicker {
+    pub fn up(&mut self) {
+        self.index = self.index.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        self.index = (self.index + 1).min(self.profiles.len().saturating_sub(1));
+    }
+
+    pub fn selected(&self) -> Option<(&str, &str)> {
+        self.profiles.get(self.index).map(|(name, path)| (name.as_str(), path.as_str()))
+    }
+}
+
+/// Saved snippets shown by the `S` popup.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnippetPicker {
+    pub snippets: Vec<Snippet>,
+    pub index: usize,
+}
+
+impl SnippetPicker {
+    pub fn up(&mut self) {
+        self.index = self.index.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        self.index = (self.index + 1).min(self.snippets.len().saturating_sub(1));
+    }
+
+    pub fn selected(&self) -> Option<&Snippet> {
+        self.snippets.get(self.index)
+    }
+}
+
+/// The `T` menu of rewrites to copy the highlighted entry with.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransformPicker {
+    pub index: usize,
+}
+
+impl TransformPicker {
+    pub fn up(&mut self) {
+        self.index = self.index.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        self.index = (self.index + 1).min(Transform::ALL.len() - 1);
+    }
+
+    pub fn selected(&self) -> Transform {
+        Transform::ALL[self.index]
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum DeleteMode {
@@ -13,6 +104,8 @@ pub enum DeleteMode {
     ConfirmingSingle,
     /// Confirming "all" deletion (tracks confirmation count)
     ConfirmingAll { confirmation_count: u8 },
+    /// Confirming deletion of the marked entries
+    ConfirmingMarked,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -26,17 +119,6 @@ pub enum DeletePeriod {
 }
 
 impl DeletePeriod {
-    pub fn to_days(&self) -> Option<i64> {
-        match self {
-            Self::Hour => Some(1),
-            Self::Day => Some(1),
-            Self::Week => Some(7),
-            Self::Month => Some(30),
-            Self::Year => Some(365),
-            Self::All => None,
-        }
-    }
-
     pub fn display(&self) -> &str {
         match self {
             Self::Hour => "Last Hour",
@@ -58,7 +140,7 @@ pub struct App {
     pub is_filtering: bool,
     pub message: Option<String>,
     pub loading: bool,
-    pub selected_entry: Option<String>,
+    pub selected_entry: Option<ClipboardEntry>,
     pub terminal_width: usize,
     pub terminal_height: usize,
     pub db_path: String,
@@ -70,6 +152,69 @@ pub struct App {
     pub delete_period_index: usize,
     /// Confirm quit dialog active
     pub confirm_quit: bool,
+    /// Filter text and the indices it matched; cleared whenever `entries` changes
+    filter_cache: RefCell<Option<(String, Vec<usize>)>>,
+    /// Wrapped lines of the previewed entry
+    pub preview_cache: PreviewCache,
+    /// Set when state changed since the last draw
+    pub needs_redraw: bool,
+    /// Connection kept open so `data_version` can tell whether the daemon wrote anything
+    watch_db: Option<Database>,
+    data_version: Option<i64>,
+    /// Full content of the highlighted entry when the list only holds its prefix
+    full_entry: Option<ClipboardEntry>,
+    /// Poll the database for new captures on ticks
+    pub auto_refresh: bool,
+    /// Database picker popup, open while `Some`
+    pub db_picker: Option<DbPicker>,
+    /// Lock screen guarding encrypted history; `None` when `secret_encryption` is off
+    pub lock: Option<Lock>,
+    /// Placeholder form for the template being picked
+    pub template_form: Option<TemplateForm>,
+    /// Whether `clippie pause` or `clippie incognito` is suppressing captures, re-read on refresh ticks
+    pub incognito: Incognito,
+    last_refresh: Instant,
+    /// Ids of entries marked with Space or `V` for bulk actions
+    pub marked: HashSet<i64>,
+    /// Position of the last entry marked with Space, where a `V` range starts
+    mark_anchor: Option<usize>,
+    /// Put between marked entries copied as one; `s` cycles it
+    pub join_separator: JoinSeparator,
+    /// Scroll position of the `?` help popup, open while `Some`
+    pub help_scroll: Option<usize>,
+    /// Every row is in `entries`; until then pages are fetched as the selection nears the end
+    pub all_loaded: bool,
+    /// Rows in the database, for the count shown while only some are loaded
+    total_entries: usize,
+    /// Set when a search needs the rows not paged in yet; the event loop streams them in
+    pub load_rest: bool,
+    /// Bumped when the list is reloaded mid-load, so batches from the earlier stream are dropped
+    pub load_generation: u64,
+    /// Name being typed for the highlighted entry's snippet, prompting while `Some`
+    pub snippet_name: Option<String>,
+    /// Snippets popup, open while `Some`
+    pub snippet_picker: Option<SnippetPicker>,
+    pub transform_picker: Option<TransformPicker>,
+    /// Show sensitive entries masked; off when `sensitive_entries` is `show`
+    pub mask_sensitive: bool,
+    /// The one sensitive entry shown in the clear, after `v` on it; `v` again masks it
+    pub revealed: Option<i64>,
+    /// Id of the entry the preview shows decoded, as a JWT or base64.
+    pub decoded: Option<i64>,
+    /// Keys from `tui.keymap`, applied to the list view's bindings
+    pub keymap: Keymap,
+    /// Profile whose database is open, shown in the header
+    pub profile: String,
+    /// Arrangement of the list and preview, from `tui.layout`
+    pub layout: Layout,
+    /// The list fills the body without a preview; toggled with `Tab`
+    pub preview_hidden: bool,
+    /// The preview fills the body; toggled with `z`
+    pub zoomed: bool,
+    /// Percent of the body the list takes, from `tui.list_percent`
+    pub list_percent: u16,
+    /// Full-screen view of an entry, open while `Some`
+    pub viewer: Option<Viewer>,
 }
 
 impl App {
@@ -96,38 +241,93 @@ impl App {
             delete_mode: DeleteMode::None,
             delete_period_index: 0,
             confirm_quit: false,
+            filter_cache: RefCell::new(None),
+            preview_cache: PreviewCache::default(),
+            needs_redraw: true,
+            watch_db: None,
+            data_version: None,
+            full_entry: None,
+            auto_refresh: true,
+            db_picker: None,
+            lock: None,
+            template_form: None,
+            incognito: Incognito::Off,
+            last_refresh: Instant::now(),
+            marked: HashSet::new(),
+            mark_anchor: None,
+            join_separator: JoinSeparator::Newline,
+            help_scroll: None,
+            all_loaded: true,
+            total_entries: 0,
+            load_rest: false,
+            load_generation: 0,
+            snippet_name: None,
+            snippet_picker: None,
+            transform_picker: None,
+            mask_sensitive: true,
+            revealed: None,
+            decoded: None,
+            keymap: Keymap::default(),
+            profile: crate::config::DEFAULT_PROFILE.to_string(),
+            layout: Layout::Auto,
+            preview_hidden: false,
+            zoomed: false,
+            list_percent: 50,
+            viewer: None,
         }
     }
 
+    #[allow(dead_code)]
     pub fn filtered_entries(&self) -> Vec<&ClipboardEntry> {
+        (0..self.filtered_len()).filter_map(|i| self.filtered_entry(i)).collect()
+    }
+
+    /// Indices into `entries` matching the filter, or `None` when there is no filter.
+    ///
+    /// The match is computed once per filter text and reused until `entries` changes, so
+    /// every caller within a frame shares the same pass over the history.
+    fn filter_matches(&self) -> Option<Ref<'_, [usize]>> {
         if self.filter_text.is_empty() {
-            self.entries.iter().collect()
-        } else {
-            let mut filtered: Vec<(usize, &ClipboardEntry)> = self.entries
-                .iter()
-                .enumerate()
-                .filter_map(|(idx, e)| {
-                    let result = fuzzy::fuzzy_match(&e.content, &self.filter_text);
-                    if result.matched { Some((idx, e)) } else { None }
-                })
-                .collect();
-
-            filtered.sort_by(|a, b| {
-                let a_exact = fuzzy::fuzzy_match(&a.1.content, &self.filter_text).is_exact;
-                let b_exact = fuzzy::fuzzy_match(&b.1.content, &self.filter_text).is_exact;
-                match (a_exact, b_exact) {
-                    (true, false) => std::cmp::Ordering::Less,
-                    (false, true) => std::cmp::Ordering::Greater,
-                    _ => std::cmp::Ordering::Equal,
-                }
-            });
+            return None;
+        }
 
-            filtered.into_iter().map(|(_, e)| e).collect()
+        let stale = !matches!(&*self.filter_cache.borrow(), Some((query, _)) if *query == self.filter_text);
+        if stale {
+            let indices = fuzzy::filter_indices(&self.entries, &self.filter_text, self.search_candidates().as_ref());
+            *self.filter_cache.borrow_mut() = Some((self.filter_text.clone(), indices));
         }
+        Some(Ref::map(self.filter_cache.borrow(), |cache| {
+            cache.as_ref().map_or(&[][..], |(_, indices)| indices.as_slice())
+        }))
+    }
+
+    /// Entries whose full content contains the filter words, looked up in the search index so
+    /// fuzzy ranking only visits those. With no hits the filter may be a typo or a subsequence
+    /// abbreviation, so every entry is fuzzy-matched instead.
+    fn search_candidates(&self) -> Option<HashSet<i64>> {
+        let query = fuzzy::parse_query(&self.filter_text);
+        let ids = self.watch_db.as_ref()?.search(&query.text, self.entries.len()).ok()??;
+        (!ids.is_empty()).then(|| ids.into_iter().collect())
+    }
+
+    pub fn filtered_len(&self) -> usize {
+        self.filter_matches().map_or(self.entries.len(), |matches| matches.len())
+    }
+
+    fn filtered_entry(&self, index: usize) -> Option<&ClipboardEntry> {
+        let entry_index = match self.filter_matches() {
+            Some(matches) => *matches.get(index)?,
+            None => index,
+        };
+        self.entries.get(entry_index)
+    }
+
+    fn invalidate_filter(&mut self) {
+        self.filter_cache.get_mut().take();
     }
 
     pub fn current_entry(&self) -> Option<&ClipboardEntry> {
-        self.filtered_entries().get(self.selected_index).copied()
+        self.filtered_entry(self.selected_index)
     }
 
     pub fn select_up(&mut self) {
@@ -141,8 +341,7 @@ impl App {
     }
 
     pub fn select_down(&mut self) {
-        let filtered = self.filtered_entries();
-        if self.selected_index < filtered.len().saturating_sub(1) {
+        if self.selected_index < self.filtered_len().saturating_sub(1) {
             self.selected_index += 1;
             self.preview_scroll = 0;
             let usable_height = self.get_list_height();
@@ -150,12 +349,48 @@ impl App {
                 self.scroll_offset = self.selected_index - usable_height + 1;
             }
         }
+        // Fetch the next page a screen ahead so scrolling never stops at its end.
+        if self.filter_text.is_empty() && self.selected_index + self.get_list_height() >= self.entries.len()
+            && let Err(e) = self.load_page()
+        {
+            self.show_message(format!("Loading failed: {}", e));
+        }
+    }
+
+    /// Replace the list with the first page of the database.
+    pub fn load_first_page(&mut self) -> crate::error::Result<()> {
+        self.entries.clear();
+        self.all_loaded = false;
+        // Take the version first so captures landing during the load trigger a refresh.
+        self.db_changed();
+        self.load_page()
+    }
+
+    /// Append the next page unless everything is loaded or the rest is already streaming in.
+    pub fn load_page(&mut self) -> crate::error::Result<()> {
+        if self.all_loaded || self.loading {
+            return Ok(());
+        }
+        let db = Database::open(&self.db_path)?;
+        let page = db.get_entries_page(LIST_PREFIX_CHARS, self.entries.len(), PAGE_SIZE)?;
+        self.total_entries = db.count_entries()? as usize;
+        self.all_loaded = page.len() < PAGE_SIZE;
+        // A capture since the last page shifts rows down, repeating the previous page's last one.
+        let loaded: HashSet<i64> = self.entries.iter().map(|e| e.id).collect();
+        self.entries.extend(page.into_iter().filter(|e| !loaded.contains(&e.id)));
+        self.invalidate_filter();
+        Ok(())
     }
 
     pub fn start_filtering(&mut self) {
         self.is_filtering = true;
         self.filter_text.clear();
         self.reset_selection();
+        // Search covers the whole history, so stream in whatever hasn't been paged in.
+        if !self.all_loaded && !self.loading {
+            self.load_rest = true;
+            self.start_loading();
+        }
     }
 
     pub fn stop_filtering(&mut self) {
@@ -184,35 +419,94 @@ impl App {
         self.preview_scroll = 0;
     }
 
-    pub fn select_entry(&mut self) -> Option<String> {
-        if let Some(entry) = self.current_entry() {
-            let content = entry.content.clone();
-            self.selected_entry = Some(content.clone());
-            return Some(content);
+    /// Choose the highlighted entry. Returns false when a template form opened instead.
+    pub fn select_entry(&mut self) -> bool {
+        self.load_full_entry();
+        let Some(entry) = self.preview_entry().cloned() else {
+            return true;
+        };
+        if entry.template
+            && !entry.encrypted
+            && let Some(form) = TemplateForm::new(entry.content.clone())
+        {
+            self.template_form = Some(form);
+            return false;
         }
-        None
+        self.selected_entry = Some(entry);
+        true
     }
 
-    pub fn get_list_height(&self) -> usize {
-        self.terminal_height.saturating_sub(4)
+    /// Choose the highlighted template with its placeholders filled in.
+    pub fn finish_template(&mut self) {
+        let Some(form) = self.template_form.take() else {
+            return;
+        };
+        self.selected_entry = self.preview_entry().map(|entry| ClipboardEntry {
+            content: form.rendered(),
+            template: false,
+            language: None,
+            truncated: false,
+            ..entry.clone()
+        });
     }
 
-    pub fn get_visible_entries(&self) -> Vec<&ClipboardEntry> {
-        let filtered = self.filtered_entries();
-        let list_height = self.get_list_height();
-        let end = (self.scroll_offset + list_height).min(filtered.len());
+    /// Save or unsave the highlighted entry as a template.
+    pub fn toggle_template(&mut self) -> crate::error::Result<()> {
+        let Some(entry) = self.current_entry() else {
+            return Ok(());
+        };
+        if entry.encrypted {
+            self.show_message("Encrypted entries can't be templates");
+            return Ok(());
+        }
+        let (id, template) = (entry.id, !entry.template);
+        Database::open(&self.db_path)?.set_template(id, template)?;
 
-        if self.scroll_offset >= filtered.len() {
-            vec![]
-        } else {
-            filtered[self.scroll_offset..end].to_vec()
+        for entry in self.entries.iter_mut().chain(self.full_entry.as_mut()) {
+            if entry.id == id {
+                entry.template = template;
+            }
         }
+        self.show_message(if template { "Saved as template ✎" } else { "No longer a template" });
+        Ok(())
+    }
+
+    /// Fetch the complete content of the highlighted entry if the list truncated it.
+    pub fn load_full_entry(&mut self) {
+        let Some((id, truncated)) = self.current_entry().map(|e| (e.id, e.truncated)) else {
+            return;
+        };
+        if !truncated || self.full_entry.as_ref().is_some_and(|e| e.id == id) {
+            return;
+        }
+        self.full_entry = Database::open(&self.db_path).ok().and_then(|db| db.get_entry(id).ok().flatten());
+    }
+
+    /// The highlighted entry with full content, once `load_full_entry` has run.
+    pub fn preview_entry(&self) -> Option<&ClipboardEntry> {
+        let current = self.current_entry()?;
+        match &self.full_entry {
+            Some(full) if current.truncated && full.id == current.id => Some(full),
+            _ => Some(current),
+        }
+    }
+
+    pub fn get_list_height(&self) -> usize {
+        // Zooming hides the list but keeps its scroll position for when it comes back.
+        (self.arrange(self.body_inner(), false)[0].height as usize).max(1)
+    }
+
+    pub fn get_visible_entries(&self) -> Vec<&ClipboardEntry> {
+        let end = (self.scroll_offset + self.get_list_height()).min(self.filtered_len());
+        (self.scroll_offset..end).filter_map(|i| self.filtered_entry(i)).collect()
     }
 
     pub fn get_entry_count_info(&self) -> String {
-        let count = self.filtered_entries().len();
+        let count = self.filtered_len();
         let total = self.entries.len();
-        if self.filter_text.is_empty() {
+        if !self.all_loaded && !self.loading {
+            format!("{} of {} entries", total, self.total_entries.max(total))
+        } else if self.filter_text.is_empty() {
             format!("{} entries", count)
         } else {
             format!("{} entries, {} matches", total, count)
@@ -228,13 +522,14 @@ impl App {
         self.terminal_height = height;
     }
 
-    pub fn get_db_path_short(&self) -> String {
-        self.db_path.clone()
-    }
-
+    /// Reload as many rows as are loaded, or the whole history once it's all in. A stream still
+    /// running is dropped and restarted past the reloaded rows.
     pub fn refresh(&mut self) -> crate::error::Result<()> {
         let db = Database::open(&self.db_path)?;
-        let new_entries = db.get_all_entries()?;
+        let limit = if self.all_loaded { usize::MAX } else { self.entries.len().max(PAGE_SIZE) };
+        let new_entries = db.get_entries_page(LIST_PREFIX_CHARS, 0, limit)?;
+        self.total_entries = db.count_entries()? as usize;
+        self.all_loaded = new_entries.len() < limit;
 
         let changed = new_entries.len() != self.entries.len()
             || new_entries.iter().zip(&self.entries).any(|(a, b)| {
@@ -243,28 +538,149 @@ impl App {
 
         if changed {
             self.entries = new_entries;
+            self.invalidate_filter();
             self.selected_index = 0;
             self.scroll_offset = 0;
         }
+        if self.loading {
+            self.load_generation += 1;
+            self.load_rest = !self.all_loaded;
+            self.loading = self.load_rest;
+        }
+
+        Ok(())
+    }
+
+    /// Whether the database was written since the last check.
+    fn db_changed(&mut self) -> bool {
+        if self.watch_db.is_none() {
+            self.watch_db = Database::open(&self.db_path).ok();
+        }
+        let Some(version) = self.watch_db.as_ref().and_then(|db| db.data_version().ok()) else {
+            return true;
+        };
+        // The first probe has no baseline, so it counts as a change.
+        let changed = self.data_version != Some(version);
+        self.data_version = Some(version);
+        changed
+    }
+
+    /// Merge in entries copied since the newest one listed, when the daemon announces a capture.
+    /// Keeps the highlighted entry selected. Deletions are still picked up by the periodic refresh.
+    pub fn load_new_entries(&mut self) -> crate::error::Result<()> {
+        // Still streaming; the refresh after loading picks the capture up.
+        if self.loading {
+            return Ok(());
+        }
+        // Take the version first so a write landing after the query still triggers a refresh.
+        self.db_changed();
+        let Some(db) = &self.watch_db else {
+            return Ok(());
+        };
+        let since = self.entries.first().map_or(0, |e| e.last_copied.timestamp());
+        let fresh = db.get_entry_summaries_since(LIST_PREFIX_CHARS, since)?;
+        if fresh.is_empty() {
+            return Ok(());
+        }
+
+        let selected = self.current_entry().map(|e| e.id);
+        let ids: HashSet<i64> = fresh.iter().map(|e| e.id).collect();
+        self.entries.retain(|e| !ids.contains(&e.id));
+        self.entries.splice(0..0, fresh);
+        self.invalidate_filter();
+        if let Some(id) = selected
+            && let Some(index) = (0..self.filtered_len()).find(|&i| self.filtered_entry(i).is_some_and(|e| e.id == id))
+        {
+            self.selected_index = index;
+            let usable_height = self.get_list_height();
+            if index < self.scroll_offset || index >= self.scroll_offset + usable_height {
+                self.scroll_offset = index.saturating_sub(usable_height / 2);
+            }
+        }
+        self.needs_redraw = true;
+        Ok(())
+    }
+
+    /// Enter the loading state; writes that land mid-load trigger a refresh afterwards.
+    pub fn start_loading(&mut self) {
+        self.loading = true;
+        self.db_changed();
+    }
+
+    /// Add rows streamed in by the background loader.
+    pub fn append_entries(&mut self, batch: Vec<ClipboardEntry>) {
+        // A capture merged in while streaming may be a row the stream still reads further down.
+        let loaded: HashSet<i64> = self.entries.iter().map(|e| e.id).collect();
+        self.entries.extend(batch.into_iter().filter(|e| !loaded.contains(&e.id)));
+        self.invalidate_filter();
+        self.needs_redraw = true;
+    }
+
+    /// Braille spinner frame while the history is still loading.
+    pub fn spinner(&self) -> Option<char> {
+        const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+        self.loading.then(|| FRAMES[self.tick_count % FRAMES.len()])
+    }
 
+    /// Whether anything consumes ticks right now; when not, the event loop sleeps until input.
+    pub fn needs_ticks(&self) -> bool {
+        self.loading || self.auto_refresh || self.lock.as_ref().is_some_and(Lock::watches_idle)
+    }
+
+    pub fn refresh_incognito(&mut self) {
+        self.incognito = ConfigManager::new().map(|config| config.incognito()).unwrap_or(Incognito::Off);
+    }
+
+    /// Pause capture until resumed, or resume it if paused or incognito.
+    pub fn toggle_pause(&mut self) -> crate::error::Result<()> {
+        let config = ConfigManager::new()?;
+        if config.is_paused() {
+            config.set_paused(false)?;
+            config.clear_incognito()?;
+            self.show_message("Capture resumed");
+        } else {
+            config.set_paused(true)?;
+            self.show_message("Capture paused, press i to resume");
+        }
+        self.refresh_incognito();
         Ok(())
     }
 
+    pub fn is_locked(&self) -> bool {
+        self.lock.as_ref().is_some_and(|lock| lock.locked)
+    }
+
     pub fn on_tick(&mut self) {
         self.tick_count += 1;
-        if self.tick_count >= 50 {
-            self.tick_count = 0;
-            let _ = self.refresh();
+        if let Some(lock) = &mut self.lock
+            && lock.check_idle()
+        {
+            self.db_picker = None;
+            self.needs_redraw = true;
+        }
+        if self.loading {
+            self.needs_redraw = true;
+            return;
+        }
+        if self.auto_refresh && self.last_refresh.elapsed() >= AUTO_REFRESH_INTERVAL {
+            self.last_refresh = Instant::now();
+            self.refresh_incognito();
+            if self.db_changed() {
+                let _ = self.refresh();
+            }
+            // Redraw even if nothing changed so relative dates stay current.
+            self.needs_redraw = true;
         }
     }
 
     pub fn delete_current_entry(&mut self) -> crate::error::Result<bool> {
         if let Some(entry) = self.current_entry() {
-            let content = entry.content.clone();
+            let id = entry.id;
             let db = Database::open(&self.db_path)?;
-            if db.delete_entry_by_content(&content)? {
-                self.entries.retain(|e| e.content != content);
-                let filtered_len = self.filtered_entries().len();
+            if db.delete_entry_by_id(id)? {
+                self.entries.retain(|e| e.id != id);
+                self.invalidate_filter();
+                let filtered_len = self.filtered_len();
                 if self.selected_index >= filtered_len && filtered_len > 0 {
                     self.selected_index = filtered_len - 1;
                 }
@@ -274,6 +690,330 @@ impl App {
         Ok(false)
     }
 
+    /// Mark or unmark the highlighted entry and move to the next one.
+    pub fn toggle_mark(&mut self) {
+        let Some(id) = self.current_entry().map(|e| e.id) else {
+            return;
+        };
+        if !self.marked.remove(&id) {
+            self.marked.insert(id);
+        }
+        self.mark_anchor = Some(self.selected_index);
+        self.select_down();
+    }
+
+    /// Mark every entry between the last one marked with Space and the highlighted one.
+    pub fn mark_range(&mut self) {
+        let anchor = self.mark_anchor.unwrap_or(self.selected_index).min(self.filtered_len().saturating_sub(1));
+        let (start, end) = (anchor.min(self.selected_index), anchor.max(self.selected_index));
+        let ids: Vec<i64> = (start..=end).filter_map(|i| self.filtered_entry(i)).map(|e| e.id).collect();
+        self.marked.extend(ids);
+        self.mark_anchor = Some(self.selected_index);
+    }
+
+    pub fn clear_marks(&mut self) {
+        self.marked.clear();
+        self.mark_anchor = None;
+    }
+
+    /// Marked entries with full content, in list order.
+    pub fn marked_entries(&self) -> crate::error::Result<Vec<ClipboardEntry>> {
+        let db = Database::open(&self.db_path)?;
+        let mut entries = Vec::with_capacity(self.marked.len());
+        for entry in self.entries.iter().filter(|e| self.marked.contains(&e.id)) {
+            match entry.truncated {
+                true => entries.extend(db.get_entry(entry.id)?),
+                false => entries.push(entry.clone()),
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Delete every marked entry. Returns how many were removed.
+    pub fn delete_marked(&mut self) -> crate::error::Result<usize> {
+        let db = Database::open(&self.db_path)?;
+        let mut deleted = 0;
+        for id in &self.marked {
+            if db.delete_entry_by_id(*id)? {
+                deleted += 1;
+            }
+        }
+        let marked = std::mem::take(&mut self.marked);
+        self.entries.retain(|e| !marked.contains(&e.id));
+        self.mark_anchor = None;
+        self.invalidate_filter();
+        let filtered_len = self.filtered_len();
+        if self.selected_index >= filtered_len {
+            self.selected_index = filtered_len.saturating_sub(1);
+            self.scroll_offset = self.scroll_offset.min(self.selected_index);
+        }
+        Ok(deleted)
+    }
+
+    /// Save the marked entries as templates, or unsave them when all of them already are.
+    pub fn toggle_marked_templates(&mut self) -> crate::error::Result<()> {
+        let marked: Vec<&ClipboardEntry> =
+            self.entries.iter().filter(|e| self.marked.contains(&e.id) && !e.encrypted).collect();
+        if marked.is_empty() {
+            self.show_message("Encrypted entries can't be templates");
+            return Ok(());
+        }
+        let template = !marked.iter().all(|e| e.template);
+        let ids: HashSet<i64> = marked.iter().map(|e| e.id).collect();
+        let db = Database::open(&self.db_path)?;
+        for id in &ids {
+            db.set_template(*id, template)?;
+        }
+
+        for entry in self.entries.iter_mut().chain(self.full_entry.as_mut()) {
+            if ids.contains(&entry.id) {
+                entry.template = template;
+            }
+        }
+        self.show_message(match template {
+            true => format!("Saved {} templates ✎", ids.len()),
+            false => format!("{} entries are no longer templates", ids.len()),
+        });
+        Ok(())
+    }
+
+    /// Add the highlighted entry to the back of the paste queue.
+    pub fn queue_current(&mut self) -> crate::error::Result<()> {
+        let Some(id) = self.current_entry().map(|e| e.id) else {
+            return Ok(());
+        };
+        let queued = Database::open(&self.db_path)?.enqueue(&[id])?;
+        self.show_message(format!("Queued for clippie pop ({} in queue)", queued));
+        Ok(())
+    }
+
+    /// Add the marked entries to the paste queue in list order and unmark them.
+    pub fn queue_marked(&mut self) -> crate::error::Result<()> {
+        let ids: Vec<i64> = self.entries.iter().filter(|e| self.marked.contains(&e.id)).map(|e| e.id).collect();
+        let queued = Database::open(&self.db_path)?.enqueue(&ids)?;
+        self.clear_marks();
+        self.show_message(format!("Queued {} entries for clippie pop ({} in queue)", ids.len(), queued));
+        Ok(())
+    }
+
+    /// `entry` as the list, preview and popups show it: a sensitive entry has its secrets masked
+    /// unless revealed. The masked copy is no longer flagged sensitive, since nothing in it is.
+    pub fn shown<'a>(&self, entry: &'a ClipboardEntry) -> Cow<'a, ClipboardEntry> {
+        if !self.mask_sensitive || !entry.sensitive || self.revealed == Some(entry.id) {
+            return Cow::Borrowed(entry);
+        }
+        Cow::Owned(ClipboardEntry { content: entry.masked_content().into_owned(), sensitive: false, ..entry.clone() })
+    }
+
+    /// Show or mask again the secrets in the highlighted entry.
+    pub fn toggle_reveal(&mut self) {
+        let Some(entry) = self.current_entry() else {
+            return;
+        };
+        if !entry.sensitive || !self.mask_sensitive {
+            self.show_message("Nothing to reveal");
+            return;
+        }
+        let id = entry.id;
+        self.revealed = if self.revealed == Some(id) { None } else { Some(id) };
+    }
+
+    /// Show the highlighted JWT or base64 entry decoded in the preview, or as copied again.
+    pub fn toggle_decoded(&mut self) {
+        self.load_full_entry();
+        let Some(entry) = self.preview_entry() else {
+            return;
+        };
+        let id = entry.id;
+        if self.decoded == Some(id) {
+            self.decoded = None;
+            return;
+        }
+        if entry.encrypted || decode::decoded_view(&entry.content).is_none() {
+            self.show_message("Not a JWT or base64");
+            return;
+        }
+        // The decoded view shows whatever the token holds, so it reveals a masked entry too.
+        if entry.sensitive && self.mask_sensitive {
+            self.revealed = Some(id);
+        }
+        self.decoded = Some(id);
+        self.preview_scroll = 0;
+    }
+
+    /// Draw with the next built-in theme, for the rest of this session.
+    pub fn cycle_theme(&mut self) {
+        let theme = theme::current().next();
+        theme::set_theme(theme);
+        self.show_message(format!("Theme: {}", theme.name()));
+    }
+
+    /// Show the highlighted entry full screen, as the preview shows it.
+    pub fn open_viewer(&mut self) {
+        self.load_full_entry();
+        let Some(entry) = self.preview_entry().map(|e| self.shown(e).into_owned()) else {
+            return;
+        };
+        let title = format!("{} · {} lines", format_absolute_date(&entry.created_at), entry.display_content().lines().count());
+        self.viewer = Some(Viewer::new(title, entry.display_content()));
+    }
+
+    /// Open the highlighted URL entry in the default browser.
+    pub fn open_url(&mut self) {
+        self.load_full_entry();
+        let Some(entry) = self.preview_entry() else {
+            return;
+        };
+        if entry.kind != Kind::Url {
+            self.show_message("Not a URL");
+            return;
+        }
+        let result = urls::open_in_browser(&entry.content);
+        match result {
+            Ok(()) => self.show_message("Opened in the browser"),
+            Err(e) => self.show_message(format!("Failed to open the browser: {}", e)),
+        }
+    }
+
+    /// Prompt for a name to save the highlighted entry under as a snippet.
+    pub fn start_snippet_name(&mut self) {
+        if self.current_entry().is_some() {
+            self.snippet_name = Some(String::new());
+        }
+    }
+
+    /// Save the highlighted entry under the name typed at the prompt.
+    pub fn save_snippet(&mut self) -> crate::error::Result<()> {
+        let Some(name) = self.snippet_name.take() else {
+            return Ok(());
+        };
+        let name = crate::commands::snippet::valid_name(&name)?;
+        self.load_full_entry();
+        let Some(entry) = self.preview_entry() else {
+            return Ok(());
+        };
+        let replaced = Database::open(&self.db_path)?.save_snippet(name, entry)?;
+        self.show_message(format!("{} snippet \"{}\"", if replaced { "Updated" } else { "Saved" }, name));
+        Ok(())
+    }
+
+    pub fn open_snippets(&mut self) -> crate::error::Result<()> {
+        let snippets = Database::open(&self.db_path)?.get_snippets()?;
+        if snippets.is_empty() {
+            self.show_message("No snippets yet; press n to save the entry as one");
+            return Ok(());
+        }
+        self.snippet_picker = Some(SnippetPicker { snippets, index: 0 });
+        Ok(())
+    }
+
+    /// Choose the highlighted snippet. Returns false when there is none.
+    pub fn select_snippet(&mut self) -> bool {
+        let Some(snippet) = self.snippet_picker.take().and_then(|picker| picker.selected().cloned()) else {
+            return false;
+        };
+        self.selected_entry = Some(ClipboardEntry {
+            id: 0,
+            content: snippet.content,
+            created_at: snippet.created_at,
+            last_copied: snippet.created_at,
+            encrypted: snippet.encrypted,
+            template: false,
+            language: None,
+            source_app: None,
+            sensitive: false,
+            kind: Kind::Text,
+            title: None,
+            truncated: false,
+        });
+        true
+    }
+
+    /// Delete the highlighted snippet, closing the popup once none are left.
+    pub fn delete_snippet(&mut self) -> crate::error::Result<()> {
+        let Some(picker) = self.snippet_picker.as_mut() else {
+            return Ok(());
+        };
+        let Some(name) = picker.selected().map(|s| s.name.clone()) else {
+            return Ok(());
+        };
+        Database::open(&self.db_path)?.delete_snippet(&name)?;
+        picker.snippets.remove(picker.index);
+        picker.index = picker.index.min(picker.snippets.len().saturating_sub(1));
+        if picker.snippets.is_empty() {
+            self.snippet_picker = None;
+        }
+        self.show_message(format!("Removed snippet \"{}\"", name));
+        Ok(())
+    }
+
+    pub fn open_transforms(&mut self) {
+        self.load_full_entry();
+        match self.preview_entry() {
+            None => {}
+            Some(entry) if entry.encrypted => self.show_message("Encrypted entries can't be transformed"),
+            Some(_) => self.transform_picker = Some(TransformPicker::default()),
+        }
+    }
+
+    /// Choose the highlighted entry rewritten by the highlighted transform. Returns false, with
+    /// the menu still open, when the transform doesn't apply to it.
+    pub fn select_transform(&mut self) -> bool {
+        let Some(op) = self.transform_picker.as_ref().map(TransformPicker::selected) else {
+            return false;
+        };
+        let Some(entry) = self.preview_entry() else {
+            return false;
+        };
+        match op.apply(&entry.content) {
+            Ok(content) => {
+                self.selected_entry = Some(ClipboardEntry {
+                    content,
+                    template: false,
+                    language: None,
+                    truncated: false,
+                    ..entry.clone()
+                });
+                self.transform_picker = None;
+                true
+            }
+            Err(e) => {
+                self.show_message(format!("{}: {}", op.label(), e));
+                false
+            }
+        }
+    }
+
+    /// Write the marked entries to a JSON export in `dir`, named after the current time.
+    pub fn export_marked(&self, dir: &std::path::Path) -> crate::error::Result<(usize, std::path::PathBuf)> {
+        let entries = self.marked_entries()?;
+        let counts = Database::open(&self.db_path)?.get_copy_counts()?;
+        let path = dir.join(format!("clippie-selection-{}.json", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+        std::fs::write(&path, serde_json::to_string_pretty(&crate::commands::export::json_dump(&entries, &counts))?)?;
+        Ok((entries.len(), path))
+    }
+
+    pub fn cycle_join_separator(&mut self) {
+        self.join_separator = self.join_separator.next();
+        self.show_message(format!("Join with: {}", self.join_separator.name()));
+    }
+
+    /// Choose the marked entries joined by `join_separator`, in list order.
+    pub fn select_marked(&mut self) -> crate::error::Result<bool> {
+        let entries = self.marked_entries()?;
+        if entries.iter().any(|e| e.encrypted) {
+            self.show_message("Encrypted entries can't be joined; unmark them first");
+            return Ok(false);
+        }
+        let Some(first) = entries.first() else {
+            return Ok(false);
+        };
+        let content = entries.iter().map(|e| e.content.as_str()).collect::<Vec<_>>().join(self.join_separator.as_str());
+        self.selected_entry =
+            Some(ClipboardEntry { content, template: false, language: None, truncated: false, ..first.clone() });
+        Ok(true)
+    }
+
     pub fn scroll_preview_up(&mut self) {
         self.preview_scroll = self.preview_scroll.saturating_sub(1);
     }
@@ -289,7 +1029,72 @@ impl App {
 
     #[allow(dead_code)]
     pub fn get_preview_height(&self) -> usize {
-        self.terminal_height.saturating_sub(4)
+        self.body_areas(self.body_inner())[2].height as usize
+    }
+
+    /// Apply `tui.layout`; `list-only` starts with the preview hidden.
+    pub fn set_layout(&mut self, layout: Layout) {
+        self.layout = layout;
+        self.preview_hidden = layout == Layout::ListOnly;
+    }
+
+    /// Show or hide the preview beside the list.
+    pub fn toggle_preview(&mut self) {
+        if self.terminal_width < COLLAPSE_BELOW_COLUMNS {
+            self.show_message("Too narrow for the preview; z zooms it");
+            return;
+        }
+        self.zoomed = false;
+        self.preview_hidden = !self.preview_hidden;
+    }
+
+    /// Grow the list by `steps` of `RESIZE_STEP` percent, or shrink it when negative, staying
+    /// within `LIST_PERCENT`. False when it was already as large or small as it gets.
+    pub fn resize_list(&mut self, steps: i16) -> bool {
+        let (min, max) = (*LIST_PERCENT.start() as i16, *LIST_PERCENT.end() as i16);
+        let percent = (self.list_percent as i16 + steps * RESIZE_STEP).clamp(min, max) as u16;
+        let changed = percent != self.list_percent;
+        self.list_percent = percent;
+        changed
+    }
+
+    /// Give the whole body to the preview, or bring the list back.
+    pub fn toggle_zoom(&mut self) {
+        self.zoomed = !self.zoomed;
+    }
+
+    /// The list, divider and preview areas within `inner`, the body inside its border. Hidden
+    /// ones are empty.
+    pub fn body_areas(&self, inner: Rect) -> [Rect; 3] {
+        self.arrange(inner, self.zoomed)
+    }
+
+    fn arrange(&self, inner: Rect, zoomed: bool) -> [Rect; 3] {
+        let empty = Rect { width: 0, height: 0, ..inner };
+        if zoomed {
+            return [empty, empty, inner];
+        }
+        if self.preview_hidden || self.terminal_width < COLLAPSE_BELOW_COLUMNS {
+            return [inner, empty, empty];
+        }
+        let stacked = match self.layout {
+            Layout::Split => false,
+            Layout::Stacked => true,
+            Layout::Auto | Layout::ListOnly => self.terminal_width < STACK_BELOW_COLUMNS,
+        };
+        let direction = if stacked { Direction::Vertical } else { Direction::Horizontal };
+        let chunks = ratatui::layout::Layout::default()
+            .direction(direction)
+            .constraints([Constraint::Percentage(self.list_percent), Constraint::Length(1), Constraint::Min(0)])
+            .split(inner);
+        [chunks[0], chunks[1], chunks[2]]
+    }
+
+    /// The body inside its border, with room left for the search and status bars.
+    fn body_inner(&self) -> Rect {
+        let width = self.terminal_width.saturating_sub(2).min(u16::MAX as usize) as u16;
+        let height = self.terminal_height.saturating_sub(4).min(u16::MAX as usize) as u16;
+        Rect::new(1, 1, width, height)
     }
 
     pub fn start_bulk_delete(&mut self) {
@@ -342,11 +1147,38 @@ impl App {
     pub fn is_in_delete_mode(&self) -> bool {
         self.delete_mode != DeleteMode::None
     }
+
+    /// Profile and database file name for the header.
+    pub fn db_label(&self) -> String {
+        let file = std::path::Path::new(&self.db_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.db_path.clone());
+        format!("{} · {}", self.profile, file)
+    }
+
+    pub fn open_db_picker(&mut self, profiles: Vec<(String, String)>) {
+        let index = profiles.iter().position(|(_, path)| *path == self.db_path).unwrap_or(0);
+        self.db_picker = Some(DbPicker { profiles, index });
+    }
+
+    /// Reopen the list on another database. Leaves the config alone.
+    pub fn switch_database(&mut self, db_path: &str) -> crate::error::Result<()> {
+        Database::open(db_path)?;
+        self.db_path = db_path.to_string();
+        self.watch_db = None;
+        self.data_version = None;
+        self.full_entry = None;
+        self.reset_selection();
+        self.clear_marks();
+        self.load_first_page()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::db::NewEntry;
     use chrono::Utc;
 
     fn create_test_entry(content: &str) -> ClipboardEntry {
@@ -355,6 +1187,14 @@ mod tests {
             content: content.to_string(),
             created_at: Utc::now(),
             last_copied: Utc::now(),
+            encrypted: false,
+            template: false,
+            language: None,
+            source_app: None,
+            sensitive: false,
+            kind: Kind::Text,
+            title: None,
+            truncated: false,
         }
     }
 
@@ -448,6 +1288,382 @@ mod tests {
         assert_eq!(app.get_list_height(), 20);
     }
 
+    #[test]
+    fn test_layouts() {
+        let mut app = App::new(vec![], "/test/db".to_string(), 100, 24);
+        let [list, divider, preview] = app.body_areas(Rect::new(1, 1, 98, 20));
+        assert_eq!((list.width, divider.width, preview.width), (49, 1, 48));
+        assert_eq!(preview.height, 20);
+
+        // Too narrow for two columns, or asked to stack: the preview goes under the list.
+        app.update_terminal_size(60, 24);
+        assert_eq!(app.get_list_height(), 10);
+        assert_eq!(app.get_preview_height(), 9);
+        app.set_layout(Layout::Split);
+        assert_eq!(app.get_list_height(), 20);
+        app.set_layout(Layout::Stacked);
+        app.update_terminal_size(100, 24);
+        assert_eq!(app.get_list_height(), 10);
+
+        app.set_layout(Layout::ListOnly);
+        assert_eq!((app.get_list_height(), app.get_preview_height()), (20, 0));
+        app.toggle_preview();
+        assert_eq!(app.get_preview_height(), 20);
+
+        // Zooming hides the list but doesn't change how far it scrolls.
+        app.update_terminal_size(60, 24);
+        app.toggle_zoom();
+        assert_eq!(app.body_areas(Rect::new(1, 1, 58, 20))[2], Rect::new(1, 1, 58, 20));
+        assert_eq!(app.get_list_height(), 10);
+        app.toggle_preview();
+        assert!(!app.zoomed);
+        assert_eq!(app.get_preview_height(), 0);
+    }
+
+    #[test]
+    fn test_resize_list() {
+        let mut app = App::new(vec![], "/test/db".to_string(), 100, 24);
+        assert!(app.resize_list(-1));
+        let [list, _, preview] = app.body_areas(Rect::new(1, 1, 98, 20));
+        assert_eq!((list.width, preview.width), (44, 53));
+        assert!(app.resize_list(20));
+        assert_eq!(app.list_percent, 80);
+        assert!(!app.resize_list(1));
+
+        // Too narrow for two panes: the list alone, until zoomed.
+        app.update_terminal_size(30, 24);
+        assert_eq!((app.get_list_height(), app.get_preview_height()), (20, 0));
+        app.toggle_preview();
+        assert!(!app.preview_hidden);
+        app.toggle_zoom();
+        assert_eq!(app.get_preview_height(), 20);
+    }
+
+    #[test]
+    fn test_filter_cache_invalidated_on_delete() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let db = Database::open(tmp.path()).unwrap();
+        for content in ["foo one", "foo two"] {
+            db.insert_entry(content, &crate::clipboard::hash_content(content)).unwrap();
+        }
+
+        let mut entries = db.get_all_entries().unwrap();
+        entries.sort_by_key(|e| e.id);
+        let mut app = App::new(entries, tmp.path().to_string_lossy().to_string(), 80, 24);
+        app.filter_text = "foo".to_string();
+        assert_eq!(app.filtered_entries().len(), 2);
+
+        assert!(app.delete_current_entry().unwrap());
+        let remaining: Vec<&str> = app.filtered_entries().iter().map(|e| e.content.as_str()).collect();
+        assert_eq!(remaining, vec!["foo two"]);
+    }
+
+    #[test]
+    fn test_select_entry_loads_full_content() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let db = Database::open(tmp.path()).unwrap();
+        let long = "x".repeat(LIST_PREFIX_CHARS * 2);
+        db.insert_entry(&long, "h").unwrap();
+
+        let entries = db.get_entries_page(LIST_PREFIX_CHARS, 0, usize::MAX).unwrap();
+        assert_eq!(entries[0].content.len(), LIST_PREFIX_CHARS);
+        let mut app = App::new(entries, tmp.path().to_string_lossy().to_string(), 80, 24);
+
+        app.select_entry();
+        assert_eq!(app.selected_entry.unwrap().content, long);
+    }
+
+    #[test]
+    fn test_visible_entries_follow_filter() {
+        let entries = vec![create_test_entry("apple"), create_test_entry("banana"), create_test_entry("apricot")];
+        let mut app = App::new(entries, "/test/db".to_string(), 80, 24);
+        assert_eq!(app.filtered_len(), 3);
+
+        app.filter_text = "ap".to_string();
+        assert_eq!(app.filtered_len(), 2);
+        app.scroll_offset = 1;
+        let visible: Vec<&str> = app.get_visible_entries().iter().map(|e| e.content.as_str()).collect();
+        assert_eq!(visible, ["apricot"]);
+        assert_eq!(app.current_entry().unwrap().content, "apple");
+    }
+
+    #[test]
+    fn test_filter_searches_full_content() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let db = Database::open(tmp.path()).unwrap();
+        db.insert_entry(&format!("{} needle", "x".repeat(LIST_PREFIX_CHARS)), "h1").unwrap();
+        db.insert_entry("nothing to see", "h2").unwrap();
+        db.insert_entry("n-e-e-d-l-e", "h3").unwrap();
+
+        let path = tmp.path().to_string_lossy().to_string();
+        let mut app = App::new(vec![], path.clone(), 80, 24);
+        app.switch_database(&path).unwrap();
+
+        app.filter_text = "needle".to_string();
+        assert_eq!(app.filtered_len(), 1);
+        assert!(app.current_entry().unwrap().truncated);
+
+        // Without an indexed hit, subsequence matches still come through.
+        app.filter_text = "ndl".to_string();
+        assert_eq!(app.filtered_len(), 1);
+        assert_eq!(app.current_entry().unwrap().content, "n-e-e-d-l-e");
+    }
+
+    #[test]
+    fn test_load_new_entries_keeps_selection() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let db = Database::open(tmp.path()).unwrap();
+        let at = |secs_ago| Utc::now() - chrono::Duration::seconds(secs_ago);
+        let entry = |content: &str, secs_ago| NewEntry {
+            created_at: at(secs_ago),
+            last_copied: at(secs_ago),
+            ..NewEntry::new(content)
+        };
+        db.insert_many(&[entry("old", 60), entry("older", 120)]).unwrap();
+
+        let path = tmp.path().to_string_lossy().to_string();
+        let mut app = App::new(vec![], path.clone(), 80, 24);
+        app.switch_database(&path).unwrap();
+        app.select_down();
+        assert_eq!(app.current_entry().unwrap().content, "older");
+
+        db.insert_many(&[entry("new", 0), entry("older", 1)]).unwrap();
+        app.load_new_entries().unwrap();
+        let contents: Vec<&str> = app.entries.iter().map(|e| e.content.as_str()).collect();
+        assert_eq!(contents, ["new", "older", "old"]);
+        assert_eq!(app.current_entry().unwrap().content, "older");
+        assert!(!app.db_changed());
+    }
+
+    #[test]
+    fn test_marked_entries_bulk_actions() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let db = Database::open(tmp.path()).unwrap();
+        let at = |secs_ago| Utc::now() - chrono::Duration::seconds(secs_ago);
+        let entry = |content: &str, secs_ago| NewEntry {
+            created_at: at(secs_ago),
+            last_copied: at(secs_ago),
+            ..NewEntry::new(content)
+        };
+        db.insert_many(&[entry("cd src", 0), entry("ls", 10), entry("make", 20), entry("rm -rf", 30)]).unwrap();
+
+        let path = tmp.path().to_string_lossy().to_string();
+        let mut app = App::new(vec![], path.clone(), 80, 24);
+        app.switch_database(&path).unwrap();
+
+        app.toggle_mark();
+        app.select_down();
+        app.mark_range();
+        assert_eq!(app.marked.len(), 3);
+        app.toggle_mark();
+        assert_eq!(app.marked.len(), 2);
+
+        assert!(app.select_marked().unwrap());
+        assert_eq!(app.selected_entry.as_ref().unwrap().content, "cd src\nls");
+        app.cycle_join_separator();
+        app.select_marked().unwrap();
+        assert_eq!(app.selected_entry.as_ref().unwrap().content, "cd src ls");
+        app.cycle_join_separator();
+        app.select_marked().unwrap();
+        assert_eq!(app.selected_entry.as_ref().unwrap().content, "cd srcls");
+
+        app.toggle_marked_templates().unwrap();
+        assert!(app.entries.iter().filter(|e| e.template).count() == 2);
+
+        let dir = tempfile::tempdir().unwrap();
+        let (count, export) = app.export_marked(dir.path()).unwrap();
+        assert_eq!(count, 2);
+        assert!(std::fs::read_to_string(export).unwrap().contains("cd src"));
+
+        app.queue_marked().unwrap();
+        assert!(app.marked.is_empty());
+        let queued = Database::open(&path).unwrap();
+        assert_eq!(queued.pop_queue().unwrap().unwrap().content, "cd src");
+        assert_eq!(queued.pop_queue().unwrap().unwrap().content, "ls");
+        app.marked.extend(app.entries[..2].iter().map(|e| e.id));
+
+        assert_eq!(app.delete_marked().unwrap(), 2);
+        let remaining: Vec<&str> = app.entries.iter().map(|e| e.content.as_str()).collect();
+        assert_eq!(remaining, ["make", "rm -rf"]);
+        assert!(app.marked.is_empty());
+    }
+
+    #[test]
+    fn test_switch_database() {
+        let first = tempfile::NamedTempFile::new().unwrap();
+        let second = tempfile::NamedTempFile::new().unwrap();
+        Database::open(first.path()).unwrap().insert_entry("one", "h1").unwrap();
+        Database::open(second.path()).unwrap().insert_entry("two", "h2").unwrap();
+
+        let first_path = first.path().to_string_lossy().to_string();
+        let second_path = second.path().to_string_lossy().to_string();
+        let mut app = App::new(vec![], first_path.clone(), 80, 24);
+        app.switch_database(&first_path).unwrap();
+
+        app.open_db_picker(vec![("default".to_string(), second_path.clone()), ("work".to_string(), first_path.clone())]);
+        let picker = app.db_picker.as_mut().unwrap();
+        assert_eq!(picker.selected(), Some(("work", first_path.as_str())));
+        picker.up();
+        let selected = picker.selected().unwrap().1.to_string();
+
+        app.switch_database(&selected).unwrap();
+        assert_eq!(app.db_path, second_path);
+        assert_eq!(app.entries[0].content, "two");
+    }
+
+    #[test]
+    fn test_pages_load_as_the_list_scrolls() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let db = Database::open(tmp.path()).unwrap();
+        let now = Utc::now();
+        let entries: Vec<NewEntry> = (0..PAGE_SIZE + 10)
+            .map(|i| NewEntry { last_copied: now - chrono::Duration::seconds(i as i64), ..NewEntry::new(&format!("entry {}", i)) })
+            .collect();
+        db.insert_many(&entries).unwrap();
+
+        let mut app = App::new(vec![], tmp.path().to_string_lossy().to_string(), 80, 24);
+        app.load_first_page().unwrap();
+        assert_eq!(app.entries.len(), PAGE_SIZE);
+        assert_eq!(app.get_entry_count_info(), format!("{} of {} entries", PAGE_SIZE, PAGE_SIZE + 10));
+
+        while app.entries.len() == PAGE_SIZE {
+            app.select_down();
+        }
+        assert!(app.all_loaded);
+        assert_eq!(app.entries.len(), PAGE_SIZE + 10);
+        assert_eq!(app.entries.last().unwrap().content, format!("entry {}", PAGE_SIZE + 9));
+
+        app.load_first_page().unwrap();
+        app.start_filtering();
+        assert!(app.load_rest && app.loading);
+    }
+
+    #[test]
+    fn test_snippets() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        Database::open(tmp.path()).unwrap().insert_entry("make release", "h1").unwrap();
+        let path = tmp.path().to_string_lossy().to_string();
+        let mut app = App::new(vec![], path.clone(), 80, 24);
+        app.switch_database(&path).unwrap();
+
+        app.open_snippets().unwrap();
+        assert!(app.snippet_picker.is_none());
+        app.start_snippet_name();
+        app.snippet_name.as_mut().unwrap().push_str(" ship ");
+        app.save_snippet().unwrap();
+        assert_eq!(app.message.as_deref(), Some("Saved snippet \"ship\""));
+
+        app.open_snippets().unwrap();
+        assert!(app.select_snippet());
+        assert_eq!(app.selected_entry.as_ref().unwrap().content, "make release");
+
+        app.open_snippets().unwrap();
+        app.delete_snippet().unwrap();
+        assert!(app.snippet_picker.is_none());
+        assert!(Database::open(&path).unwrap().get_snippets().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_transform_copies_without_changing_the_entry() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        Database::open(tmp.path()).unwrap().insert_entry("userAccountId", "h1").unwrap();
+        let mut app = App::new(vec![], tmp.path().to_string_lossy().to_string(), 80, 24);
+        app.refresh().unwrap();
+
+        app.open_transforms();
+        let picker = app.transform_picker.as_mut().unwrap();
+        picker.index = Transform::ALL.iter().position(|&op| op == Transform::Base64Decode).unwrap();
+        assert!(!app.select_transform());
+        assert!(app.transform_picker.is_some());
+        assert!(app.message.as_deref().unwrap().starts_with("Base64 decode: "));
+
+        app.transform_picker.as_mut().unwrap().index = Transform::ALL.iter().position(|&op| op == Transform::Snake).unwrap();
+        assert!(app.select_transform());
+        assert!(app.transform_picker.is_none());
+        assert_eq!(app.selected_entry.as_ref().unwrap().content, "user_account_id");
+
+        app.refresh().unwrap();
+        assert_eq!(app.entries[0].content, "userAccountId");
+    }
+
+    #[test]
+    fn test_toggle_decoded() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let db = Database::open(tmp.path()).unwrap();
+        db.insert_entry("plain words", "h1").unwrap();
+        let encoded = db.insert_entry("aGVsbG8gd29ybGQ=", "h2").unwrap();
+        let mut app = App::new(vec![], tmp.path().to_string_lossy().to_string(), 80, 24);
+        app.refresh().unwrap();
+
+        app.toggle_decoded();
+        assert_eq!(app.decoded, Some(encoded));
+        app.toggle_decoded();
+        assert_eq!(app.decoded, None);
+
+        app.select_down();
+        app.toggle_decoded();
+        assert_eq!(app.decoded, None);
+        assert_eq!(app.message.as_deref(), Some("Not a JWT or base64"));
+    }
+
+    #[test]
+    fn test_open_url_needs_a_url() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        Database::open(tmp.path()).unwrap().insert_entry("plain words", "h1").unwrap();
+        let mut app = App::new(vec![], tmp.path().to_string_lossy().to_string(), 80, 24);
+        app.refresh().unwrap();
+
+        app.open_url();
+        assert_eq!(app.message.as_deref(), Some("Not a URL"));
+    }
+
+    #[test]
+    fn test_sensitive_entries_are_masked_until_revealed() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        Database::open(tmp.path()).unwrap().insert_entry("token: abc123", "h1").unwrap();
+        let mut app = App::new(vec![], tmp.path().to_string_lossy().to_string(), 80, 24);
+        app.refresh().unwrap();
+
+        let entry = app.current_entry().unwrap();
+        assert_eq!(app.shown(entry).content, "token: ••••••••");
+        app.toggle_reveal();
+        assert_eq!(app.shown(app.current_entry().unwrap()).content, "token: abc123");
+        app.toggle_reveal();
+        assert_eq!(app.shown(app.current_entry().unwrap()).content, "token: ••••••••");
+
+        app.mask_sensitive = false;
+        assert_eq!(app.shown(app.current_entry().unwrap()).content, "token: abc123");
+    }
+
+    #[test]
+    fn test_template_entry_opens_form() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        Database::open(tmp.path()).unwrap().insert_entry("ping {host}", "h1").unwrap();
+        let mut app = App::new(vec![], tmp.path().to_string_lossy().to_string(), 80, 24);
+        app.refresh().unwrap();
+
+        app.toggle_template().unwrap();
+        assert!(app.entries[0].template);
+        assert!(!app.select_entry());
+
+        app.template_form.as_mut().unwrap().values[0] = "example.com".to_string();
+        app.finish_template();
+        assert_eq!(app.selected_entry.as_ref().unwrap().content, "ping example.com");
+
+        app.refresh().unwrap();
+        assert!(app.entries[0].template);
+    }
+
+    #[test]
+    fn test_needs_ticks() {
+        let mut app = App::new(vec![], "/test/db".to_string(), 80, 24);
+        app.auto_refresh = false;
+        assert!(!app.needs_ticks());
+
+        app.loading = true;
+        assert!(app.needs_ticks());
+    }
+
     #[test]
     fn test_entry_count_info() {
         let entries = vec![
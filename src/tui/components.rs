@@ -1,5 +1,10 @@
+use crate::color::{self, Rgba};
 use crate::db::ClipboardEntry;
+use crate::kind::Kind;
+use crate::tui::decode;
 use crate::tui::fuzzy;
+use crate::tui::syntax::{self, Token};
+use crate::tui::theme::{palette, Badge};
 use chrono::{DateTime, Local, Utc};
 use once_cell::sync::Lazy;
 use ratatui::{
@@ -8,17 +13,12 @@ use ratatui::{
     widgets::{Block, Borders, BorderType, Clear, Paragraph},
     layout::{Alignment, Margin},
 };
-use regex::Regex;
-use crate::tui::app::DeletePeriod;
-
-// ── Color palette (matching mindful-jira) ───────────────────
-const ZEBRA_DARK: Color = Color::Rgb(30, 30, 40);
-const HIGHLIGHT_BG: Color = Color::Rgb(55, 55, 80);
-const DIM: Color = Color::Rgb(100, 100, 110);
-const ACCENT: Color = Color::Rgb(180, 180, 255);
-const BORDER_COLOR: Color = Color::Rgb(60, 60, 80);
-const HINT_COLOR: Color = Color::Rgb(120, 120, 140);
-const SEARCH_BG: Color = Color::Rgb(25, 25, 35);
+use regex::{Regex, RegexSet};
+use std::collections::HashSet;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+use crate::tui::app::{App, DbPicker, DeletePeriod, SnippetPicker, TransformPicker};
+use crate::transform::Transform;
 
 pub fn dim_background(f: &mut Frame) {
     let area = f.size();
@@ -26,77 +26,123 @@ pub fn dim_background(f: &mut Frame) {
     for y in area.top()..area.bottom() {
         for x in area.left()..area.right() {
             let cell = buf.get_mut(x, y);
-            cell.set_fg(Color::Rgb(50, 50, 60));
-            cell.set_bg(Color::Rgb(10, 10, 15));
+            cell.set_fg(palette().overlay_fg);
+            cell.set_bg(palette().overlay_bg);
         }
     }
 }
 
-static EMAIL_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}").unwrap()
-});
-static URL_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"https?://[^\s<>\[\]()]+").unwrap()
-});
-static IP_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"\b(?:\d{1,3}\.){3}\d{1,3}\b").unwrap()
-});
-static SECRET_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?i)(password|secret|token|api[_-]?key|auth)[=:]\s*\S+").unwrap()
-});
-static UUID_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}").unwrap()
+/// Pattern kinds in priority order; on equal starts the earlier kind wins.
+const PATTERN_TYPES: [PatternType; 5] =
+    [PatternType::Email, PatternType::Url, PatternType::Ip, PatternType::Secret, PatternType::Uuid];
+const PATTERN_SOURCES: [&str; 5] = [
+    r"[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}",
+    r"https?://[^\s<>\[\]()]+",
+    r"\b(?:\d{1,3}\.){3}\d{1,3}\b",
+    crate::secrets::CREDENTIAL_PATTERN,
+    r"[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}",
+];
+
+/// Cheap prefilter: most lines contain none of the patterns and skip the capturing scan.
+static PATTERN_SET: Lazy<RegexSet> = Lazy::new(|| RegexSet::new(PATTERN_SOURCES).unwrap());
+/// All patterns as one alternation, one capture group per pattern kind.
+static PATTERN_RE: Lazy<Regex> = Lazy::new(|| {
+    let groups: Vec<String> = PATTERN_SOURCES.iter().map(|p| format!("({})", p)).collect();
+    Regex::new(&groups.join("|")).unwrap()
 });
 
-#[derive(Clone, Copy)]
-enum PatternType {
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum PatternType {
     Email,
     Url,
     Ip,
     Secret,
     Uuid,
+    /// A CSS color, shown on a background of itself.
+    Color(u8, u8, u8),
+    /// Code in an entry tagged with a language.
+    Syntax(Token),
 }
 
 impl PatternType {
-    fn color(self) -> Color {
-        match self {
+    fn style(self) -> Style {
+        let fg = match self {
             PatternType::Email => Color::Cyan,
             PatternType::Url => Color::Blue,
             PatternType::Ip => Color::Green,
             PatternType::Secret => Color::Red,
             PatternType::Uuid => Color::Magenta,
+            PatternType::Color(r, g, b) => return swatch_style(Rgba { r, g, b, a: 1.0 }),
+            PatternType::Syntax(token) => token.color(),
+        };
+        Style::default().fg(fg)
+    }
+
+    /// SGR parameters for plain-terminal output.
+    pub(crate) fn ansi_code(self) -> String {
+        match self {
+            PatternType::Email => "36".to_string(),
+            PatternType::Url => "34".to_string(),
+            PatternType::Ip => "32".to_string(),
+            PatternType::Secret => "31".to_string(),
+            PatternType::Uuid => "35".to_string(),
+            PatternType::Color(r, g, b) => {
+                let fg = if (Rgba { r, g, b, a: 1.0 }).is_light() { 30 } else { 97 };
+                format!("{};48;2;{};{};{}", fg, r, g, b)
+            }
+            PatternType::Syntax(token) => token.ansi_code().to_string(),
         }
     }
 }
 
-fn find_patterns(text: &str) -> Vec<(usize, usize, PatternType)> {
-    let patterns: &[(_, PatternType)] = &[
-        (&*EMAIL_RE, PatternType::Email),
-        (&*URL_RE, PatternType::Url),
-        (&*IP_RE, PatternType::Ip),
-        (&*SECRET_RE, PatternType::Secret),
-        (&*UUID_RE, PatternType::Uuid),
-    ];
+/// The color as background, with text that stays readable on it.
+fn swatch_style(color: Rgba) -> Style {
+    let fg = if color.is_light() { Color::Black } else { Color::White };
+    Style::default().fg(fg).bg(Color::Rgb(color.r, color.g, color.b))
+}
 
-    let mut matches: Vec<_> = patterns.iter()
-        .flat_map(|(re, ptype)| re.find_iter(text).map(move |m| (m.start(), m.end(), *ptype)))
-        .collect();
+pub(crate) type PatternMatch = (usize, usize, PatternType);
 
-    matches.sort_by_key(|(start, _, _)| *start);
+/// Non-overlapping pattern matches in `text`, leftmost first.
+pub(crate) fn find_patterns(text: &str) -> Vec<PatternMatch> {
+    if !PATTERN_SET.is_match(text) {
+        return vec![];
+    }
 
-    let mut result = vec![];
-    let mut last_end = 0;
-    for (start, end, ptype) in matches {
-        if start >= last_end {
-            result.push((start, end, ptype));
-            last_end = end;
-        }
+    PATTERN_RE
+        .captures_iter(text)
+        .filter_map(|caps| {
+            let m = caps.get(0)?;
+            let kind = (1..caps.len()).find(|&i| caps.get(i).is_some())?;
+            Some((m.start(), m.end(), PATTERN_TYPES[kind - 1]))
+        })
+        .collect()
+}
+
+/// Pattern matches, then the colors and, for code in `language`, the syntax tokens that don't
+/// overlap anything found before them.
+pub(crate) fn find_highlights(text: &str, language: Option<&str>) -> Vec<PatternMatch> {
+    let mut matches = find_patterns(text);
+    let free = |matches: &[PatternMatch], start: usize, end: usize| !matches.iter().any(|&(s, e, _)| start < e && s < end);
+    let colors: Vec<PatternMatch> = color::find(text)
+        .into_iter()
+        .filter(|&(start, end, _)| free(&matches, start, end))
+        .map(|(start, end, c)| (start, end, PatternType::Color(c.r, c.g, c.b)))
+        .collect();
+    matches.extend(colors);
+    if let Some(language) = language {
+        let tokens: Vec<PatternMatch> = syntax::tokens(text, language)
+            .into_iter()
+            .filter(|&(start, end, _)| free(&matches, start, end))
+            .map(|(start, end, token)| (start, end, PatternType::Syntax(token)))
+            .collect();
+        matches.extend(tokens);
     }
-    result
+    matches.sort_by_key(|&(start, _, _)| start);
+    matches
 }
 
-fn highlight_patterns(text: &str) -> Vec<Span<'static>> {
-    let patterns = find_patterns(text);
+fn highlight_patterns(text: &str, patterns: &[PatternMatch]) -> Vec<Span<'static>> {
     if patterns.is_empty() {
         return vec![Span::raw(text.to_string())];
     }
@@ -104,14 +150,11 @@ fn highlight_patterns(text: &str) -> Vec<Span<'static>> {
     let mut spans = vec![];
     let mut last_end = 0;
 
-    for (start, end, ptype) in patterns {
+    for &(start, end, ptype) in patterns {
         if start > last_end {
             spans.push(Span::raw(text[last_end..start].to_string()));
         }
-        spans.push(Span::styled(
-            text[start..end].to_string(),
-            Style::default().fg(ptype.color()),
-        ));
+        spans.push(Span::styled(text[start..end].to_string(), ptype.style()));
         last_end = end;
     }
 
@@ -122,67 +165,101 @@ fn highlight_patterns(text: &str) -> Vec<Span<'static>> {
     spans
 }
 
-fn highlight_search(text: &str, query: &str) -> Vec<Span<'static>> {
-    if query.is_empty() {
-        return highlight_patterns(text);
+fn highlight_search(text: &str, query: &str, patterns: &[PatternMatch]) -> Vec<Span<'static>> {
+    let ranges = find_match_ranges(text, query);
+    if ranges.is_empty() {
+        return highlight_patterns(text, patterns);
     }
 
-    let chars: Vec<char> = text.chars().collect();
-    let chars_lower: Vec<char> = text.to_lowercase().chars().collect();
-    let query_chars: Vec<char> = query.to_lowercase().chars().collect();
+    let mut spans = vec![];
+    let mut last_end = 0;
+    for range in ranges {
+        if range.start > last_end {
+            spans.push(Span::raw(text[last_end..range.start].to_string()));
+        }
+        spans.push(Span::styled(
+            text[range.clone()].to_string(),
+            Style::default().bg(Color::Yellow).fg(Color::Black),
+        ));
+        last_end = range.end;
+    }
+    if last_end < text.len() {
+        spans.push(Span::raw(text[last_end..].to_string()));
+    }
+    spans
+}
 
-    if chars_lower.len() < query_chars.len() {
-        return highlight_patterns(text);
+/// Byte ranges of case-insensitive, non-overlapping matches of `query` in `text`.
+///
+/// Matching walks grapheme clusters and lowercases each one separately, so ranges always
+/// land on cluster boundaries of the original text even when lowercasing changes lengths
+/// (e.g. 'İ' becomes two chars) or the text contains combining sequences.
+pub(crate) fn find_match_ranges(text: &str, query: &str) -> Vec<Range<usize>> {
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return vec![];
     }
 
-    let mut spans = vec![];
-    let mut last_end = 0;
-    let max_i = chars_lower.len() - query_chars.len();
+    let graphemes: Vec<(usize, String)> = text
+        .grapheme_indices(true)
+        .map(|(offset, g)| (offset, g.to_lowercase()))
+        .collect();
+    let end_of = |i: usize| graphemes.get(i).map_or(text.len(), |(offset, _)| *offset);
 
+    let mut ranges = vec![];
     let mut i = 0;
-    while i <= max_i {
-        if chars_lower[i..i + query_chars.len()] == query_chars[..] {
-            if i > last_end {
-                spans.push(Span::raw(chars[last_end..i].iter().collect::<String>()));
-            }
-            spans.push(Span::styled(
-                chars[i..i + query_chars.len()].iter().collect::<String>(),
-                Style::default().bg(Color::Yellow).fg(Color::Black),
-            ));
-            last_end = i + query_chars.len();
-            i = last_end;
+    while i < graphemes.len() {
+        let mut lowered = String::new();
+        let mut j = i;
+        while j < graphemes.len() && lowered.len() < query.len() {
+            lowered.push_str(&graphemes[j].1);
+            j += 1;
+        }
+        if lowered == query {
+            ranges.push(graphemes[i].0..end_of(j));
+            i = j;
         } else {
             i += 1;
         }
     }
+    ranges
+}
 
-    if last_end < chars.len() {
-        spans.push(Span::raw(chars[last_end..].iter().collect::<String>()));
-    }
+pub fn draw_header(
+    f: &mut Frame,
+    area: Rect,
+    db_label: &str,
+    incognito: Option<&str>,
+    subtitle: &str,
+    spinner: Option<char>,
+) {
+    let display_subtitle = match spinner {
+        Some(frame) => format!("{} Loading…", frame),
+        None => subtitle.to_string(),
+    };
 
-    if spans.is_empty() {
-        highlight_patterns(text)
-    } else {
-        spans
+    let mut title = Line::from(vec![
+        Span::styled(
+            " Clippie ",
+            Style::default().fg(palette().accent).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!("v{} ", env!("CARGO_PKG_VERSION")),
+            Style::default().fg(palette().version),
+        ),
+        Span::styled(format!("· {} ", db_label), Style::default().fg(palette().dim)),
+    ]);
+    if let Some(badge) = incognito {
+        title.spans.push(Span::styled(
+            format!(" {} ", badge),
+            Style::default().fg(palette().popup_bg).bg(palette().danger).add_modifier(Modifier::BOLD),
+        ));
     }
-}
-
-pub fn draw_header(f: &mut Frame, area: Rect, _title: &str, subtitle: &str, loading: bool) {
-    let display_subtitle = if loading { "Loading..." } else { subtitle };
 
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(BORDER_COLOR))
-        .title(Line::from(vec![
-            Span::styled(
-                " Clippie ",
-                Style::default().fg(ACCENT).add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(
-                format!("v{} ", env!("CARGO_PKG_VERSION")),
-                Style::default().fg(Color::Rgb(80, 80, 100)),
-            ),
-        ]));
+        .border_style(Style::default().fg(palette().border))
+        .title(title);
 
     f.render_widget(block, area);
 
@@ -193,7 +270,7 @@ pub fn draw_header(f: &mut Frame, area: Rect, _title: &str, subtitle: &str, load
         let x = area.x + area.width.saturating_sub(sub_len + 2);
         let sub_area = Rect::new(x, area.y, sub_len, 1);
         f.render_widget(
-            Paragraph::new(Span::styled(sub_text, Style::default().fg(DIM))),
+            Paragraph::new(Span::styled(sub_text, Style::default().fg(palette().dim))),
             sub_area,
         );
     }
@@ -205,21 +282,21 @@ pub fn draw_search_bar(f: &mut Frame, area: Rect, filter_text: &str, is_filterin
         Span::styled(
             " /",
             Style::default()
-                .fg(Color::Rgb(255, 200, 60))
+                .fg(palette().search)
                 .add_modifier(Modifier::BOLD),
         ),
         Span::styled(
             filter_text.to_string(),
-            Style::default().fg(Color::White),
+            Style::default().fg(palette().text_selected),
         ),
-        Span::styled(cursor.to_string(), Style::default().fg(Color::Rgb(255, 200, 60))),
+        Span::styled(cursor.to_string(), Style::default().fg(palette().search)),
         Span::styled(
             format!("  ({} matches)", match_count),
-            Style::default().fg(Color::Rgb(100, 100, 120)),
+            Style::default().fg(palette().muted),
         ),
     ]);
 
-    f.render_widget(Paragraph::new(line).style(Style::default().bg(SEARCH_BG)), area);
+    f.render_widget(Paragraph::new(line).style(Style::default().bg(palette().search_bg)), area);
 }
 
 pub fn draw_confirm_quit_popup(f: &mut Frame, area: Rect) {
@@ -233,10 +310,10 @@ pub fn draw_confirm_quit_popup(f: &mut Frame, area: Rect) {
 
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(ACCENT))
+        .border_style(Style::default().fg(palette().accent))
         .title(Span::styled(
             " Quit ",
-            Style::default().fg(ACCENT).add_modifier(Modifier::BOLD),
+            Style::default().fg(palette().accent).add_modifier(Modifier::BOLD),
         ));
 
     let inner = block.inner(modal_area);
@@ -246,18 +323,25 @@ pub fn draw_confirm_quit_popup(f: &mut Frame, area: Rect) {
         Line::from(""),
         Line::from(Span::styled(
             "  Quit Clippie?",
-            Style::default().fg(Color::White),
+            Style::default().fg(palette().popup_fg),
         )),
         Line::from(""),
         Line::from(Span::styled(
             "  y/Enter:Quit  n/Esc:Cancel",
-            Style::default().fg(Color::Rgb(100, 100, 120)),
+            Style::default().fg(palette().muted),
         )),
     ];
 
     f.render_widget(Paragraph::new(lines), inner);
 }
 
+/// Characters given to the source app column, and the list width it needs to be shown.
+const APP_COLUMN_WIDTH: usize = 12;
+const APP_COLUMN_MIN_WIDTH: usize = 70;
+/// Characters given to the kind tag, e.g. `url` or `path`, and the list width it needs.
+const KIND_COLUMN_WIDTH: usize = 6;
+const KIND_COLUMN_MIN_WIDTH: usize = 45;
+
 pub fn draw_entry_list(
     f: &mut Frame,
     area: Rect,
@@ -265,9 +349,13 @@ pub fn draw_entry_list(
     selected_index: usize,
     scroll_offset: usize,
     filter_text: &str,
+    marked: &HashSet<i64>,
 ) {
     let width = area.width as usize;
-    let content_max_width = width.saturating_sub(15); // selector(3) + date(10) + padding(2)
+    // Source app names only get a column when there's room to spare.
+    let app_width = if width >= APP_COLUMN_MIN_WIDTH { APP_COLUMN_WIDTH } else { 0 };
+    let kind_width = if width >= KIND_COLUMN_MIN_WIDTH { KIND_COLUMN_WIDTH } else { 0 };
+    let content_max_width = width.saturating_sub(15 + app_width + kind_width); // selector(3) + date(10) + padding(2)
 
     let visible_entries: Vec<Line> = entries
         .iter()
@@ -275,7 +363,7 @@ pub fn draw_entry_list(
         .map(|(idx, entry)| {
             let absolute_idx = scroll_offset + idx;
             let is_selected = absolute_idx == selected_index;
-            let content_preview = entry.content.replace('\n', "↵").replace('\r', "");
+            let content_preview = entry.label().replace('\n', "↵").replace('\r', "");
 
             let content_display = if content_preview.chars().count() > content_max_width {
                 let truncated: String = content_preview.chars().take(content_max_width.saturating_sub(1)).collect();
@@ -285,20 +373,37 @@ pub fn draw_entry_list(
             };
 
             let date_str = format_relative_date(&entry.last_copied);
+            let app_label = match (&entry.source_app, app_width) {
+                (_, 0) => String::new(),
+                (Some(app), _) if app.chars().count() >= app_width => {
+                    format!(" {}…", app.chars().take(app_width - 2).collect::<String>())
+                }
+                (app, _) => format!("{:>width$}", app.as_deref().unwrap_or(""), width = app_width),
+            };
+            let kind_label = match kind_width {
+                0 => String::new(),
+                _ => format!("{:>width$}", entry.kind.label(), width = kind_width),
+            };
 
             // Zebra striping + highlight for selected row
             let bg = if is_selected {
-                HIGHLIGHT_BG
+                palette().highlight_bg
             } else if absolute_idx % 2 == 1 {
-                ZEBRA_DARK
+                palette().zebra
             } else {
                 Color::Reset
             };
 
-            let fg = if is_selected { Color::White } else { Color::Rgb(200, 200, 210) };
-            let date_fg = if is_selected { Color::Rgb(160, 160, 180) } else { DIM };
-            let selector = if is_selected { "▶ " } else { "  " };
-            let selector_style = Style::default().fg(ACCENT).bg(bg).add_modifier(if is_selected { Modifier::BOLD } else { Modifier::empty() });
+            let fg = if is_selected { palette().text_selected } else { palette().text };
+            let date_fg = if is_selected { palette().date_selected } else { palette().dim };
+            let selector = match (is_selected, marked.contains(&entry.id), entry.template) {
+                (true, true, _) => "▶●",
+                (true, false, _) => "▶ ",
+                (false, true, _) => " ●",
+                (false, false, true) => "✎ ",
+                (false, false, false) => "  ",
+            };
+            let selector_style = Style::default().fg(palette().accent).bg(bg).add_modifier(if is_selected { Modifier::BOLD } else { Modifier::empty() });
 
             if filter_text.is_empty() {
                 let mut spans = vec![
@@ -310,9 +415,11 @@ pub fn draw_entry_list(
                 if padding > 0 {
                     spans.push(Span::styled(" ".repeat(padding), Style::default().bg(bg)));
                 }
+                spans.push(Span::styled(kind_label, Style::default().fg(palette().accent).bg(bg)));
+                spans.push(Span::styled(app_label, Style::default().fg(date_fg).bg(bg)));
                 spans.push(Span::styled(format!("{:>10}", date_str), Style::default().fg(date_fg).bg(bg)));
                 // Fill remaining space with bg color
-                let total: usize = current_len + padding + 10;
+                let total: usize = current_len + padding + kind_width + app_width + 10;
                 let remaining = width.saturating_sub(total);
                 if remaining > 0 {
                     spans.push(Span::styled(" ".repeat(remaining), Style::default().bg(bg)));
@@ -335,7 +442,7 @@ pub fn draw_entry_list(
                         }
                         spans.push(Span::styled(
                             chars[*match_start..(*match_start + match_len)].iter().collect::<String>(),
-                            Style::default().fg(Color::Rgb(255, 200, 60)).bg(bg).add_modifier(Modifier::BOLD),
+                            Style::default().fg(palette().search).bg(bg).add_modifier(Modifier::BOLD),
                         ));
                         last_pos = *match_start + match_len;
                     }
@@ -355,6 +462,8 @@ pub fn draw_entry_list(
                     spans.push(Span::styled(" ".repeat(padding), Style::default().bg(bg)));
                 }
 
+                spans.push(Span::styled(kind_label, Style::default().fg(palette().accent).bg(bg)));
+                spans.push(Span::styled(app_label, Style::default().fg(date_fg).bg(bg)));
                 spans.push(Span::styled(format!("{:>10}", date_str), Style::default().fg(date_fg).bg(bg)));
                 Line::from(spans)
             }
@@ -363,54 +472,149 @@ pub fn draw_entry_list(
 
     if visible_entries.is_empty() {
         let message = if entries.is_empty() { "  No clipboard history found." } else { "  No matches." };
-        f.render_widget(Paragraph::new(message).style(Style::default().fg(DIM)), area);
+        f.render_widget(Paragraph::new(message).style(Style::default().fg(palette().dim)), area);
     } else {
         f.render_widget(Paragraph::new(visible_entries), area);
     }
 }
 
+/// Wrapped preview lines, reused across frames until the entry, width or filter changes.
+#[derive(Debug, Default)]
+pub struct PreviewCache {
+    /// Keyed on `sensitive` too, which `App::shown` clears on masked copies, so revealing re-lays out,
+    /// and on whether the decoded view was asked for.
+    layout_key: Option<(i64, DateTime<Utc>, usize, bool, bool)>,
+    /// Language of the laid out entry, which picks the syntax highlighting.
+    language: Option<String>,
+    /// What the entry decodes as, `JWT` or `base64`, if anything.
+    decodable: Option<&'static str>,
+    lines: Vec<String>,
+    match_key: Option<String>,
+    first_match: Option<usize>,
+    /// Pattern matches per wrapped line, filled in as lines scroll into view.
+    patterns: Vec<Option<Vec<PatternMatch>>>,
+}
+
+impl PreviewCache {
+    fn layout(&mut self, entry: &ClipboardEntry, width: usize, filter_text: &str, decoded: bool) -> (&[String], Option<usize>) {
+        let layout_key = (entry.id, entry.last_copied, width, entry.sensitive, decoded);
+        if self.layout_key != Some(layout_key) {
+            let view = if entry.encrypted { None } else { decode::decoded_view(&entry.content) };
+            self.decodable = view.as_ref().map(|(label, _)| *label);
+            let (text, language) = match view {
+                Some((_, text)) if decoded => (text, None),
+                _ => (entry.display_content().to_string(), entry.language.clone()),
+            };
+            self.lines = text.lines().flat_map(|l| wrap_text(l, width)).collect();
+            self.layout_key = Some(layout_key);
+            self.language = language;
+            self.match_key = None;
+            self.patterns = vec![None; self.lines.len()];
+        }
+
+        if self.match_key.as_deref() != Some(filter_text) {
+            let query = filter_text.to_lowercase();
+            self.first_match = if query.is_empty() {
+                None
+            } else {
+                self.lines.iter().position(|l| l.to_lowercase().contains(&query))
+            };
+            self.match_key = Some(filter_text.to_string());
+        }
+
+        (&self.lines, self.first_match)
+    }
+
+    fn scan_patterns(&mut self, range: Range<usize>) {
+        let end = range.end.min(self.lines.len());
+        for i in range.start.min(end)..end {
+            if self.patterns[i].is_none() {
+                self.patterns[i] = Some(find_highlights(&self.lines[i], self.language.as_deref()));
+            }
+        }
+    }
+}
+
+/// Cells of the swatch shown above a color entry.
+const SWATCH_WIDTH: usize = 10;
+
+/// A swatch of the color with its hex, rgb and hsl notations beside it, then a blank spacer.
+fn color_swatch(color: Rgba) -> Vec<Line<'static>> {
+    let swatch = Style::default().bg(Color::Rgb(color.r, color.g, color.b));
+    let mut lines: Vec<Line> = [color.hex(), color.rgb(), color.hsl()]
+        .into_iter()
+        .map(|notation| Line::from(vec![Span::styled(" ".repeat(SWATCH_WIDTH), swatch), Span::raw("  "), Span::raw(notation)]))
+        .collect();
+    lines.push(Line::from(""));
+    lines
+}
+
 pub fn draw_preview(
     f: &mut Frame,
     area: Rect,
     entry: Option<&ClipboardEntry>,
     filter_text: &str,
     scroll_offset: usize,
+    decoded: bool,
+    cache: &mut PreviewCache,
 ) -> (usize, Option<usize>) {
     let width = area.width.saturating_sub(2) as usize;
     let height = area.height as usize;
 
-    let (lines, first_match_line) = if let Some(e) = entry {
-        let mut lines = vec![];
-        let mut first_match: Option<usize> = None;
-
-        lines.push(Line::from(Span::styled(
-            format!("─ {}", format_absolute_date(&e.created_at)),
-            Style::default().fg(DIM),
-        )));
-        lines.push(Line::from(""));
-
-        for content_line in e.content.lines() {
-            for wrapped_line in wrap_text(content_line, width) {
-                let line = if filter_text.is_empty() {
-                    Line::from(highlight_patterns(&wrapped_line))
-                } else {
-                    if first_match.is_none() && wrapped_line.to_lowercase().contains(&filter_text.to_lowercase()) {
-                        first_match = Some(lines.len());
-                    }
-                    Line::from(highlight_search(&wrapped_line, filter_text))
-                };
-                lines.push(line);
-            }
-        }
+    let (visible_lines, total_lines, first_match_line) = if let Some(e) = entry {
+        let first_match = cache.layout(e, width, filter_text, decoded).1;
+        let swatch = match e.kind {
+            Kind::Color if !e.encrypted => color::parse(&e.content).map(color_swatch).unwrap_or_default(),
+            _ => vec![],
+        };
+        // The date, a blank spacer and the swatch of a color.
+        let header_lines = 2 + swatch.len();
+        let first_line = scroll_offset.saturating_sub(header_lines);
+        cache.scan_patterns(first_line..(scroll_offset + height).saturating_sub(header_lines));
+        let content = &cache.lines;
+        let header = [
+            Line::from(Span::styled(
+                std::iter::once(format!("─ {}", format_absolute_date(&e.created_at)))
+                    .chain(e.language.clone())
+                    .chain(e.files().map(|paths| match paths.len() {
+                        1 => "1 file".to_string(),
+                        n => format!("{} files", n),
+                    }))
+                    .chain(cache.decodable.map(|label| match decoded {
+                        true => format!("decoded {}", label),
+                        false => format!("{}, B decodes", label),
+                    }))
+                    .chain(e.source_app.as_ref().map(|app| format!("from {}", app)))
+                    .collect::<Vec<_>>()
+                    .join(" · "),
+                Style::default().fg(palette().dim),
+            )),
+            Line::from(""),
+        ];
+
+        // Only the lines on screen get highlighted.
+        let visible: Vec<Line> = header
+            .into_iter()
+            .chain(swatch)
+            .chain(content.iter().map(|l| Line::raw(l.as_str())))
+            .skip(scroll_offset)
+            .take(height)
+            .enumerate()
+            .map(|(i, line)| match scroll_offset + i {
+                n if n < header_lines => line,
+                n => {
+                    let n = n - header_lines;
+                    let patterns = cache.patterns[n].as_deref().unwrap_or_default();
+                    Line::from(highlight_search(&content[n], filter_text, patterns))
+                }
+            })
+            .collect();
 
-        (lines, first_match)
+        (visible, content.len() + header_lines, first_match.map(|m| m + header_lines))
     } else {
-        (vec![Line::from(Span::styled("No entry selected", Style::default().fg(DIM)))], None)
+        (vec![Line::from(Span::styled("No entry selected", Style::default().fg(palette().dim)))], 1, None)
     };
 
-    let total_lines = lines.len();
-    let visible_lines: Vec<Line> = lines.into_iter().skip(scroll_offset).take(height).collect();
-
     let content_area = Rect { x: area.x, y: area.y, width: area.width.saturating_sub(1), height: area.height };
     f.render_widget(Paragraph::new(visible_lines), content_area);
 
@@ -446,7 +650,7 @@ fn draw_scrollbar(f: &mut Frame, area: Rect, offset: usize, total: usize, visibl
     f.render_widget(Paragraph::new(scrollbar_lines), area);
 }
 
-fn wrap_text(text: &str, width: usize) -> Vec<String> {
+pub(crate) fn wrap_text(text: &str, width: usize) -> Vec<String> {
     if width == 0 || text.is_empty() {
         return vec![text.to_string()];
     }
@@ -477,84 +681,81 @@ fn wrap_text(text: &str, width: usize) -> Vec<String> {
     lines
 }
 
-pub fn draw_status_bar(
-    f: &mut Frame,
-    area: Rect,
-    is_filtering: bool,
-    filter_text: &str,
-    confirm_quit: bool,
-    is_in_delete_mode: bool,
-    message: Option<&str>,
-) {
-    let (mode_badge, help_text) = if confirm_quit {
+fn badge_style(badge: Badge) -> Style {
+    Style::default().bg(badge.bg).fg(badge.fg)
+}
+
+pub fn draw_status_bar(f: &mut Frame, area: Rect, app: &App) {
+    let marked = app.marked.len();
+    let (selected_badge, marked_help);
+    let (mode_badge, help_text) = if app.confirm_quit {
         (
             Span::styled(
                 " QUIT ",
-                Style::default()
-                    .bg(Color::Rgb(180, 60, 60))
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
+                badge_style(palette().quit_badge).add_modifier(Modifier::BOLD),
             ),
             " y/Enter:Quit  n/Esc:Cancel ",
         )
-    } else if is_in_delete_mode {
+    } else if app.is_in_delete_mode() {
         (
             Span::styled(
                 " DELETE ",
-                Style::default()
-                    .bg(Color::Red)
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
+                badge_style(palette().delete_badge).add_modifier(Modifier::BOLD),
             ),
             " y:Confirm  n/Esc:Cancel  j/k:Navigate ",
         )
-    } else if is_filtering {
+    } else if app.is_filtering {
         (
             Span::styled(
                 " FILTER ",
-                Style::default()
-                    .bg(Color::Rgb(180, 160, 40))
-                    .fg(Color::Black)
-                    .add_modifier(Modifier::BOLD),
+                badge_style(palette().filter_badge).add_modifier(Modifier::BOLD),
             ),
             " Type to filter  Enter:Keep  Esc:Clear ",
         )
-    } else if !filter_text.is_empty() {
+    } else if marked > 0 {
+        selected_badge = format!(" {} SELECTED ", marked);
+        marked_help = format!(
+            " Space:Mark  V:Range  Enter:Join ({})  s:Separator  d:Del  t:Template  e:Export  Esc:Unmark ",
+            app.join_separator.name()
+        );
+        (
+            Span::styled(
+                selected_badge.as_str(),
+                badge_style(palette().selected_badge).add_modifier(Modifier::BOLD),
+            ),
+            marked_help.as_str(),
+        )
+    } else if !app.filter_text.is_empty() {
         (
             Span::styled(
                 " FILTERED ",
-                Style::default()
-                    .bg(Color::Rgb(180, 130, 50))
-                    .fg(Color::Black)
-                    .add_modifier(Modifier::BOLD),
+                badge_style(palette().filtered_badge).add_modifier(Modifier::BOLD),
             ),
-            " q:Quit  j/k:Nav  Enter:Copy  /:Filter  d:Del  x:Del  D:Bulk  r:Refresh  h/l:Scroll ",
+            " q:Quit  j/k:Nav  Enter:Copy  /:Filter  Space:Mark  d:Del  t:Template  ?:Help ",
         )
     } else {
         (
             Span::styled(
                 " NORMAL ",
-                Style::default()
-                    .bg(Color::Rgb(60, 60, 120))
-                    .fg(Color::White),
+                badge_style(palette().normal_badge),
             ),
-            " q:Quit  j/k:Nav  Enter:Copy  /:Filter  d:Del  x:Del  D:Bulk  r:Refresh  h/l:Scroll ",
+            " q:Quit  j/k:Nav  Enter:Copy  /:Filter  Space:Mark  d:Del  t:Template  ?:Help ",
         )
     };
 
     let mut spans = vec![
         mode_badge,
-        Span::styled(help_text, Style::default().fg(HINT_COLOR)),
+        Span::styled(help_text, Style::default().fg(palette().hint)),
     ];
 
-    if let Some(msg) = message {
-        spans.push(Span::styled(msg, Style::default().fg(Color::Rgb(140, 200, 255))));
+    if let Some(msg) = &app.message {
+        spans.push(Span::styled(msg.as_str(), Style::default().fg(palette().message)));
     }
 
     f.render_widget(Paragraph::new(Line::from(spans)), area);
 }
 
-fn format_relative_date(date: &DateTime<Utc>) -> String {
+pub(crate) fn format_relative_date(date: &DateTime<Utc>) -> String {
     let duration = Utc::now().signed_duration_since(*date);
 
     if duration.num_seconds() < 60 {
@@ -572,11 +773,299 @@ fn format_relative_date(date: &DateTime<Utc>) -> String {
     }
 }
 
-fn format_absolute_date(date: &DateTime<Utc>) -> String {
+pub(crate) fn format_absolute_date(date: &DateTime<Utc>) -> String {
     date.with_timezone(&Local).format("%b %d at %H:%M").to_string()
 }
 
 /// Helper function to create a centered rect
+pub fn draw_db_picker_popup(f: &mut Frame, area: Rect, picker: &DbPicker, current: &str) {
+    let popup_area = centered_rect(60, 40, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(palette().accent))
+        .title(Span::styled(
+            " Switch Profile ",
+            Style::default().fg(palette().accent).add_modifier(Modifier::BOLD),
+        ))
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(palette().popup_bg).fg(palette().popup_fg));
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(block, popup_area);
+
+    let inner = popup_area.inner(&Margin { vertical: 1, horizontal: 2 });
+    let width = picker.profiles.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0);
+    let mut lines: Vec<Line> = picker
+        .profiles
+        .iter()
+        .enumerate()
+        .map(|(idx, (name, path))| {
+            let prefix = if idx == picker.index { "> " } else { "  " };
+            let suffix = if path == current { "  (open)" } else { "" };
+            let style = if idx == picker.index {
+                Style::default().fg(palette().accent).bold()
+            } else {
+                Style::default()
+            };
+            Line::from(vec![
+                Span::styled(format!("{}{:<width$}  ", prefix, name, width = width), style),
+                Span::styled(format!("{}{}", path, suffix), style.fg(palette().hint)),
+            ])
+        })
+        .collect();
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "j/k:Navigate  Enter:Open  Esc:Cancel",
+        Style::default().fg(palette().hint),
+    )));
+
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+pub fn draw_snippet_name_popup(f: &mut Frame, area: Rect, name: &str) {
+    let popup_area = centered_rect(50, 20, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(palette().accent))
+        .title(Span::styled(
+            " Save as Snippet ",
+            Style::default().fg(palette().accent).add_modifier(Modifier::BOLD),
+        ))
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(palette().popup_bg).fg(palette().popup_fg));
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(block, popup_area);
+
+    let inner = popup_area.inner(&Margin { vertical: 1, horizontal: 2 });
+    let lines = vec![
+        Line::from(vec![Span::styled("Name: ", Style::default().bold()), Span::raw(name), Span::raw("█")]),
+        Line::from(""),
+        Line::from(Span::styled("Enter:Save  Esc:Cancel", Style::default().fg(palette().hint))),
+    ];
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+pub fn draw_snippet_picker_popup(f: &mut Frame, area: Rect, picker: &SnippetPicker) {
+    let popup_area = centered_rect(60, 50, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(palette().accent))
+        .title(Span::styled(
+            " Snippets ",
+            Style::default().fg(palette().accent).add_modifier(Modifier::BOLD),
+        ))
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(palette().popup_bg).fg(palette().popup_fg));
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(block, popup_area);
+
+    let inner = popup_area.inner(&Margin { vertical: 1, horizontal: 2 });
+    let name_width = picker.snippets.iter().map(|s| s.name.chars().count()).max().unwrap_or(0);
+    // Keep the highlighted snippet in view, leaving room for the hint below the list.
+    let rows = (inner.height as usize).saturating_sub(2).max(1);
+    let first = (picker.index + 1).saturating_sub(rows);
+    let mut lines: Vec<Line> = picker
+        .snippets
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(rows)
+        .map(|(idx, snippet)| {
+            let selected = idx == picker.index;
+            let name_style = if selected { Style::default().fg(palette().accent).bold() } else { Style::default().bold() };
+            Line::from(vec![
+                Span::styled(format!("{}{:<width$}  ", if selected { "> " } else { "  " }, snippet.name, width = name_width), name_style),
+                Span::styled(
+                    crate::commands::snippet::summary(&snippet.content, snippet.encrypted).to_string(),
+                    Style::default().fg(palette().hint),
+                ),
+            ])
+        })
+        .collect();
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "j/k:Navigate  Enter:Copy  d:Delete  Esc:Close",
+        Style::default().fg(palette().hint),
+    )));
+
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+/// The transforms, with the first line of what the highlighted one makes of `entry`.
+pub fn draw_transform_picker_popup(f: &mut Frame, area: Rect, picker: &TransformPicker, entry: Option<&ClipboardEntry>) {
+    let popup_area = centered_rect(60, 60, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(palette().accent))
+        .title(Span::styled(
+            " Transform ",
+            Style::default().fg(palette().accent).add_modifier(Modifier::BOLD),
+        ))
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(palette().popup_bg).fg(palette().popup_fg));
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(block, popup_area);
+
+    let inner = popup_area.inner(&Margin { vertical: 1, horizontal: 2 });
+    // Room for the result and the hint below the list.
+    let rows = (inner.height as usize).saturating_sub(4).max(1);
+    let first = (picker.index + 1).saturating_sub(rows);
+    let mut lines: Vec<Line> = Transform::ALL
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(rows)
+        .map(|(idx, op)| {
+            let selected = idx == picker.index;
+            let style = if selected { Style::default().fg(palette().accent).bold() } else { Style::default() };
+            Line::from(Span::styled(format!("{}{}", if selected { "> " } else { "  " }, op.label()), style))
+        })
+        .collect();
+
+    let result = match entry.map(|e| picker.selected().apply(&e.content)) {
+        Some(Ok(text)) => format!("→ {}", text.lines().next().unwrap_or("")),
+        Some(Err(e)) => format!("✗ {}", e),
+        None => String::new(),
+    };
+    let width = inner.width as usize;
+    let result = if result.chars().count() > width {
+        format!("{}…", result.chars().take(width.saturating_sub(1)).collect::<String>())
+    } else {
+        result
+    };
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(result, Style::default().fg(palette().dim))));
+    lines.push(Line::from(Span::styled(
+        "j/k:Navigate  Enter:Copy  Esc:Close",
+        Style::default().fg(palette().hint),
+    )));
+
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+/// Every keybinding, grouped by the mode it applies in.
+const HELP_SECTIONS: &[(&str, &[(&str, &str)])] = &[
+    ("Normal", &[
+        ("j/k ↓/↑", "Move through the list"),
+        ("Enter", "Copy the entry and exit"),
+        ("/", "Search; lang:sql, :url and @app narrow by language, kind or source app"),
+        ("h/l ←/→", "Scroll the preview"),
+        ("PgUp/PgDn", "Scroll the preview by ten lines"),
+        ("r", "Reload the history"),
+        ("d", "Delete the entry"),
+        ("x Del", "Delete the entry after confirming"),
+        ("D Ctrl-D", "Delete everything copied within a period"),
+        ("t", "Save or unsave as a template"),
+        ("p", "Add to the paste queue for clippie pop"),
+        ("v", "Reveal or mask a sensitive entry"),
+        ("n", "Save as a named snippet"),
+        ("S", "Browse snippets"),
+        ("B", "Decode a JWT or base64 entry in the preview"),
+        ("o", "Open a URL in the browser"),
+        ("C", "Switch to the next color theme"),
+        ("Tab", "Show or hide the preview"),
+        ("z", "Zoom the preview to full screen, or back"),
+        ("f", "View the entry full screen with line numbers; / searches it, w wraps"),
+        ("< > Ctrl-←/→", "Shrink or grow the list"),
+        ("T", "Copy the entry transformed: case, base64, URL or JSON"),
+        ("i", "Pause or resume capture"),
+        ("b", "Switch database"),
+        ("Space", "Mark the entry for bulk actions"),
+        ("V", "Mark everything since the last marked entry"),
+        ("?", "This help"),
+        ("q Esc", "Clear the search, or quit"),
+        ("Ctrl-C", "Quit immediately"),
+    ]),
+    ("Search", &[
+        ("type", "Narrow the list as you type"),
+        ("Backspace", "Delete a character"),
+        ("Enter", "Keep the filter and return to the list"),
+        ("Esc", "Clear the filter"),
+    ]),
+    ("With marked entries", &[
+        ("Enter", "Copy the marked entries joined as one"),
+        ("s", "Cycle the separator: newline, space, none"),
+        ("d x Del", "Delete the marked entries"),
+        ("t", "Save or unsave them as templates"),
+        ("e", "Export them to JSON in the current directory"),
+        ("p", "Add them to the paste queue, top first"),
+        ("Esc", "Unmark all"),
+    ]),
+    ("Snippets", &[
+        ("j/k", "Move through the snippets"),
+        ("Enter", "Copy the snippet and exit"),
+        ("d Del", "Delete the snippet"),
+        ("Esc q S", "Close"),
+    ]),
+    ("Transform", &[
+        ("j/k", "Choose a transform"),
+        ("Enter", "Copy the result and exit"),
+        ("Esc q T", "Close"),
+    ]),
+    ("Delete", &[
+        ("j/k", "Choose a period"),
+        ("Enter", "Pick the period"),
+        ("y", "Confirm"),
+        ("n Esc", "Cancel"),
+    ]),
+];
+
+fn help_lines() -> Vec<Line<'static>> {
+    let key_width = HELP_SECTIONS.iter().flat_map(|(_, keys)| keys.iter()).map(|(key, _)| key.chars().count()).max().unwrap_or(0);
+    let mut lines = Vec::new();
+    for (title, keys) in HELP_SECTIONS {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(Span::styled(*title, Style::default().fg(palette().accent).bold())));
+        for (key, action) in keys.iter() {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {:<width$}  ", key, width = key_width), Style::default().bold()),
+                Span::raw(*action),
+            ]));
+        }
+    }
+    lines
+}
+
+/// Keybinding reference; clamps `scroll` to the last page.
+pub fn draw_help_popup(f: &mut Frame, area: Rect, scroll: &mut usize) {
+    let popup_area = centered_rect(70, 80, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(palette().accent))
+        .title(Span::styled(
+            " Keys ",
+            Style::default().fg(palette().accent).add_modifier(Modifier::BOLD),
+        ))
+        .title_bottom(Line::from(Span::styled(" j/k:Scroll  Esc:Close ", Style::default().fg(palette().hint))))
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(palette().popup_bg).fg(palette().popup_fg));
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(block, popup_area);
+
+    let inner = popup_area.inner(&Margin { vertical: 1, horizontal: 2 });
+    let lines = help_lines();
+    *scroll = (*scroll).min(lines.len().saturating_sub(inner.height as usize));
+    f.render_widget(Paragraph::new(lines).scroll((*scroll as u16, 0)), inner);
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -609,13 +1098,13 @@ pub fn draw_delete_period_popup(
     let block = Block::default()
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .border_style(Style::default().fg(ACCENT))
+        .border_style(Style::default().fg(palette().accent))
         .title(Span::styled(
             " Delete History ",
-            Style::default().fg(ACCENT).add_modifier(Modifier::BOLD),
+            Style::default().fg(palette().accent).add_modifier(Modifier::BOLD),
         ))
         .title_alignment(Alignment::Center)
-        .style(Style::default().bg(Color::Black).fg(Color::White));
+        .style(Style::default().bg(palette().popup_bg).fg(palette().popup_fg));
 
     f.render_widget(Clear, popup_area);
     f.render_widget(block, popup_area);
@@ -623,7 +1112,7 @@ pub fn draw_delete_period_popup(
     // Content area (inside border)
     let inner = popup_area.inner(&Margin { vertical: 2, horizontal: 2 });
 
-    let periods = vec![
+    let periods = [
         ("Last Hour", "Delete entries from the past hour"),
         ("Last Day", "Delete entries from the past 24 hours"),
         ("Last Week", "Delete entries from the past 7 days"),
@@ -697,13 +1186,13 @@ pub fn draw_delete_confirmation_popup(
     let block = Block::default()
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .border_style(Style::default().fg(Color::Rgb(180, 60, 60)))
+        .border_style(Style::default().fg(palette().danger))
         .title(Span::styled(
             title,
-            Style::default().fg(Color::Rgb(180, 60, 60)).add_modifier(Modifier::BOLD),
+            Style::default().fg(palette().danger).add_modifier(Modifier::BOLD),
         ))
         .title_alignment(Alignment::Center)
-        .style(Style::default().bg(Color::Black).fg(Color::White));
+        .style(Style::default().bg(palette().popup_bg).fg(palette().popup_fg));
 
     f.render_widget(Clear, popup_area);
     f.render_widget(block, popup_area);
@@ -767,23 +1256,24 @@ pub fn draw_single_delete_confirmation_popup(
     let block = Block::default()
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .border_style(Style::default().fg(ACCENT))
+        .border_style(Style::default().fg(palette().accent))
         .title(Span::styled(
             " Delete Entry ",
-            Style::default().fg(ACCENT).add_modifier(Modifier::BOLD),
+            Style::default().fg(palette().accent).add_modifier(Modifier::BOLD),
         ))
         .title_alignment(Alignment::Center)
-        .style(Style::default().bg(Color::Black).fg(Color::White));
+        .style(Style::default().bg(palette().popup_bg).fg(palette().popup_fg));
 
     f.render_widget(Clear, popup_area);
     f.render_widget(block, popup_area);
 
     let inner = popup_area.inner(&Margin { vertical: 2, horizontal: 2 });
 
-    let preview = if entry.content.len() > 100 {
-        format!("{}...", &entry.content[..100])
+    let content = entry.display_content();
+    let preview = if content.len() > 100 {
+        format!("{}...", &content[..100])
     } else {
-        entry.content.clone()
+        content.to_string()
     }.replace('\n', "↵");
 
     let lines = vec![
@@ -810,6 +1300,41 @@ pub fn draw_single_delete_confirmation_popup(
     f.render_widget(paragraph, inner);
 }
 
+pub fn draw_marked_delete_confirmation_popup(f: &mut Frame, area: Rect, count: usize) {
+    let popup_area = centered_rect(50, 25, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(palette().accent))
+        .title(Span::styled(
+            " Delete Selected ",
+            Style::default().fg(palette().accent).add_modifier(Modifier::BOLD),
+        ))
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(palette().popup_bg).fg(palette().popup_fg));
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(block, popup_area);
+
+    let inner = popup_area.inner(&Margin { vertical: 2, horizontal: 2 });
+    let lines = vec![
+        Line::from(Span::styled(
+            format!("Delete {} selected {}?", count, if count == 1 { "entry" } else { "entries" }),
+            Style::default().bold(),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("y", Style::default().fg(Color::Red).bold()),
+            Span::raw(" delete  "),
+            Span::styled("n", Style::default().fg(Color::Green).bold()),
+            Span::raw(" cancel"),
+        ]),
+    ];
+
+    f.render_widget(Paragraph::new(lines).alignment(Alignment::Center), inner);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -839,6 +1364,60 @@ mod tests {
         assert!(matches!(patterns[0].2, PatternType::Url));
     }
 
+    #[test]
+    fn test_find_highlights_colors() {
+        let text = "color: #ff8800; see https://a.io/#abc";
+        let found: Vec<_> = find_highlights(text, None).into_iter().map(|(s, e, t)| (&text[s..e], t)).collect();
+        assert!(found.contains(&("#ff8800", PatternType::Color(255, 136, 0))), "{:?}", found);
+        assert!(!found.iter().any(|(s, _)| *s == "#abc"));
+        assert_eq!(color_swatch(color::parse("#ff8800").unwrap()).len(), 4);
+    }
+
+    #[test]
+    fn test_find_patterns_dispatch_and_overlap() {
+        let patterns = find_patterns("token: abc 10.0.0.1 mail me@x.io at https://a.io/x@y.com");
+        let kinds: Vec<PatternType> = patterns.iter().map(|p| p.2).collect();
+        assert_eq!(kinds, [PatternType::Secret, PatternType::Ip, PatternType::Email, PatternType::Url]);
+        assert!(find_patterns("nothing to see here").is_empty());
+    }
+
+    #[test]
+    fn test_preview_cache_rewraps_only_on_change() {
+        let entry = ClipboardEntry {
+            id: 1,
+            content: "alpha beta\ngamma".to_string(),
+            created_at: Utc::now(),
+            last_copied: Utc::now(),
+            encrypted: false,
+            template: false,
+            language: None,
+            source_app: None,
+            sensitive: false,
+            kind: Kind::Text,
+            title: None,
+            truncated: false,
+        };
+        let mut cache = PreviewCache::default();
+
+        let (lines, first_match) = cache.layout(&entry, 40, "gam", false);
+        assert_eq!(lines, ["alpha beta", "gamma"]);
+        assert_eq!(first_match, Some(1));
+
+        let (lines, first_match) = cache.layout(&entry, 5, "", false);
+        assert_eq!(lines, ["alpha", "beta", "gamma"]);
+        assert_eq!(first_match, None);
+    }
+
+    #[test]
+    fn test_find_match_ranges_unicode() {
+        assert_eq!(find_match_ranges("Hello hello", "HELLO"), vec![0..5, 6..11]);
+        // 'İ' lowercases to two chars; the range must still cover exactly its bytes.
+        assert_eq!(find_match_ranges("xİy", "i\u{307}y"), vec![1..4]);
+        // A query must not match half of a combining sequence.
+        assert!(find_match_ranges("cafe\u{301}", "cafe").is_empty());
+        assert_eq!(find_match_ranges("cafe\u{301}!", "cafe\u{301}"), vec![0..6]);
+    }
+
     #[test]
     fn test_wrap_text() {
         let wrapped = wrap_text("hello world test", 10);
@@ -847,25 +1426,25 @@ mod tests {
 
     #[test]
     fn test_highlight_search() {
-        let spans = highlight_search("Hello World", "world");
+        let spans = highlight_search("Hello World", "world", &[]);
         assert_eq!(spans.len(), 2);
     }
 
     #[test]
     fn test_highlight_search_unicode() {
-        let spans = highlight_search("Héllo Wörld", "wörld");
+        let spans = highlight_search("Héllo Wörld", "wörld", &[]);
         assert_eq!(spans.len(), 2);
     }
 
     #[test]
     fn test_highlight_search_empty_text() {
-        let spans = highlight_search("", "query");
+        let spans = highlight_search("", "query", &[]);
         assert_eq!(spans.len(), 1);
     }
 
     #[test]
     fn test_highlight_search_query_longer_than_text() {
-        let spans = highlight_search("ab", "abcdef");
+        let spans = highlight_search("ab", "abcdef", &[]);
         assert_eq!(spans.len(), 1);
     }
 }
@@ -0,0 +1,126 @@
+//! Decoded views of JWTs and base64 for the preview, toggled with `B`.
+
+use crate::transform::{decode_base64, reformat_json};
+use chrono::{DateTime, Local, Utc};
+
+/// Shortest base64 that may be binary; shorter runs of letters are more often just words.
+const MIN_BINARY_CHARS: usize = 24;
+/// Bytes of binary data shown as hex.
+const HEX_DUMP_BYTES: usize = 256;
+
+/// What `text` decodes to: `("JWT" or "base64", lines to show)`. `None` when it's neither.
+pub fn decoded_view(text: &str) -> Option<(&'static str, String)> {
+    let text = text.trim();
+    jwt_view(text).map(|view| ("JWT", view)).or_else(|| base64_view(text).map(|view| ("base64", view)))
+}
+
+/// Header and payload pretty-printed, with the time claims as local dates. The signature isn't checked.
+fn jwt_view(token: &str) -> Option<String> {
+    let mut parts = token.split('.');
+    let (header, payload, signature) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    let header = json_segment(header)?;
+    let payload = json_segment(payload)?;
+    let mut view = format!("Header\n{}\n\nPayload\n{}", reformat_json(&header, true).ok()?, reformat_json(&payload, true).ok()?);
+
+    let claims: serde_json::Value = serde_json::from_str(&payload).ok()?;
+    let times: Vec<String> = [("iat", "Issued"), ("nbf", "Not before"), ("exp", "Expires")]
+        .iter()
+        .filter_map(|(claim, name)| {
+            let time = DateTime::<Utc>::from_timestamp(claims.get(claim)?.as_i64()?, 0)?;
+            let expired = *claim == "exp" && time < Utc::now();
+            let date = time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S");
+            Some(format!("{:<10}  {}{}", name, date, if expired { " (expired)" } else { "" }))
+        })
+        .collect();
+    if !times.is_empty() {
+        view.push_str("\n\n");
+        view.push_str(&times.join("\n"));
+    }
+    view.push_str(if signature.is_empty() { "\n\nUnsigned" } else { "\n\nSignature not verified" });
+    Some(view)
+}
+
+/// A JWT segment holding a JSON object.
+fn json_segment(segment: &str) -> Option<String> {
+    if segment.is_empty() || !segment.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_') {
+        return None;
+    }
+    let json = String::from_utf8(decode_base64(segment)?).ok()?;
+    serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&json).ok()?;
+    Some(json)
+}
+
+/// Decoded text, pretty-printed when it's JSON, or a hex dump of binary data.
+fn base64_view(text: &str) -> Option<String> {
+    let is_base64 = |c: char| c.is_ascii_alphanumeric() || "+/-_=".contains(c) || c.is_whitespace();
+    if text.len() < 8 || !text.chars().all(is_base64) {
+        return None;
+    }
+    let bytes = decode_base64(text)?;
+    match std::str::from_utf8(&bytes) {
+        Ok(decoded) if decoded.chars().all(|c| !c.is_control() || c.is_whitespace()) => {
+            let shown = reformat_json(decoded, true).unwrap_or_else(|_| decoded.to_string());
+            Some(format!("{} bytes of text\n\n{}", bytes.len(), shown))
+        }
+        // Anything of letters alone decodes to something; only longer runs with digits or
+        // symbols are likely encoded data.
+        _ if text.len() >= MIN_BINARY_CHARS && text.chars().any(|c| !c.is_ascii_alphabetic()) => {
+            let mut view = format!("{} bytes of binary data\n", bytes.len());
+            for (i, row) in bytes.chunks(16).take(HEX_DUMP_BYTES / 16).enumerate() {
+                let hex: Vec<String> = row.iter().map(|b| format!("{:02x}", b)).collect();
+                let ascii: String = row.iter().map(|&b| if b.is_ascii_graphic() { b as char } else { '.' }).collect();
+                view.push_str(&format!("\n{:08x}  {:<47}  {}", i * 16, hex.join(" "), ascii));
+            }
+            if bytes.len() > HEX_DUMP_BYTES {
+                view.push_str("\n…");
+            }
+            Some(view)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+
+    fn jwt(header: &str, payload: &str, signature: &str) -> String {
+        format!("{}.{}.{}", URL_SAFE_NO_PAD.encode(header), URL_SAFE_NO_PAD.encode(payload), signature)
+    }
+
+    #[test]
+    fn test_jwt_view() {
+        let token = jwt(r#"{"alg":"HS256","typ":"JWT"}"#, r#"{"sub":"ann","exp":100000}"#, "c2ln");
+        let (label, view) = decoded_view(&format!("{}\n", token)).unwrap();
+        assert_eq!(label, "JWT");
+        assert!(view.starts_with("Header\n{\n  \"alg\": \"HS256\",\n  \"typ\": \"JWT\"\n}\n\nPayload\n{\n  \"sub\": \"ann\","), "{}", view);
+        assert!(view.contains("Expires     1970-"), "{}", view);
+        assert!(view.contains("(expired)\n\nSignature not verified"), "{}", view);
+
+        let unsigned = jwt(r#"{"alg":"none"}"#, r#"{"sub":"ann"}"#, "");
+        assert!(decoded_view(&unsigned).unwrap().1.ends_with("}\n\nUnsigned"));
+        assert_eq!(decoded_view(&jwt("{}", "not json", "x")).map(|(label, _)| label), None);
+        assert_eq!(decoded_view("example.com.au").map(|(label, _)| label), None);
+    }
+
+    #[test]
+    fn test_base64_view() {
+        let (label, view) = decoded_view(&STANDARD.encode(r#"{"a":[1]}"#)).unwrap();
+        assert_eq!(label, "base64");
+        assert_eq!(view, "9 bytes of text\n\n{\n  \"a\": [\n    1\n  ]\n}");
+        assert_eq!(decoded_view("aGVsbG8g\nd29ybGQ=").unwrap().1, "11 bytes of text\n\nhello world");
+
+        let binary = decoded_view(&STANDARD.encode([0u8, 1, 2, 255, 65, 66, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 9, 9])).unwrap().1;
+        assert!(binary.starts_with("18 bytes of binary data\n\n00000000  00 01 02 ff 41 42"), "{}", binary);
+        assert!(binary.ends_with(&format!("\n00000010  {:<47}  ..", "09 09")), "{}", binary);
+
+        assert!(decoded_view("password").is_none());
+        assert!(decoded_view("hello world").is_none());
+        assert!(decoded_view("abc").is_none());
+    }
+}
@@ -1,10 +1,16 @@
+use crate::db::{ClipboardEntry, Database};
+use crate::ipc::Request;
+use crate::tui::app::LIST_PREFIX_CHARS;
 use crossterm::event::{self, Event as CrosstermEvent, KeyEvent, MouseEvent};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
 };
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
 #[derive(Clone, Debug)]
@@ -14,58 +20,129 @@ pub enum Event {
     #[allow(dead_code)]
     Mouse(MouseEvent),
     Resize(u16, u16),
+    /// A batch of history rows read by the background loader started in a load generation
+    Entries(u64, Vec<ClipboardEntry>),
+    /// The background loader of a load generation finished (or failed with a message)
+    EntriesLoaded(u64, Option<String>),
+    /// The daemon saved new clipboard entries
+    Captured,
 }
 
+/// Rows per `Event::Entries`.
+const LOAD_BATCH_SIZE: usize = 500;
+
+/// How long the input thread blocks before re-checking `stop` and whether a tick is due.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How often to retry the daemon's control socket while it's down.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
 pub struct EventHandler {
     rx: mpsc::UnboundedReceiver<Event>,
-    #[allow(dead_code)]
     tx: mpsc::UnboundedSender<Event>,
     stop: Arc<AtomicBool>,
+    ticking: Arc<AtomicBool>,
+    input_thread: Option<thread::JoinHandle<()>>,
 }
 
 impl EventHandler {
-    pub fn new() -> Self {
+    /// Forward terminal input, plus a `Tick` every `tick_rate` while ticking is enabled.
+    pub fn new(tick_rate: Duration) -> Self {
         let (tx, rx) = mpsc::unbounded_channel();
         let stop = Arc::new(AtomicBool::new(false));
+        let ticking = Arc::new(AtomicBool::new(true));
         let stop_clone = Arc::clone(&stop);
+        let ticking_clone = Arc::clone(&ticking);
         let tx_clone = tx.clone();
 
-        thread::spawn(move || {
+        let input_thread = thread::spawn(move || {
+            let mut last_tick = Instant::now();
             while !stop_clone.load(Ordering::Relaxed) {
-                if event::poll(Duration::from_millis(100)).unwrap_or(false) {
-                    if let Ok(event) = event::read() {
-                        let msg = match event {
-                            CrosstermEvent::Key(key) => Some(Event::Key(key)),
-                            CrosstermEvent::Mouse(mouse) => Some(Event::Mouse(mouse)),
-                            CrosstermEvent::Resize(w, h) => Some(Event::Resize(w, h)),
-                            _ => None,
-                        };
-                        if let Some(e) = msg {
-                            let _ = tx_clone.send(e);
-                        }
+                if event::poll(POLL_INTERVAL).unwrap_or(false)
+                    && let Ok(event) = event::read()
+                {
+                    let msg = match event {
+                        CrosstermEvent::Key(key) => Some(Event::Key(key)),
+                        CrosstermEvent::Mouse(mouse) => Some(Event::Mouse(mouse)),
+                        CrosstermEvent::Resize(w, h) => Some(Event::Resize(w, h)),
+                        _ => None,
+                    };
+                    if let Some(e) = msg {
+                        let _ = tx_clone.send(e);
                     }
                 }
-                if !stop_clone.load(Ordering::Relaxed) {
+                if ticking_clone.load(Ordering::Relaxed) && last_tick.elapsed() >= tick_rate {
+                    last_tick = Instant::now();
                     let _ = tx_clone.send(Event::Tick);
                 }
             }
         });
 
-        EventHandler { rx, tx, stop }
+        EventHandler { rx, tx, stop, ticking, input_thread: Some(input_thread) }
+    }
+
+    /// Turn ticks on or off; with them off the loop only wakes for input and loader events.
+    pub fn set_ticking(&self, enabled: bool) {
+        self.ticking.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Read the history past the `(last_copied, id)` cursor `before` in a background thread, so
+    /// a search over rows not yet paged in doesn't freeze the screen. Its events carry
+    /// `generation`, so ones from a load the list was reloaded over can be told apart.
+    pub fn load_entries(&self, db_path: &str, before: Option<(i64, i64)>, generation: u64) {
+        let tx = self.tx.clone();
+        let db_path = db_path.to_string();
+        thread::spawn(move || {
+            let result = Database::open(&db_path).and_then(|db| {
+                db.stream_entry_summaries(LIST_PREFIX_CHARS, before, LOAD_BATCH_SIZE, |batch| {
+                    let _ = tx.send(Event::Entries(generation, batch));
+                })
+            });
+            let _ = tx.send(Event::EntriesLoaded(generation, result.err().map(|e| e.to_string())));
+        });
+    }
+
+    /// Forward the daemon's capture announcements, reconnecting whenever it restarts.
+    pub fn subscribe(&self, socket_path: PathBuf) {
+        let tx = self.tx.clone();
+        let stop = Arc::clone(&self.stop);
+        thread::spawn(move || {
+            let request = serde_json::to_string(&Request::Subscribe).unwrap_or_default();
+            while !stop.load(Ordering::Relaxed) {
+                if let Ok(mut stream) = UnixStream::connect(&socket_path)
+                    && writeln!(stream, "{}", request).is_ok()
+                {
+                    // The first line acknowledges the subscription.
+                    for line in BufReader::new(stream).lines().skip(1) {
+                        if line.is_err() || tx.send(Event::Captured).is_err() {
+                            break;
+                        }
+                    }
+                }
+                if tx.is_closed() {
+                    return;
+                }
+                thread::sleep(RECONNECT_INTERVAL);
+            }
+        });
     }
 
     pub async fn next(&mut self) -> Option<Event> {
         self.rx.recv().await
     }
 
-    pub fn stop(&self) {
+    /// Stop the input thread and wait for it, so it can't swallow input meant for the shell.
+    pub fn stop(&mut self) {
         self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.input_thread.take() {
+            let _ = handle.join();
+        }
     }
 }
 
-impl Default for EventHandler {
-    fn default() -> Self {
-        Self::new()
+impl Drop for EventHandler {
+    fn drop(&mut self) {
+        self.stop();
     }
 }
 
@@ -75,7 +152,8 @@ mod tests {
 
     #[test]
     fn test_event_handler_creation() {
-        let handler = EventHandler::new();
+        let mut handler = EventHandler::new(Duration::from_millis(10));
+        handler.set_ticking(false);
         handler.stop();
     }
 }
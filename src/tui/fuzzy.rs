@@ -1,3 +1,11 @@
+use crate::db::ClipboardEntry;
+use crate::kind::Kind;
+use rayon::prelude::*;
+use std::collections::HashSet;
+
+/// Below this many entries, spreading the work over threads costs more than it saves.
+const PARALLEL_THRESHOLD: usize = 2_000;
+
 #[derive(Debug, Clone)]
 pub struct FuzzyMatch {
     pub matched: bool,
@@ -51,14 +59,99 @@ pub fn fuzzy_match(text: &str, query: &str) -> FuzzyMatch {
     }
 }
 
+/// A search query split into `lang:`, `:kind` and `@app` filters and the text to fuzzy-match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query<'a> {
+    pub text: String,
+    pub language: Option<&'a str>,
+    /// `:url`, `:path` and the like; words after `:` that aren't a kind stay search text.
+    pub kind: Option<Kind>,
+    /// Part of the source app's name, e.g. `@term` for Terminal.
+    pub app: Option<&'a str>,
+}
+
+fn kind_filter(word: &str) -> Option<Kind> {
+    word.strip_prefix(':').and_then(Kind::parse)
+}
+
+fn is_filter(word: &str) -> bool {
+    word.starts_with("lang:") || kind_filter(word).is_some() || (word.len() > 1 && word.starts_with('@'))
+}
+
+/// `lang:sql docker` keeps SQL entries that fuzzy-match "docker"; `:url` keeps URLs; `@slack`
+/// keeps entries copied in Slack.
+pub fn parse_query(query: &str) -> Query<'_> {
+    let language = query.split_whitespace().find_map(|word| word.strip_prefix("lang:"));
+    let kind = query.split_whitespace().find_map(kind_filter);
+    let app = query.split_whitespace().filter(|word| is_filter(word)).find_map(|word| word.strip_prefix('@'));
+    if language.is_none() && kind.is_none() && app.is_none() {
+        return Query { text: query.to_string(), language, kind, app };
+    }
+    let text = query.split_whitespace().filter(|word| !is_filter(word)).collect::<Vec<_>>().join(" ");
+    Query { text, language, kind, app }
+}
+
+/// Entries matching `query`, exact substring matches first, otherwise in input order.
+pub fn filter_entries<'a>(entries: &'a [ClipboardEntry], query: &str) -> Vec<&'a ClipboardEntry> {
+    filter_indices(entries, query, None).into_iter().map(|i| &entries[i]).collect()
+}
+
+/// Like `filter_entries`, but returns positions into `entries` so callers can cache them.
+///
+/// `candidates` are ids from `Database::search`: only those entries are considered, and since
+/// they contain the query somewhere in their full content they match even when the
+/// in-memory prefix doesn't. Sensitive entries aren't indexed, so they are always matched.
+pub fn filter_indices(entries: &[ClipboardEntry], query: &str, candidates: Option<&HashSet<i64>>) -> Vec<usize> {
+    let query = parse_query(query);
+    let matcher = |(i, e): (usize, &ClipboardEntry)| {
+        let candidate = candidates.filter(|_| !e.sensitive).map(|ids| ids.contains(&e.id));
+        if candidate == Some(false) {
+            return None;
+        }
+        if let Some(language) = query.language
+            && !e.language.as_deref().is_some_and(|l| l.eq_ignore_ascii_case(language))
+        {
+            return None;
+        }
+        if query.kind.is_some_and(|kind| e.kind != kind) {
+            return None;
+        }
+        if let Some(app) = query.app
+            && !e.source_app.as_deref().is_some_and(|name| name.to_lowercase().contains(&app.to_lowercase()))
+        {
+            return None;
+        }
+        if query.text.is_empty() {
+            return Some((true, i));
+        }
+        // A fetched page title is searched too, since the list shows it in place of the URL.
+        let mut result = fuzzy_match(e.display_content(), &query.text);
+        if !result.matched
+            && let Some(title) = &e.title
+        {
+            result = fuzzy_match(title, &query.text);
+        }
+        (result.matched || candidate == Some(true)).then_some((result.is_exact, i))
+    };
+
+    // Indexed parallel collect keeps input order, so both paths rank identically.
+    let mut matches: Vec<(bool, usize)> = if entries.len() >= PARALLEL_THRESHOLD {
+        entries.par_iter().enumerate().filter_map(matcher).collect()
+    } else {
+        entries.iter().enumerate().filter_map(matcher).collect()
+    };
+    matches.sort_by_key(|(exact, _)| !exact);
+    matches.into_iter().map(|(_, i)| i).collect()
+}
+
 fn merge_adjacent_positions(positions: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
     let mut merged: Vec<(usize, usize)> = Vec::new();
     for (pos, len) in positions {
-        if let Some(last) = merged.last_mut() {
-            if last.0 + last.1 == pos {
-                last.1 += len;
-                continue;
-            }
+        if let Some(last) = merged.last_mut()
+            && last.0 + last.1 == pos
+        {
+            last.1 += len;
+            continue;
         }
         merged.push((pos, len));
     }
@@ -69,6 +162,24 @@ fn merge_adjacent_positions(positions: Vec<(usize, usize)>) -> Vec<(usize, usize
 mod tests {
     use super::*;
 
+    fn entry(id: i64, content: &str) -> ClipboardEntry {
+        let now = chrono::Utc::now();
+        ClipboardEntry {
+            id,
+            content: content.to_string(),
+            created_at: now,
+            last_copied: now,
+            encrypted: false,
+            template: false,
+            language: None,
+            source_app: None,
+            sensitive: false,
+            kind: Kind::Text,
+            title: None,
+            truncated: false,
+        }
+    }
+
     #[test]
     fn test_exact_match() {
         let result = fuzzy_match("hello world", "world");
@@ -105,4 +216,75 @@ mod tests {
         );
         assert!(result.matched);
     }
+
+    #[test]
+    fn test_filter_entries_ranks_exact_first() {
+        let entries = vec![entry(1, "g-i-t"), entry(2, "nothing"), entry(3, "git")];
+        let ids: Vec<i64> = filter_entries(&entries, "git").iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![3, 1]);
+    }
+
+    #[test]
+    fn test_language_filter() {
+        let sql = |entry| ClipboardEntry { language: Some("sql".to_string()), ..entry };
+        let entries = vec![sql(entry(1, "select * from users")), entry(2, "users.json"), sql(entry(3, "select 1"))];
+        let ids = |query| filter_entries(&entries, query).iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(ids("lang:sql"), vec![1, 3]);
+        assert_eq!(ids("users lang:SQL"), vec![1]);
+        assert_eq!(parse_query("docker "), Query { text: "docker ".to_string(), language: None, kind: None, app: None });
+    }
+
+    #[test]
+    fn test_kind_filter() {
+        let classified = |id, content| ClipboardEntry { kind: crate::kind::classify(content, None), ..entry(id, content) };
+        let entries = vec![
+            classified(1, "https://example.com/docs"),
+            classified(2, "/usr/share/docs"),
+            classified(3, "https://example.org"),
+        ];
+        let ids = |query| filter_entries(&entries, query).iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(ids(":url"), vec![1, 3]);
+        assert_eq!(ids("docs :URL"), vec![1]);
+        assert_eq!(ids(":file"), vec![2]);
+        // Not a kind, so it's searched for like any other text.
+        assert_eq!(parse_query(":wq").text, ":wq");
+    }
+
+    #[test]
+    fn test_app_filter() {
+        let from = |app: &str, entry| ClipboardEntry { source_app: Some(app.to_string()), ..entry };
+        let entries = vec![from("Terminal", entry(1, "ls -la")), from("Slack", entry(2, "meeting notes")), entry(3, "ls")];
+        let ids = |query| filter_entries(&entries, query).iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(ids("@term"), vec![1]);
+        assert_eq!(ids("ls @SLACK"), Vec::<i64>::new());
+        assert_eq!(ids("user@host"), Vec::<i64>::new());
+        assert_eq!(parse_query("ls @ x").app, None);
+    }
+
+    #[test]
+    fn test_candidates_limit_and_extend_matches() {
+        let prefix = |id, content| ClipboardEntry { truncated: true, ..entry(id, content) };
+        // Entry 2's match lies past its loaded prefix; entry 3 only matches as a subsequence.
+        let entries = vec![prefix(1, "kubectl get pods"), prefix(2, "long log output…"), prefix(3, "k-u-b-e")];
+        let candidates = HashSet::from([1, 2]);
+        assert_eq!(filter_indices(&entries, "kube", Some(&candidates)), vec![0, 1]);
+        assert_eq!(filter_indices(&entries, "kube", None), vec![0, 2]);
+
+        // Sensitive entries are left out of the index but still match on their own text.
+        let entries = vec![entry(1, "kube token"), ClipboardEntry { sensitive: true, ..entry(2, "kube password=x") }];
+        assert_eq!(filter_indices(&entries, "kube", Some(&HashSet::from([1]))), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_filter_indices_parallel_keeps_order() {
+        let entries: Vec<ClipboardEntry> = (0..PARALLEL_THRESHOLD * 2)
+            .map(|i| entry(i as i64, &if i % 3 == 0 { format!("git {}", i) } else { format!("g-i-t {}", i) }))
+            .collect();
+
+        let indices = filter_indices(&entries, "git", None);
+        assert_eq!(indices.len(), entries.len());
+        let split = indices.iter().position(|i| i % 3 != 0).unwrap();
+        assert!(indices[..split].windows(2).all(|w| w[0] < w[1] && w[0] % 3 == 0));
+        assert!(indices[split..].windows(2).all(|w| w[0] < w[1]));
+    }
 }
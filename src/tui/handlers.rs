@@ -1,6 +1,10 @@
 use super::app::{App, DeleteMode, DeletePeriod};
 use super::events::Event;
+use super::lock::LockOutcome;
+use super::template::FormOutcome;
+use super::viewer::ViewerOutcome;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::config::ConfigManager;
 use crate::db::Database;
 
 pub struct EventHandler;
@@ -8,32 +12,151 @@ pub struct EventHandler;
 impl EventHandler {
     pub fn handle(event: &Event, app: &mut App) -> bool {
         match event {
-            Event::Key(key) => Self::handle_key(*key, app),
+            Event::Key(key) => {
+                app.needs_redraw = true;
+                Self::handle_key(*key, app)
+            }
             Event::Mouse(_) => false,
             Event::Resize(w, h) => {
                 app.update_terminal_size(*w as usize, *h as usize);
+                app.needs_redraw = true;
                 false
             }
             Event::Tick => {
                 app.on_tick();
                 false
             }
+            Event::Entries(generation, batch) => {
+                if *generation == app.load_generation {
+                    app.append_entries(batch.clone());
+                }
+                false
+            }
+            Event::Captured => {
+                if let Err(e) = app.load_new_entries() {
+                    app.show_message(format!("Refresh failed: {}", e));
+                }
+                false
+            }
+            Event::EntriesLoaded(generation, _) if *generation != app.load_generation => false,
+            Event::EntriesLoaded(_, error) => {
+                app.loading = false;
+                app.all_loaded = error.is_none();
+                app.needs_redraw = true;
+                if let Some(e) = error {
+                    app.show_message(format!("Loading failed: {}", e));
+                }
+                false
+            }
         }
     }
 
     fn handle_key(key: KeyEvent, app: &mut App) -> bool {
+        if app.is_locked() {
+            return Self::handle_lock(key, app);
+        }
+        if let Some(lock) = &mut app.lock {
+            lock.touch();
+        }
+
         if app.confirm_quit {
             return Self::handle_confirm_quit(key, app);
         }
 
+        if let Some(scroll) = app.help_scroll.as_mut() {
+            match key.code {
+                KeyCode::Up | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => *scroll += 1,
+                KeyCode::PageUp => *scroll = scroll.saturating_sub(10),
+                KeyCode::PageDown => *scroll += 10,
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('?') => app.help_scroll = None,
+                _ => {}
+            }
+            return false;
+        }
+
+        if let Some(viewer) = app.viewer.as_mut() {
+            if viewer.handle_key(key) == ViewerOutcome::Close {
+                app.viewer = None;
+            }
+            return false;
+        }
+
         if app.is_in_delete_mode() {
             return Self::handle_delete_mode(key, app);
         }
 
+        if app.db_picker.is_some() {
+            return Self::handle_db_picker(key, app);
+        }
+
+        if let Some(name) = app.snippet_name.as_mut() {
+            match key.code {
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => name.push(c),
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Enter => {
+                    if let Err(e) = app.save_snippet() {
+                        app.show_message(format!("Snippet not saved: {}", e));
+                    }
+                }
+                KeyCode::Esc => app.snippet_name = None,
+                _ => {}
+            }
+            return false;
+        }
+
+        if let Some(picker) = app.snippet_picker.as_mut() {
+            match key.code {
+                KeyCode::Up | KeyCode::Char('k') => picker.up(),
+                KeyCode::Down | KeyCode::Char('j') => picker.down(),
+                KeyCode::Enter => return app.select_snippet(),
+                KeyCode::Char('d') | KeyCode::Delete => {
+                    if let Err(e) = app.delete_snippet() {
+                        app.show_message(format!("Delete failed: {}", e));
+                    }
+                }
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('S') => app.snippet_picker = None,
+                _ => {}
+            }
+            return false;
+        }
+
+        if let Some(picker) = app.transform_picker.as_mut() {
+            match key.code {
+                KeyCode::Up | KeyCode::Char('k') => picker.up(),
+                KeyCode::Down | KeyCode::Char('j') => picker.down(),
+                KeyCode::Enter => return app.select_transform(),
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('T') => app.transform_picker = None,
+                _ => {}
+            }
+            return false;
+        }
+
+        if let Some(form) = app.template_form.as_mut() {
+            return match form.handle_key(key) {
+                FormOutcome::Editing => false,
+                FormOutcome::Cancel => {
+                    app.template_form = None;
+                    false
+                }
+                FormOutcome::Done => {
+                    app.finish_template();
+                    true
+                }
+            };
+        }
+
         if app.is_filtering {
             return Self::handle_filter_mode(key, app);
         }
 
+        let key = app.keymap.translate(key);
+        if !app.marked.is_empty() && let Some(quit) = Self::handle_marked(key, app) {
+            return quit;
+        }
+
         match key.code {
             KeyCode::Up | KeyCode::Char('k') if key.modifiers == KeyModifiers::NONE => {
                 app.select_up();
@@ -43,10 +166,11 @@ impl EventHandler {
                 app.select_down();
                 false
             }
-            KeyCode::Enter => {
-                app.select_entry();
-                true
+            KeyCode::Enter if app.zoomed => {
+                app.open_viewer();
+                false
             }
+            KeyCode::Enter => app.select_entry(),
             KeyCode::Char('/') if key.modifiers == KeyModifiers::NONE => {
                 app.start_filtering();
                 false
@@ -117,10 +241,236 @@ impl EventHandler {
                 app.start_bulk_delete();
                 false
             }
+            KeyCode::Char('b') if key.modifiers == KeyModifiers::NONE => {
+                Self::open_db_picker(app);
+                false
+            }
+            KeyCode::Char('i') if key.modifiers == KeyModifiers::NONE => {
+                if let Err(e) = app.toggle_pause() {
+                    app.show_message(format!("Pause failed: {}", e));
+                }
+                false
+            }
+            KeyCode::Char(' ') if key.modifiers == KeyModifiers::NONE => {
+                app.toggle_mark();
+                false
+            }
+            KeyCode::Char('V') => {
+                app.mark_range();
+                false
+            }
+            KeyCode::Char('?') => {
+                app.help_scroll = Some(0);
+                false
+            }
+            KeyCode::Char('v') if key.modifiers == KeyModifiers::NONE => {
+                app.toggle_reveal();
+                false
+            }
+            KeyCode::Char('n') if key.modifiers == KeyModifiers::NONE => {
+                app.start_snippet_name();
+                false
+            }
+            KeyCode::Char('B') => {
+                app.toggle_decoded();
+                false
+            }
+            KeyCode::Char('o') => {
+                app.open_url();
+                false
+            }
+            KeyCode::Char('C') => {
+                app.cycle_theme();
+                false
+            }
+            KeyCode::Tab => {
+                app.toggle_preview();
+                false
+            }
+            KeyCode::Char('z') => {
+                app.toggle_zoom();
+                false
+            }
+            KeyCode::Char('f') => {
+                app.open_viewer();
+                false
+            }
+            KeyCode::Char('<') => {
+                Self::resize_list(app, -1);
+                false
+            }
+            KeyCode::Char('>') => {
+                Self::resize_list(app, 1);
+                false
+            }
+            KeyCode::Left if key.modifiers == KeyModifiers::CONTROL => {
+                Self::resize_list(app, -1);
+                false
+            }
+            KeyCode::Right if key.modifiers == KeyModifiers::CONTROL => {
+                Self::resize_list(app, 1);
+                false
+            }
+            KeyCode::Char('T') => {
+                app.open_transforms();
+                false
+            }
+            KeyCode::Char('S') => {
+                if let Err(e) = app.open_snippets() {
+                    app.show_message(format!("Snippets failed: {}", e));
+                }
+                false
+            }
+            KeyCode::Char('p') if key.modifiers == KeyModifiers::NONE => {
+                if let Err(e) = app.queue_current() {
+                    app.show_message(format!("Queue failed: {}", e));
+                }
+                false
+            }
+            KeyCode::Char('t') if key.modifiers == KeyModifiers::NONE => {
+                if let Err(e) = app.toggle_template() {
+                    app.show_message(format!("Template failed: {}", e));
+                }
+                false
+            }
             _ => false,
         }
     }
 
+    /// Bulk actions on marked entries; `None` leaves the key to the normal bindings.
+    fn handle_marked(key: KeyEvent, app: &mut App) -> Option<bool> {
+        if key.modifiers != KeyModifiers::NONE && key.modifiers != KeyModifiers::SHIFT {
+            return None;
+        }
+        match key.code {
+            KeyCode::Enter => Some(app.select_marked().unwrap_or_else(|e| {
+                app.show_message(format!("Join failed: {}", e));
+                false
+            })),
+            KeyCode::Char('d') | KeyCode::Char('x') | KeyCode::Delete => {
+                app.delete_mode = DeleteMode::ConfirmingMarked;
+                Some(false)
+            }
+            KeyCode::Char('p') => {
+                if let Err(e) = app.queue_marked() {
+                    app.show_message(format!("Queue failed: {}", e));
+                }
+                Some(false)
+            }
+            KeyCode::Char('t') => {
+                if let Err(e) = app.toggle_marked_templates() {
+                    app.show_message(format!("Template failed: {}", e));
+                }
+                Some(false)
+            }
+            KeyCode::Char('s') => {
+                app.cycle_join_separator();
+                Some(false)
+            }
+            KeyCode::Char('e') => {
+                let exported = std::env::current_dir().map_err(crate::error::CliError::from).and_then(|dir| app.export_marked(&dir));
+                match exported {
+                    Ok((count, path)) => app.show_message(format!("Exported {} entries to {}", count, path.display())),
+                    Err(e) => app.show_message(format!("Export failed: {}", e)),
+                }
+                Some(false)
+            }
+            KeyCode::Esc => {
+                app.clear_marks();
+                Some(false)
+            }
+            _ => None,
+        }
+    }
+
+    fn handle_lock(key: KeyEvent, app: &mut App) -> bool {
+        let Some(lock) = app.lock.as_mut() else {
+            return false;
+        };
+        match lock.handle_key(key) {
+            LockOutcome::Quit => return true,
+            LockOutcome::PassphraseChosen(hash) => {
+                let saved = ConfigManager::new().and_then(|config| {
+                    let mut settings = config.load()?;
+                    settings.tui.lock_passphrase = Some(hash);
+                    config.save(&settings)
+                });
+                if let Err(e) = saved {
+                    app.show_message(format!("Passphrase not saved: {}", e));
+                }
+            }
+            LockOutcome::Locked | LockOutcome::Unlocked => {}
+        }
+        false
+    }
+
+    /// Move the split and save it as `tui.list_percent` for the next session.
+    fn resize_list(app: &mut App, steps: i16) {
+        if !app.resize_list(steps) {
+            return;
+        }
+        let percent = app.list_percent;
+        let saved = ConfigManager::new().and_then(|config| {
+            let mut settings = config.load()?;
+            settings.tui.list_percent = percent;
+            config.save(&settings)
+        });
+        match saved {
+            Ok(()) => app.show_message(format!("List: {}%", percent)),
+            Err(e) => app.show_message(format!("Size not saved: {}", e)),
+        }
+    }
+
+    fn open_db_picker(app: &mut App) {
+        if app.loading {
+            app.show_message("Still loading…");
+            return;
+        }
+        match ConfigManager::new().and_then(|c| c.profiles()) {
+            Ok(profiles) => {
+                let profiles = profiles.into_iter().map(|(name, path)| (name, path.to_string_lossy().to_string())).collect();
+                app.open_db_picker(profiles);
+            }
+            Err(e) => app.show_message(format!("Can't list profiles: {}", e)),
+        }
+    }
+
+    fn handle_db_picker(key: KeyEvent, app: &mut App) -> bool {
+        let Some(picker) = app.db_picker.as_mut() else {
+            return false;
+        };
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => picker.up(),
+            KeyCode::Down | KeyCode::Char('j') => picker.down(),
+            KeyCode::Enter => {
+                let selected = picker.selected().map(|(name, path)| (name.to_string(), path.to_string()));
+                app.db_picker = None;
+                if let Some((name, path)) = selected
+                    && (name != app.profile || path != app.db_path)
+                {
+                    Self::switch_profile(app, &name, &path);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('b') => app.db_picker = None,
+            _ => {}
+        }
+        false
+    }
+
+    /// Switch the TUI and the saved config to another profile.
+    fn switch_profile(app: &mut App, name: &str, path: &str) {
+        let result = app
+            .switch_database(path)
+            .and_then(|_| ConfigManager::new()?.switch_profile(name));
+        match result {
+            Ok(_) => {
+                app.profile = name.to_string();
+                app.show_message(format!("Switched to {} (restart the daemon to capture into it)", name));
+            }
+            Err(e) => app.show_message(format!("Switch failed: {}", e)),
+        }
+    }
+
     fn handle_confirm_quit(key: KeyEvent, app: &mut App) -> bool {
         match key.code {
             KeyCode::Char('y') | KeyCode::Enter => true,
@@ -170,6 +520,24 @@ impl EventHandler {
                 }
             }
 
+            DeleteMode::ConfirmingMarked => {
+                match key.code {
+                    KeyCode::Char('y') | KeyCode::Char('Y') => {
+                        match app.delete_marked() {
+                            Ok(count) => app.show_message(format!("Deleted {} entries ✓", count)),
+                            Err(e) => app.show_message(format!("Delete failed: {}", e)),
+                        }
+                        app.cancel_delete();
+                        false
+                    }
+                    KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                        app.cancel_delete();
+                        false
+                    }
+                    _ => false
+                }
+            }
+
             DeleteMode::ConfirmingBulk { period } => {
                 match key.code {
                     KeyCode::Char('y') | KeyCode::Char('Y') => {
@@ -321,6 +689,7 @@ impl EventHandler {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::kind::Kind;
 
     fn create_test_app() -> App {
         App::new(vec![], "/test/db".to_string(), 80, 24)
@@ -345,12 +714,28 @@ mod tests {
                 content: "entry1".to_string(),
                 created_at: now,
                 last_copied: now,
+                encrypted: false,
+                template: false,
+                language: None,
+                source_app: None,
+                sensitive: false,
+                kind: Kind::Text,
+                title: None,
+                truncated: false,
             },
             crate::db::ClipboardEntry {
                 id: 2,
                 content: "entry2".to_string(),
                 created_at: now,
                 last_copied: now,
+                encrypted: false,
+                template: false,
+                language: None,
+                source_app: None,
+                sensitive: false,
+                kind: Kind::Text,
+                title: None,
+                truncated: false,
             },
         ];
         let mut app = App::new(entries, "/test/db".to_string(), 80, 24);
@@ -359,6 +744,72 @@ mod tests {
         assert_eq!(app.selected_index, 1);
     }
 
+    #[test]
+    fn test_tick_does_not_request_redraw() {
+        let mut app = create_test_app();
+        app.needs_redraw = false;
+        EventHandler::handle(&Event::Tick, &mut app);
+        assert!(!app.needs_redraw);
+
+        EventHandler::handle(&Event::Key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)), &mut app);
+        assert!(app.needs_redraw);
+    }
+
+    #[test]
+    fn test_streamed_entries() {
+        let mut app = create_test_app();
+        app.loading = true;
+        assert!(app.spinner().is_some());
+
+        let now = chrono::Utc::now();
+        let entry = crate::db::ClipboardEntry {
+            id: 7,
+            content: "streamed".to_string(),
+            created_at: now,
+            last_copied: now,
+            encrypted: false,
+            template: false,
+            language: None,
+            source_app: None,
+            sensitive: false,
+            kind: Kind::Text,
+            title: None,
+            truncated: false,
+        };
+        EventHandler::handle(&Event::Entries(0, vec![entry.clone()]), &mut app);
+        EventHandler::handle(&Event::Entries(0, vec![entry.clone()]), &mut app);
+        EventHandler::handle(&Event::EntriesLoaded(0, None), &mut app);
+
+        assert_eq!(app.entries.len(), 1);
+        assert!(!app.loading);
+        assert!(app.spinner().is_none());
+
+        // Rows from a load the list was reloaded over are dropped.
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        crate::db::Database::open(tmp.path()).unwrap();
+        app.db_path = tmp.path().to_string_lossy().to_string();
+        app.all_loaded = false;
+        app.loading = true;
+        app.refresh().unwrap();
+        assert_eq!(app.load_generation, 1);
+        EventHandler::handle(&Event::Entries(0, vec![entry]), &mut app);
+        EventHandler::handle(&Event::EntriesLoaded(0, None), &mut app);
+        assert!(app.entries.is_empty() && !app.loading);
+    }
+
+    #[test]
+    fn test_help_popup() {
+        let mut app = create_test_app();
+        let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        EventHandler::handle(&key(KeyCode::Char('?')), &mut app);
+        assert_eq!(app.help_scroll, Some(0));
+
+        EventHandler::handle(&key(KeyCode::Char('j')), &mut app);
+        EventHandler::handle(&key(KeyCode::Char('q')), &mut app);
+        assert_eq!(app.help_scroll, None);
+        assert!(!app.confirm_quit);
+    }
+
     #[test]
     fn test_filter_mode() {
         let mut app = create_test_app();
@@ -419,4 +870,19 @@ mod tests {
         assert!(!app.is_filtering);
         assert!(app.filter_text.is_empty());
     }
+
+    #[test]
+    fn test_locked_app_only_takes_passphrase() {
+        let mut app = create_test_app();
+        app.lock = Some(crate::tui::lock::Lock::new(None, 300));
+        app.selected_index = 1;
+
+        let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        assert!(!EventHandler::handle(&key(KeyCode::Char('q')), &mut app));
+        EventHandler::handle(&key(KeyCode::Up), &mut app);
+        assert_eq!(app.selected_index, 1);
+        assert_eq!(app.lock.as_ref().unwrap().input, "q");
+
+        assert!(EventHandler::handle(&key(KeyCode::Esc), &mut app));
+    }
 }
@@ -0,0 +1,160 @@
+//! Extra key bindings from `tui.keymap`, layered over the built-in ones.
+
+use crate::error::{CliError, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::BTreeMap;
+
+/// Actions that can be bound to another key, with the built-in key each one keeps.
+pub const ACTIONS: &[(&str, &str)] = &[
+    ("up", "k"),
+    ("down", "j"),
+    ("select", "enter"),
+    ("search", "/"),
+    ("refresh", "r"),
+    ("delete", "d"),
+    ("confirm_delete", "x"),
+    ("bulk_delete", "D"),
+    ("preview_up", "h"),
+    ("preview_down", "l"),
+    ("quit", "q"),
+    ("mark", "space"),
+    ("mark_range", "V"),
+    ("reveal", "v"),
+    ("template", "t"),
+    ("queue", "p"),
+    ("save_snippet", "n"),
+    ("snippets", "S"),
+    ("transform", "T"),
+    ("decode", "B"),
+    ("open", "o"),
+    ("theme", "C"),
+    ("preview", "tab"),
+    ("zoom", "z"),
+    ("view", "f"),
+    ("shrink_list", "<"),
+    ("grow_list", ">"),
+    ("pause", "i"),
+    ("databases", "b"),
+    ("help", "?"),
+];
+
+/// Rewrites a bound key into the built-in key of its action before the list view handles it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<(KeyEvent, KeyEvent)>,
+}
+
+impl Keymap {
+    /// Bindings from `action = "key"` pairs; the error names the offending `tui.keymap` entry.
+    pub fn new(config: &BTreeMap<String, String>) -> Result<Self> {
+        let mut bindings = Vec::new();
+        for (action, spec) in config {
+            let invalid = |reason: String| CliError::ConfigError(format!("tui.keymap.{}: {}", action, reason));
+            let Some((_, builtin)) = ACTIONS.iter().find(|(name, _)| name == action) else {
+                let names: Vec<&str> = ACTIONS.iter().map(|(name, _)| *name).collect();
+                return Err(invalid(format!("unknown action, expected one of {}", names.join(", "))));
+            };
+            let key = parse_key(spec).ok_or_else(|| invalid(format!("can't read key {:?}", spec)))?;
+            bindings.push((key, parse_key(builtin).expect("built-in keys parse")));
+        }
+        Ok(Keymap { bindings })
+    }
+
+    pub fn translate(&self, key: KeyEvent) -> KeyEvent {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| same_key(bound, &key))
+            .map_or(key, |(_, builtin)| *builtin)
+    }
+}
+
+/// Shift is part of an uppercase letter, and terminals disagree on whether to report it.
+fn same_key(a: &KeyEvent, b: &KeyEvent) -> bool {
+    let significant = |key: &KeyEvent| match key.code {
+        KeyCode::Char(_) => key.modifiers - KeyModifiers::SHIFT,
+        _ => key.modifiers,
+    };
+    a.code == b.code && significant(a) == significant(b)
+}
+
+/// `ctrl-x`, `alt-j`, `K`, `space`, `pagedown`, `f2` and the like.
+pub fn parse_key(spec: &str) -> Option<KeyEvent> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = spec;
+    loop {
+        let lower = rest.to_ascii_lowercase();
+        if let Some(prefix) = ["ctrl-", "alt-"].into_iter().find(|p| lower.starts_with(p) && rest.len() > p.len()) {
+            modifiers |= if prefix == "ctrl-" { KeyModifiers::CONTROL } else { KeyModifiers::ALT };
+            rest = &rest[prefix.len()..];
+        } else {
+            break;
+        }
+    }
+
+    let mut chars = rest.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => {
+            if c.is_uppercase() {
+                modifiers |= KeyModifiers::SHIFT;
+            }
+            KeyCode::Char(c)
+        }
+        _ => match rest.to_ascii_lowercase().as_str() {
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "space" => KeyCode::Char(' '),
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            f => KeyCode::F(f.strip_prefix('f')?.parse().ok().filter(|n| (1..=12).contains(n))?),
+        },
+    };
+    Some(KeyEvent::new(code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keymap(pairs: &[(&str, &str)]) -> Result<Keymap> {
+        Keymap::new(&pairs.iter().map(|(a, k)| (a.to_string(), k.to_string())).collect())
+    }
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key("ctrl-x"), Some(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL)));
+        assert_eq!(parse_key("K"), Some(KeyEvent::new(KeyCode::Char('K'), KeyModifiers::SHIFT)));
+        assert_eq!(parse_key("alt-PageDown"), Some(KeyEvent::new(KeyCode::PageDown, KeyModifiers::ALT)));
+        assert_eq!(parse_key("f2"), Some(KeyEvent::new(KeyCode::F(2), KeyModifiers::NONE)));
+        assert_eq!(parse_key("-"), Some(KeyEvent::new(KeyCode::Char('-'), KeyModifiers::NONE)));
+        assert_eq!(parse_key("f13"), None);
+        assert_eq!(parse_key("hyper-x"), None);
+    }
+
+    #[test]
+    fn test_bound_keys_become_builtin_keys() {
+        let keymap = keymap(&[("down", "ctrl-n"), ("bulk_delete", "X")]).unwrap();
+        let ctrl_n = KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL);
+        assert_eq!(keymap.translate(ctrl_n).code, KeyCode::Char('j'));
+        let shift_x = keymap.translate(KeyEvent::new(KeyCode::Char('X'), KeyModifiers::NONE));
+        assert_eq!((shift_x.code, shift_x.modifiers), (KeyCode::Char('D'), KeyModifiers::SHIFT));
+        let n = KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE);
+        assert_eq!(keymap.translate(n), n);
+    }
+
+    #[test]
+    fn test_errors_name_the_entry() {
+        let err = keymap(&[("jump", "g")]).unwrap_err().to_string();
+        assert!(err.contains("tui.keymap.jump: unknown action"), "{}", err);
+        let err = keymap(&[("up", "ctrl-")]).unwrap_err().to_string();
+        assert!(err.contains("tui.keymap.up: can't read key"), "{}", err);
+    }
+}
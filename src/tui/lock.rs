@@ -0,0 +1,277 @@
+//! Lock screen shown before any entries when `secret_encryption` is configured.
+
+use crate::secrets;
+use crate::tui::theme::palette;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Alignment, Margin},
+    prelude::*,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LockOutcome {
+    Locked,
+    Unlocked,
+    /// A new passphrase was chosen and unlocked the TUI; the hash needs saving.
+    PassphraseChosen(String),
+    Quit,
+}
+
+#[derive(Debug)]
+pub struct Lock {
+    pub locked: bool,
+    pub input: String,
+    pub error: Option<String>,
+    /// Hash from `tui.lock_passphrase`; `None` until the user picks one.
+    passphrase: Option<String>,
+    /// First entry of a new passphrase, waiting to be repeated.
+    choosing: Option<String>,
+    idle_timeout: Option<Duration>,
+    last_activity: Instant,
+}
+
+impl Lock {
+    pub fn new(passphrase: Option<String>, lock_after_secs: u64) -> Self {
+        Lock {
+            locked: true,
+            input: String::new(),
+            error: None,
+            passphrase,
+            choosing: None,
+            idle_timeout: (lock_after_secs > 0).then(|| Duration::from_secs(lock_after_secs)),
+            last_activity: Instant::now(),
+        }
+    }
+
+    /// Whether an unlocked TUI still needs ticks to notice it went idle.
+    pub fn watches_idle(&self) -> bool {
+        !self.locked && self.idle_timeout.is_some()
+    }
+
+    pub fn touch(&mut self) {
+        self.last_activity = Instant::now();
+    }
+
+    /// Lock again once the idle timeout has passed. Returns true if it just locked.
+    pub fn check_idle(&mut self) -> bool {
+        let Some(timeout) = self.idle_timeout else {
+            return false;
+        };
+        if self.locked || self.last_activity.elapsed() < timeout {
+            return false;
+        }
+        self.locked = true;
+        self.input.clear();
+        self.error = None;
+        true
+    }
+
+    pub fn prompt(&self) -> &'static str {
+        match (&self.passphrase, &self.choosing) {
+            (Some(_), _) => "Enter passphrase to unlock",
+            (None, None) => "Choose a passphrase for unlocking clippie",
+            (None, Some(_)) => "Repeat the passphrase",
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> LockOutcome {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return LockOutcome::Quit;
+        }
+
+        match key.code {
+            KeyCode::Esc => return LockOutcome::Quit,
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Char(ch) => self.input.push(ch),
+            KeyCode::Enter => return self.submit(),
+            _ => {}
+        }
+        LockOutcome::Locked
+    }
+
+    fn submit(&mut self) -> LockOutcome {
+        let input = std::mem::take(&mut self.input);
+        self.error = None;
+
+        if input.is_empty() {
+            match biometric_unlock() {
+                Some(true) => return self.unlock(),
+                Some(false) => self.error = Some("Touch ID failed".to_string()),
+                None => self.error = Some("Touch ID is not available; type the passphrase".to_string()),
+            }
+            return LockOutcome::Locked;
+        }
+
+        if let Some(stored) = &self.passphrase {
+            if secrets::verify_passphrase(&input, stored) {
+                return self.unlock();
+            }
+            self.error = Some("Wrong passphrase".to_string());
+            return LockOutcome::Locked;
+        }
+
+        match self.choosing.take() {
+            None => self.choosing = Some(input),
+            Some(first) if first == input => match secrets::hash_passphrase(&input) {
+                Ok(hash) => {
+                    self.passphrase = Some(hash.clone());
+                    self.unlock();
+                    return LockOutcome::PassphraseChosen(hash);
+                }
+                Err(e) => self.error = Some(format!("Can't hash passphrase: {}", e)),
+            },
+            Some(_) => self.error = Some("Passphrases don't match; choose again".to_string()),
+        }
+        LockOutcome::Locked
+    }
+
+    fn unlock(&mut self) -> LockOutcome {
+        self.locked = false;
+        self.touch();
+        LockOutcome::Unlocked
+    }
+}
+
+/// Ask for Touch ID. `None` when the machine has no usable biometrics.
+#[cfg(target_os = "macos")]
+fn biometric_unlock() -> Option<bool> {
+    use block2::RcBlock;
+    use objc2::rc::Retained;
+    use objc2::runtime::{AnyClass, AnyObject, Bool};
+    use objc2::{msg_send, msg_send_id};
+    use objc2_foundation::NSString;
+
+    #[link(name = "LocalAuthentication", kind = "framework")]
+    unsafe extern "C" {}
+
+    // LAPolicyDeviceOwnerAuthenticationWithBiometrics
+    const POLICY: isize = 1;
+
+    let class = AnyClass::get("LAContext")?;
+    unsafe {
+        let context: Retained<AnyObject> = msg_send_id![class, new];
+        let available: Bool = msg_send![
+            &*context,
+            canEvaluatePolicy: POLICY,
+            error: std::ptr::null_mut::<*mut AnyObject>()
+        ];
+        if !available.as_bool() {
+            return None;
+        }
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let reply = RcBlock::new(move |success: Bool, _error: *mut AnyObject| {
+            let _ = tx.send(success.as_bool());
+        });
+        let reason = NSString::from_str("unlock clipboard history");
+        let _: () = msg_send![
+            &*context,
+            evaluatePolicy: POLICY,
+            localizedReason: &*reason,
+            reply: &*reply
+        ];
+        rx.recv().ok()
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn biometric_unlock() -> Option<bool> {
+    None
+}
+
+pub fn draw_lock_screen(f: &mut Frame, lock: &Lock) {
+    let area = f.size();
+    let width = 56u16.min(area.width.saturating_sub(2));
+    let height = 9u16.min(area.height);
+    let popup = Rect::new(
+        area.x + (area.width.saturating_sub(width)) / 2,
+        area.y + (area.height.saturating_sub(height)) / 2,
+        width,
+        height,
+    );
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(palette().accent))
+        .title(Span::styled(
+            " Clippie is locked ",
+            Style::default().fg(palette().accent).add_modifier(Modifier::BOLD),
+        ))
+        .title_alignment(Alignment::Center);
+
+    f.render_widget(Clear, popup);
+    f.render_widget(block, popup);
+
+    let mut lines = vec![
+        Line::from(lock.prompt()),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("> ", Style::default().fg(palette().search)),
+            Span::styled("•".repeat(lock.input.chars().count()), Style::default().fg(palette().text_selected)),
+            Span::styled("│", Style::default().fg(palette().search)),
+        ]),
+        Line::from(""),
+    ];
+    match &lock.error {
+        Some(error) => lines.push(Line::from(Span::styled(error.clone(), Style::default().fg(palette().danger)))),
+        None => lines.push(Line::from(Span::styled(
+            "Enter:Unlock (empty for Touch ID)  Esc:Quit",
+            Style::default().fg(palette().hint),
+        ))),
+    }
+
+    let inner = popup.inner(&Margin { vertical: 1, horizontal: 2 });
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_line(lock: &mut Lock, text: &str) -> LockOutcome {
+        for ch in text.chars() {
+            lock.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE));
+        }
+        lock.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_choose_then_unlock() {
+        let mut lock = Lock::new(None, 300);
+        assert_eq!(type_line(&mut lock, "hunter2"), LockOutcome::Locked);
+        assert_eq!(type_line(&mut lock, "hunter3"), LockOutcome::Locked);
+        assert!(lock.error.is_some());
+
+        type_line(&mut lock, "hunter2");
+        let LockOutcome::PassphraseChosen(hash) = type_line(&mut lock, "hunter2") else {
+            panic!("passphrase should have been chosen");
+        };
+        assert!(!lock.locked);
+
+        let mut lock = Lock::new(Some(hash), 300);
+        assert_eq!(type_line(&mut lock, "wrong"), LockOutcome::Locked);
+        assert_eq!(type_line(&mut lock, "hunter2"), LockOutcome::Unlocked);
+    }
+
+    #[test]
+    fn test_relocks_when_idle() {
+        let mut lock = Lock::new(None, 1);
+        lock.locked = false;
+        assert!(lock.watches_idle());
+        assert!(!lock.check_idle());
+
+        lock.last_activity -= Duration::from_secs(2);
+        assert!(lock.check_idle());
+        assert!(lock.locked);
+
+        let mut never = Lock::new(None, 0);
+        never.locked = false;
+        assert!(!never.watches_idle());
+    }
+}
@@ -0,0 +1,231 @@
+//! First-run wizard shown by `clippie` when no database is configured yet.
+
+use crate::tui::theme::palette;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Alignment, Margin},
+    prelude::*,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+/// Retention options offered by the wizard, as `(label, max_age_days)`.
+pub const RETENTION_CHOICES: [(&str, Option<u32>); 5] = [
+    ("Keep everything", None),
+    ("7 days", Some(7)),
+    ("30 days", Some(30)),
+    ("90 days", Some(90)),
+    ("1 year", Some(365)),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step {
+    DbPath,
+    Retention,
+    Daemon,
+}
+
+impl Step {
+    fn number(self) -> usize {
+        match self {
+            Step::DbPath => 1,
+            Step::Retention => 2,
+            Step::Daemon => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Continue,
+    Quit,
+    Done,
+}
+
+#[derive(Debug)]
+pub struct Onboarding {
+    pub step: Step,
+    /// Editable database path, prefilled with the default location.
+    pub db_path: String,
+    default_db_path: String,
+    pub retention_index: usize,
+    pub install_daemon: bool,
+}
+
+impl Onboarding {
+    pub fn new(default_db_path: String) -> Self {
+        Onboarding {
+            step: Step::DbPath,
+            db_path: default_db_path.clone(),
+            default_db_path,
+            retention_index: 0,
+            install_daemon: true,
+        }
+    }
+
+    /// The chosen database path, or `None` to keep the default.
+    pub fn custom_db_path(&self) -> Option<String> {
+        let path = self.db_path.trim();
+        (path != self.default_db_path).then(|| path.to_string())
+    }
+
+    pub fn max_age_days(&self) -> Option<u32> {
+        RETENTION_CHOICES[self.retention_index].1
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Outcome {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Outcome::Quit;
+        }
+
+        match (self.step, key.code) {
+            (Step::DbPath, KeyCode::Esc) => return Outcome::Quit,
+            (_, KeyCode::Esc) => self.back(),
+            (Step::DbPath, KeyCode::Enter) if !self.db_path.trim().is_empty() => self.step = Step::Retention,
+            (Step::DbPath, KeyCode::Backspace) => {
+                self.db_path.pop();
+            }
+            (Step::DbPath, KeyCode::Char(ch)) => self.db_path.push(ch),
+            (Step::Retention, KeyCode::Up | KeyCode::Char('k')) => {
+                self.retention_index = self.retention_index.saturating_sub(1);
+            }
+            (Step::Retention, KeyCode::Down | KeyCode::Char('j')) => {
+                self.retention_index = (self.retention_index + 1).min(RETENTION_CHOICES.len() - 1);
+            }
+            (Step::Retention, KeyCode::Enter) => self.step = Step::Daemon,
+            (Step::Daemon, KeyCode::Char('y')) => self.install_daemon = true,
+            (Step::Daemon, KeyCode::Char('n')) => self.install_daemon = false,
+            (Step::Daemon, KeyCode::Left | KeyCode::Right | KeyCode::Tab) => {
+                self.install_daemon = !self.install_daemon;
+            }
+            (Step::Daemon, KeyCode::Enter) => return Outcome::Done,
+            _ => {}
+        }
+        Outcome::Continue
+    }
+
+    fn back(&mut self) {
+        self.step = match self.step {
+            Step::DbPath | Step::Retention => Step::DbPath,
+            Step::Daemon => Step::Retention,
+        };
+    }
+}
+
+pub fn draw_onboarding(f: &mut Frame, wizard: &Onboarding) {
+    let area = f.size();
+    let width = 64u16.min(area.width.saturating_sub(2));
+    let height = 16u16.min(area.height);
+    let popup = Rect::new(
+        area.x + (area.width.saturating_sub(width)) / 2,
+        area.y + (area.height.saturating_sub(height)) / 2,
+        width,
+        height,
+    );
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(palette().accent))
+        .title(Span::styled(
+            " Welcome to Clippie ",
+            Style::default().fg(palette().accent).add_modifier(Modifier::BOLD),
+        ))
+        .title_alignment(Alignment::Center);
+
+    f.render_widget(Clear, popup);
+    f.render_widget(block, popup);
+
+    let dim = Style::default().fg(palette().dim);
+    let selected = Style::default().fg(palette().text_selected).bg(palette().highlight_bg);
+    let mut lines = vec![
+        Line::from(Span::styled(format!("Step {} of 3", wizard.step.number()), dim)),
+        Line::from(""),
+    ];
+
+    match wizard.step {
+        Step::DbPath => {
+            lines.push(Line::from("Where should clipboard history be stored?"));
+            lines.push(Line::from(""));
+            lines.push(Line::from(vec![
+                Span::styled("> ", Style::default().fg(palette().search)),
+                Span::styled(wizard.db_path.clone(), Style::default().fg(palette().text_selected)),
+                Span::styled("│", Style::default().fg(palette().search)),
+            ]));
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("Enter:Next  Esc:Quit", Style::default().fg(palette().hint))));
+        }
+        Step::Retention => {
+            lines.push(Line::from("How long should entries be kept?"));
+            lines.push(Line::from(""));
+            for (idx, (label, _)) in RETENTION_CHOICES.iter().enumerate() {
+                let is_selected = idx == wizard.retention_index;
+                let prefix = if is_selected { "▶ " } else { "  " };
+                let style = if is_selected { selected } else { Style::default().fg(palette().text) };
+                lines.push(Line::from(Span::styled(format!("{}{}", prefix, label), style)));
+            }
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("j/k:Choose  Enter:Next  Esc:Back", Style::default().fg(palette().hint))));
+        }
+        Step::Daemon => {
+            lines.push(Line::from("Install the daemon so clipboard history is captured at login?"));
+            lines.push(Line::from(""));
+            let option = |label: &'static str, on: bool| {
+                Span::styled(format!(" {} ", label), if on { selected } else { Style::default().fg(palette().text) })
+            };
+            lines.push(Line::from(vec![
+                option("Yes", wizard.install_daemon),
+                Span::raw("  "),
+                option("No", !wizard.install_daemon),
+            ]));
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("y/n:Choose  Enter:Finish  Esc:Back", Style::default().fg(palette().hint))));
+        }
+    }
+
+    let inner = popup.inner(&Margin { vertical: 1, horizontal: 2 });
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_wizard_walks_through_steps() {
+        let mut wizard = Onboarding::new("~/.clippie/clipboard.db".to_string());
+        assert_eq!(wizard.custom_db_path(), None);
+
+        for _ in 0..("clipboard.db".len()) {
+            wizard.handle_key(key(KeyCode::Backspace));
+        }
+        for ch in "work.db".chars() {
+            wizard.handle_key(key(KeyCode::Char(ch)));
+        }
+        assert_eq!(wizard.handle_key(key(KeyCode::Enter)), Outcome::Continue);
+        assert_eq!(wizard.step, Step::Retention);
+
+        wizard.handle_key(key(KeyCode::Down));
+        wizard.handle_key(key(KeyCode::Down));
+        wizard.handle_key(key(KeyCode::Enter));
+        wizard.handle_key(key(KeyCode::Char('n')));
+        assert_eq!(wizard.handle_key(key(KeyCode::Enter)), Outcome::Done);
+
+        assert_eq!(wizard.custom_db_path().as_deref(), Some("~/.clippie/work.db"));
+        assert_eq!(wizard.max_age_days(), Some(30));
+        assert!(!wizard.install_daemon);
+    }
+
+    #[test]
+    fn test_wizard_esc_goes_back_then_quits() {
+        let mut wizard = Onboarding::new("db".to_string());
+        wizard.handle_key(key(KeyCode::Enter));
+        assert_eq!(wizard.handle_key(key(KeyCode::Esc)), Outcome::Continue);
+        assert_eq!(wizard.step, Step::DbPath);
+        assert_eq!(wizard.handle_key(key(KeyCode::Esc)), Outcome::Quit);
+    }
+}
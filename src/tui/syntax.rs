@@ -0,0 +1,200 @@
+//! Syntax highlighting for the languages `language::detect` tags entries with.
+//!
+//! Lines are tokenized one at a time, so a string or comment spanning lines only colors its first line.
+
+use once_cell::sync::Lazy;
+use ratatui::style::Color;
+use regex::Regex;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Token {
+    Keyword,
+    String,
+    Comment,
+    Number,
+    /// Object keys, attributes and shell variables.
+    Key,
+}
+
+impl Token {
+    pub(crate) fn color(self) -> Color {
+        match self {
+            Token::Keyword => Color::Yellow,
+            Token::String => Color::Green,
+            Token::Comment => Color::DarkGray,
+            Token::Number => Color::Cyan,
+            Token::Key => Color::LightBlue,
+        }
+    }
+
+    pub(crate) fn ansi_code(self) -> u8 {
+        match self {
+            Token::Keyword => 33,
+            Token::String => 32,
+            Token::Comment => 90,
+            Token::Number => 36,
+            Token::Key => 94,
+        }
+    }
+}
+
+/// One language's rules as a single alternation; earlier rules win at the same position.
+struct Grammar {
+    re: Regex,
+    tokens: Vec<Token>,
+}
+
+fn grammar(rules: &[(Token, &str)]) -> Grammar {
+    let groups: Vec<String> = rules.iter().map(|(_, pattern)| format!("({})", pattern)).collect();
+    Grammar {
+        re: Regex::new(&groups.join("|")).expect("syntax patterns are valid"),
+        tokens: rules.iter().map(|(token, _)| *token).collect(),
+    }
+}
+
+fn keywords(words: &str) -> String {
+    format!(r"\b(?:{})\b", words.split_whitespace().collect::<Vec<_>>().join("|"))
+}
+
+const DOUBLE_QUOTED: &str = r#""(?:[^"\\]|\\.)*"?"#;
+const SINGLE_QUOTED: &str = r"'(?:[^'\\]|\\.)*'?";
+const NUMBER: &str = r"\b\d[\d_]*(?:\.\d+)?(?:[eE][+-]?\d+)?\b";
+const HASH_COMMENT: &str = r"(?:^|\s)#.*";
+
+static GRAMMARS: Lazy<HashMap<&'static str, Grammar>> = Lazy::new(|| {
+    HashMap::from([
+        ("rust", grammar(&[
+            (Token::Comment, r"//.*"),
+            (Token::String, DOUBLE_QUOTED),
+            (Token::Keyword, &keywords(
+                "as async await break const continue crate dyn else enum extern false fn for if impl in let loop \
+                 match mod move mut pub ref return self Self static struct super trait true type unsafe use where while",
+            )),
+            (Token::Number, NUMBER),
+        ])),
+        ("sql", grammar(&[
+            (Token::Comment, r"--.*"),
+            (Token::String, r"'(?:[^']|'')*'?"),
+            (Token::Keyword, &format!("(?i:{})", keywords(
+                "select from where insert into values update set delete create table alter drop index view join left \
+                 right inner outer full cross on group by order having limit offset as and or not null is in like \
+                 between distinct union all case when then else end primary key references default returning with \
+                 asc desc exists count sum avg min max",
+            ))),
+            (Token::Number, NUMBER),
+        ])),
+        ("shell", grammar(&[
+            (Token::Comment, HASH_COMMENT),
+            (Token::String, &format!("{}|{}", DOUBLE_QUOTED, r"'[^']*'?")),
+            (Token::Key, r"\$\{[^}]*\}|\$\w+"),
+            (Token::Keyword, &keywords(
+                "if then else elif fi for while until do done case esac in function return export local sudo",
+            )),
+        ])),
+        ("json", grammar(&[
+            (Token::Key, &format!(r"{}\s*:", DOUBLE_QUOTED)),
+            (Token::String, DOUBLE_QUOTED),
+            (Token::Keyword, &keywords("true false null")),
+            (Token::Number, r"-?\b\d+(?:\.\d+)?(?:[eE][+-]?\d+)?\b"),
+        ])),
+        ("yaml", grammar(&[
+            (Token::Comment, HASH_COMMENT),
+            (Token::Key, r"^\s*(?:- )?[\w.-]+\s*:"),
+            (Token::String, &format!("{}|{}", DOUBLE_QUOTED, SINGLE_QUOTED)),
+            (Token::Keyword, &keywords("true false null yes no on off")),
+            (Token::Number, NUMBER),
+        ])),
+        ("python", grammar(&[
+            (Token::Comment, r"#.*"),
+            (Token::String, &format!("{}|{}", DOUBLE_QUOTED, SINGLE_QUOTED)),
+            (Token::Keyword, &keywords(
+                "and as assert async await break class continue def del elif else except False finally for from \
+                 global if import in is lambda None nonlocal not or pass raise return self True try while with yield",
+            )),
+            (Token::Number, NUMBER),
+        ])),
+        ("javascript", grammar(&[
+            (Token::Comment, r"//.*"),
+            (Token::String, &format!("{}|{}|{}", DOUBLE_QUOTED, SINGLE_QUOTED, r"`(?:[^`\\]|\\.)*`?")),
+            (Token::Keyword, &keywords(
+                "async await break case catch class const continue default delete do else export extends false \
+                 finally for from function if import in instanceof interface let new null of return super switch \
+                 this throw true try type typeof undefined var void while yield",
+            )),
+            (Token::Number, NUMBER),
+        ])),
+        ("go", grammar(&[
+            (Token::Comment, r"//.*"),
+            (Token::String, &format!("{}|{}", DOUBLE_QUOTED, r"`[^`]*`?")),
+            (Token::Keyword, &keywords(
+                "break case chan const continue default defer else fallthrough false for func go goto if import \
+                 interface map nil package range return select struct switch true type var",
+            )),
+            (Token::Number, NUMBER),
+        ])),
+        ("html", grammar(&[
+            (Token::Comment, r"<!--.*?(?:-->|$)"),
+            (Token::Keyword, r"</?[\w-]+|/?>"),
+            (Token::Key, r"\b[\w-]+="),
+            (Token::String, &format!("{}|{}", DOUBLE_QUOTED, SINGLE_QUOTED)),
+        ])),
+    ])
+});
+
+/// Byte ranges of `line` to color as `language`, leftmost first. Unknown languages get none.
+pub(crate) fn tokens(line: &str, language: &str) -> Vec<(usize, usize, Token)> {
+    let Some(grammar) = GRAMMARS.get(language) else {
+        return vec![];
+    };
+    grammar
+        .re
+        .captures_iter(line)
+        .filter_map(|caps| {
+            let m = caps.get(0)?;
+            let token = grammar.tokens[(1..caps.len()).find(|&i| caps.get(i).is_some())? - 1];
+            let (mut start, mut end) = (m.start(), m.end());
+            // Keys are matched with their `:` or `=` and, in YAML, a list dash; color only the name.
+            if token == Token::Key {
+                let text = &line[start..end];
+                start += text.len() - text.trim_start_matches([' ', '\t', '-']).len();
+                end -= text.len() - text.trim_end_matches([' ', '\t', ':', '=']).len();
+            }
+            (start < end).then_some((start, end, token))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words<'a>(line: &'a str, language: &str) -> Vec<(&'a str, Token)> {
+        tokens(line, language).into_iter().map(|(start, end, token)| (&line[start..end], token)).collect()
+    }
+
+    #[test]
+    fn test_rust_tokens() {
+        assert_eq!(
+            words(r#"let mut n = 42; // "not a string""#, "rust"),
+            [("let", Token::Keyword), ("mut", Token::Keyword), ("42", Token::Number), (r#"// "not a string""#, Token::Comment)]
+        );
+        assert_eq!(words(r#"println!("fn {}", x)"#, "rust"), [(r#""fn {}""#, Token::String)]);
+    }
+
+    #[test]
+    fn test_keys_exclude_punctuation() {
+        assert_eq!(
+            words(r#"  "name": "clippie", "ok": true"#, "json"),
+            [(r#""name""#, Token::Key), (r#""clippie""#, Token::String), (r#""ok""#, Token::Key), ("true", Token::Keyword)]
+        );
+        assert_eq!(words("  - name: build # step", "yaml"), [("name", Token::Key), (" # step", Token::Comment)]);
+        assert_eq!(words("echo $HOME '#x'", "shell"), [("$HOME", Token::Key), ("'#x'", Token::String)]);
+    }
+
+    #[test]
+    fn test_sql_keywords_ignore_case() {
+        assert_eq!(words("Select 1 from t", "sql"), [("Select", Token::Keyword), ("1", Token::Number), ("from", Token::Keyword)]);
+        assert!(tokens("plain text", "klingon").is_empty());
+    }
+}
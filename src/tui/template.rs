@@ -0,0 +1,186 @@
+//! Fill-in form for template entries: each `{placeholder}` is asked for, then substituted.
+
+use crate::tui::theme::palette;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use once_cell::sync::Lazy;
+use ratatui::{
+    layout::{Alignment, Margin},
+    prelude::*,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+use regex::Regex;
+
+/// `{name}` with a word-like name, so braces in code and JSON aren't mistaken for placeholders.
+static PLACEHOLDER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{([A-Za-z_][A-Za-z0-9_ -]{0,39})\}").expect("placeholder pattern is valid"));
+
+/// Distinct placeholder names in order of first appearance.
+pub fn placeholders(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for caps in PLACEHOLDER.captures_iter(content) {
+        let name = caps[1].to_string();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Replace every placeholder with its value; names without a value are left as is.
+pub fn render(content: &str, names: &[String], values: &[String]) -> String {
+    PLACEHOLDER
+        .replace_all(content, |caps: &regex::Captures| {
+            match names.iter().position(|name| *name == caps[1]) {
+                Some(i) if i < values.len() => values[i].clone(),
+                _ => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormOutcome {
+    Editing,
+    Cancel,
+    Done,
+}
+
+#[derive(Debug, Clone)]
+pub struct TemplateForm {
+    /// Full template text.
+    pub content: String,
+    pub names: Vec<String>,
+    /// Values entered so far; the last one is being edited.
+    pub values: Vec<String>,
+}
+
+impl TemplateForm {
+    /// `None` when the content has no placeholders to fill.
+    pub fn new(content: String) -> Option<Self> {
+        let names = placeholders(&content);
+        if names.is_empty() {
+            return None;
+        }
+        Some(TemplateForm { content, names, values: vec![String::new()] })
+    }
+
+    pub fn field(&self) -> usize {
+        self.values.len() - 1
+    }
+
+    pub fn rendered(&self) -> String {
+        render(&self.content, &self.names, &self.values)
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> FormOutcome {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return FormOutcome::Cancel;
+        }
+
+        let field = self.field();
+        match key.code {
+            KeyCode::Esc => return FormOutcome::Cancel,
+            KeyCode::Enter | KeyCode::Tab if field + 1 == self.names.len() => return FormOutcome::Done,
+            KeyCode::Enter | KeyCode::Tab => self.values.push(String::new()),
+            KeyCode::BackTab | KeyCode::Up if field > 0 => {
+                self.values.pop();
+            }
+            KeyCode::Backspace => {
+                self.values[field].pop();
+            }
+            KeyCode::Char(ch) => self.values[field].push(ch),
+            _ => {}
+        }
+        FormOutcome::Editing
+    }
+}
+
+pub fn draw_template_form(f: &mut Frame, area: Rect, form: &TemplateForm) {
+    let width = 64u16.min(area.width.saturating_sub(2));
+    let height = (form.names.len() as u16 + 8).min(area.height);
+    let popup = Rect::new(
+        area.x + (area.width.saturating_sub(width)) / 2,
+        area.y + (area.height.saturating_sub(height)) / 2,
+        width,
+        height,
+    );
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(palette().accent))
+        .title(Span::styled(
+            " Fill Template ",
+            Style::default().fg(palette().accent).add_modifier(Modifier::BOLD),
+        ))
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(palette().popup_bg).fg(palette().popup_fg));
+
+    f.render_widget(Clear, popup);
+    f.render_widget(block, popup);
+
+    let label_width = form.names.iter().map(|name| name.chars().count()).max().unwrap_or(0);
+    let mut lines = Vec::new();
+    for (idx, name) in form.names.iter().enumerate() {
+        let value = form.values.get(idx).map(String::as_str).unwrap_or("");
+        let active = idx == form.field();
+        let label_style = if active {
+            Style::default().fg(palette().accent).bold()
+        } else {
+            Style::default().fg(palette().dim)
+        };
+        let mut spans = vec![
+            Span::styled(format!("{:>width$}: ", name, width = label_width), label_style),
+            Span::styled(value.to_string(), Style::default().fg(palette().text_selected)),
+        ];
+        if active {
+            spans.push(Span::styled("│", Style::default().fg(palette().search)));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    lines.push(Line::from(""));
+    let preview = form.rendered().replace('\n', "↵");
+    lines.push(Line::from(Span::styled(preview, Style::default().fg(palette().muted))));
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Enter:Next/Copy  Shift-Tab:Back  Esc:Cancel",
+        Style::default().fg(palette().hint),
+    )));
+
+    let inner = popup.inner(&Margin { vertical: 1, horizontal: 2 });
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders_skip_code_braces() {
+        let content = "ssh {user}@{host} -p {port} && echo {user} '{\"a\": 1}' {} fn() { x }";
+        assert_eq!(placeholders(content), vec!["user", "host", "port"]);
+        assert!(TemplateForm::new("no placeholders {}".to_string()).is_none());
+    }
+
+    #[test]
+    fn test_form_fills_and_renders() {
+        let mut form = TemplateForm::new("Hi {name}, see {link} — {name}".to_string()).unwrap();
+        let type_text = |form: &mut TemplateForm, text: &str| {
+            for ch in text.chars() {
+                form.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE));
+            }
+        };
+
+        type_text(&mut form, "Ann");
+        assert_eq!(form.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)), FormOutcome::Editing);
+        type_text(&mut form, "x.io");
+        assert_eq!(form.rendered(), "Hi Ann, see x.io — Ann");
+        assert_eq!(form.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)), FormOutcome::Done);
+
+        form.handle_key(KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT));
+        assert_eq!(form.field(), 0);
+        assert_eq!(render("{a} {b}", &["a".to_string()], &["1".to_string()]), "1 {b}");
+    }
+}
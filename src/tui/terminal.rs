@@ -0,0 +1,72 @@
+//! Raw mode and the alternate screen, undone on every way out of the TUI, panics included.
+
+use crossterm::{cursor, execute, terminal};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+
+/// Whether raw mode and the alternate screen are currently engaged.
+static ENGAGED: AtomicBool = AtomicBool::new(false);
+/// The stream the TUI draws on; the ZLE widget keeps stdout for its result.
+static ON_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Restores the terminal when dropped.
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    /// Enter raw mode and the alternate screen on stdout, or on stderr with `on_stderr`.
+    pub fn enter(on_stderr: bool) -> io::Result<Self> {
+        install_panic_hook();
+        ON_STDERR.store(on_stderr, Ordering::SeqCst);
+        terminal::enable_raw_mode()?;
+        ENGAGED.store(true, Ordering::SeqCst);
+        execute!(output(), terminal::EnterAlternateScreen)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = restore();
+    }
+}
+
+/// Leave the alternate screen and raw mode. A no-op once the terminal is restored.
+pub fn restore() -> io::Result<()> {
+    if !ENGAGED.swap(false, Ordering::SeqCst) {
+        return Ok(());
+    }
+    terminal::disable_raw_mode()?;
+    execute!(output(), terminal::LeaveAlternateScreen, cursor::Show)
+}
+
+fn output() -> Box<dyn Write> {
+    if ON_STDERR.load(Ordering::SeqCst) {
+        Box::new(io::stderr())
+    } else {
+        Box::new(io::stdout())
+    }
+}
+
+/// Restore the terminal before the default hook prints the panic message.
+fn install_panic_hook() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = restore();
+            previous(info);
+        }));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_without_guard_is_noop() {
+        assert!(restore().is_ok());
+        assert!(!ENGAGED.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,424 @@
+//! Built-in palettes: dark and light, picked from the terminal background at startup unless
+//! `tui.theme` names one, solarized and high-contrast. `C` cycles through them while the TUI runs.
+
+use crate::config::{Background, Theme};
+use once_cell::sync::OnceCell;
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Every color the TUI draws with, apart from the ANSI accents used for emphasis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    pub text: Color,
+    pub text_selected: Color,
+    pub dim: Color,
+    pub muted: Color,
+    pub hint: Color,
+    pub accent: Color,
+    pub border: Color,
+    pub zebra: Color,
+    pub highlight_bg: Color,
+    pub date_selected: Color,
+    pub version: Color,
+    pub search: Color,
+    pub search_bg: Color,
+    pub message: Color,
+    pub danger: Color,
+    pub popup_fg: Color,
+    pub popup_bg: Color,
+    pub overlay_fg: Color,
+    pub overlay_bg: Color,
+    pub normal_badge: Badge,
+    pub filter_badge: Badge,
+    pub filtered_badge: Badge,
+    pub selected_badge: Badge,
+    pub delete_badge: Badge,
+    pub quit_badge: Badge,
+}
+
+/// Colors of a mode badge in the status bar.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Badge {
+    pub bg: Color,
+    pub fg: Color,
+}
+
+const fn badge(bg: Color, fg: Color) -> Badge {
+    Badge { bg, fg }
+}
+
+pub const DARK: Palette = Palette {
+    text: Color::Rgb(200, 200, 210),
+    text_selected: Color::White,
+    dim: Color::Rgb(100, 100, 110),
+    muted: Color::Rgb(100, 100, 120),
+    hint: Color::Rgb(120, 120, 140),
+    accent: Color::Rgb(180, 180, 255),
+    border: Color::Rgb(60, 60, 80),
+    zebra: Color::Rgb(30, 30, 40),
+    highlight_bg: Color::Rgb(55, 55, 80),
+    date_selected: Color::Rgb(160, 160, 180),
+    version: Color::Rgb(80, 80, 100),
+    search: Color::Rgb(255, 200, 60),
+    search_bg: Color::Rgb(25, 25, 35),
+    message: Color::Rgb(140, 200, 255),
+    danger: Color::Rgb(180, 60, 60),
+    popup_fg: Color::White,
+    popup_bg: Color::Black,
+    overlay_fg: Color::Rgb(50, 50, 60),
+    overlay_bg: Color::Rgb(10, 10, 15),
+    normal_badge: badge(Color::Rgb(60, 60, 120), Color::White),
+    filter_badge: badge(Color::Rgb(180, 160, 40), Color::Black),
+    filtered_badge: badge(Color::Rgb(180, 130, 50), Color::Black),
+    selected_badge: badge(Color::Rgb(60, 120, 90), Color::White),
+    delete_badge: badge(Color::Red, Color::White),
+    quit_badge: badge(Color::Rgb(180, 60, 60), Color::White),
+};
+
+pub const LIGHT: Palette = Palette {
+    text: Color::Rgb(45, 45, 55),
+    text_selected: Color::Black,
+    dim: Color::Rgb(125, 125, 135),
+    muted: Color::Rgb(115, 115, 135),
+    hint: Color::Rgb(100, 100, 120),
+    accent: Color::Rgb(70, 70, 190),
+    border: Color::Rgb(190, 190, 210),
+    zebra: Color::Rgb(240, 240, 246),
+    highlight_bg: Color::Rgb(205, 210, 240),
+    date_selected: Color::Rgb(80, 80, 100),
+    version: Color::Rgb(150, 150, 170),
+    search: Color::Rgb(175, 110, 0),
+    search_bg: Color::Rgb(236, 236, 242),
+    message: Color::Rgb(20, 100, 170),
+    danger: Color::Rgb(180, 50, 50),
+    popup_fg: Color::Black,
+    popup_bg: Color::White,
+    overlay_fg: Color::Rgb(170, 170, 180),
+    overlay_bg: Color::Rgb(225, 225, 232),
+    normal_badge: badge(Color::Rgb(60, 60, 120), Color::White),
+    filter_badge: badge(Color::Rgb(180, 160, 40), Color::Black),
+    filtered_badge: badge(Color::Rgb(180, 130, 50), Color::Black),
+    selected_badge: badge(Color::Rgb(60, 120, 90), Color::White),
+    delete_badge: badge(Color::Red, Color::White),
+    quit_badge: badge(Color::Rgb(180, 50, 50), Color::White),
+};
+
+/// Ethan Schoonover's Solarized, dark variant.
+pub const SOLARIZED: Palette = Palette {
+    text: Color::Rgb(131, 148, 150),
+    text_selected: Color::Rgb(238, 232, 213),
+    dim: Color::Rgb(88, 110, 117),
+    muted: Color::Rgb(88, 110, 117),
+    hint: Color::Rgb(101, 123, 131),
+    accent: Color::Rgb(38, 139, 210),
+    border: Color::Rgb(7, 54, 66),
+    zebra: Color::Rgb(7, 54, 66),
+    highlight_bg: Color::Rgb(23, 78, 92),
+    date_selected: Color::Rgb(147, 161, 161),
+    version: Color::Rgb(88, 110, 117),
+    search: Color::Rgb(181, 137, 0),
+    search_bg: Color::Rgb(7, 54, 66),
+    message: Color::Rgb(42, 161, 152),
+    danger: Color::Rgb(220, 50, 47),
+    popup_fg: Color::Rgb(147, 161, 161),
+    popup_bg: Color::Rgb(0, 43, 54),
+    overlay_fg: Color::Rgb(20, 60, 70),
+    overlay_bg: Color::Rgb(0, 30, 38),
+    normal_badge: badge(Color::Rgb(108, 113, 196), Color::Rgb(253, 246, 227)),
+    filter_badge: badge(Color::Rgb(181, 137, 0), Color::Rgb(0, 43, 54)),
+    filtered_badge: badge(Color::Rgb(203, 75, 22), Color::Rgb(253, 246, 227)),
+    selected_badge: badge(Color::Rgb(133, 153, 0), Color::Rgb(0, 43, 54)),
+    delete_badge: badge(Color::Rgb(220, 50, 47), Color::Rgb(253, 246, 227)),
+    quit_badge: badge(Color::Rgb(220, 50, 47), Color::Rgb(253, 246, 227)),
+};
+
+/// Pure white text on black with saturated accents, for low vision and washed-out displays.
+pub const HIGH_CONTRAST: Palette = Palette {
+    text: Color::White,
+    text_selected: Color::White,
+    dim: Color::Rgb(200, 200, 200),
+    muted: Color::Rgb(200, 200, 200),
+    hint: Color::White,
+    accent: Color::Rgb(0, 255, 255),
+    border: Color::White,
+    zebra: Color::Black,
+    highlight_bg: Color::Rgb(0, 0, 180),
+    date_selected: Color::White,
+    version: Color::White,
+    search: Color::Rgb(255, 255, 0),
+    search_bg: Color::Black,
+    message: Color::Rgb(0, 255, 255),
+    danger: Color::Rgb(255, 80, 80),
+    popup_fg: Color::White,
+    popup_bg: Color::Black,
+    overlay_fg: Color::Rgb(120, 120, 120),
+    overlay_bg: Color::Black,
+    normal_badge: badge(Color::White, Color::Black),
+    filter_badge: badge(Color::Rgb(255, 255, 0), Color::Black),
+    filtered_badge: badge(Color::Rgb(255, 170, 0), Color::Black),
+    selected_badge: badge(Color::Rgb(0, 255, 0), Color::Black),
+    delete_badge: badge(Color::Rgb(255, 0, 0), Color::White),
+    quit_badge: badge(Color::Rgb(255, 0, 0), Color::White),
+};
+
+impl Theme {
+    pub fn palette(self) -> &'static Palette {
+        match self {
+            Theme::Dark => &DARK,
+            Theme::Light => &LIGHT,
+            Theme::Solarized => &SOLARIZED,
+            Theme::HighContrast => &HIGH_CONTRAST,
+        }
+    }
+}
+
+/// How many colors the terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorSupport {
+    TrueColor,
+    /// RGB colors are mapped to the nearest entry of the xterm 256-color palette.
+    Ansi256,
+    /// `NO_COLOR` or `--no-color`: no colors at all, only bold, dim and reverse video.
+    Monochrome,
+}
+
+impl ColorSupport {
+    pub fn detect(no_color_flag: bool) -> Self {
+        Self::from_env(no_color_flag, std::env::var("NO_COLOR").ok(), std::env::var("COLORTERM").ok())
+    }
+
+    /// Any non-empty `NO_COLOR` disables color (see no-color.org); truecolor needs `COLORTERM`.
+    fn from_env(no_color_flag: bool, no_color: Option<String>, colorterm: Option<String>) -> Self {
+        if no_color_flag || no_color.is_some_and(|v| !v.is_empty()) {
+            ColorSupport::Monochrome
+        } else if colorterm.is_some_and(|v| v == "truecolor" || v == "24bit") {
+            ColorSupport::TrueColor
+        } else {
+            ColorSupport::Ansi256
+        }
+    }
+}
+
+/// Index into `Theme::ALL` of the theme being drawn with.
+static THEME: AtomicUsize = AtomicUsize::new(0);
+static COLOR_SUPPORT: OnceCell<ColorSupport> = OnceCell::new();
+
+/// Choose the theme before the first frame: `theme` if set, else the palette for the
+/// background. With `Auto`, ask the terminal.
+pub fn init(background: Background, theme: Option<Theme>, support: ColorSupport) {
+    let theme = theme.unwrap_or_else(|| {
+        let light = match background {
+            Background::Dark => false,
+            Background::Light => true,
+            // The palette doesn't matter without colors, so skip the query.
+            Background::Auto if support == ColorSupport::Monochrome => false,
+            Background::Auto => detect_light_background().unwrap_or(false),
+        };
+        if light { Theme::Light } else { Theme::Dark }
+    });
+    set_theme(theme);
+    let _ = COLOR_SUPPORT.set(support);
+}
+
+/// The theme being drawn with.
+pub fn current() -> Theme {
+    Theme::ALL[THEME.load(Ordering::Relaxed)]
+}
+
+/// Draw with `theme` from the next frame on.
+pub fn set_theme(theme: Theme) {
+    THEME.store(Theme::ALL.iter().position(|&t| t == theme).unwrap_or(0), Ordering::Relaxed);
+}
+
+/// Rewrite a drawn frame for the terminal's color support.
+pub fn adapt(buf: &mut Buffer) {
+    let support = COLOR_SUPPORT.get().copied().unwrap_or(ColorSupport::TrueColor);
+    adapt_buffer(buf, support, palette());
+}
+
+fn adapt_buffer(buf: &mut Buffer, support: ColorSupport, palette: &Palette) {
+    match support {
+        ColorSupport::TrueColor => {}
+        ColorSupport::Ansi256 => {
+            for cell in buf.content.iter_mut() {
+                cell.fg = to_ansi256(cell.fg);
+                cell.bg = to_ansi256(cell.bg);
+            }
+        }
+        ColorSupport::Monochrome => {
+            // Backgrounds that fill an area are dropped; those that mark something
+            // (selected row, search match, mode badge) become reverse video.
+            let fills = [palette.zebra, palette.search_bg, palette.popup_bg, Color::Reset];
+            for cell in buf.content.iter_mut() {
+                if cell.bg == palette.overlay_bg {
+                    cell.modifier.insert(Modifier::DIM);
+                } else if !fills.contains(&cell.bg) {
+                    cell.modifier.insert(Modifier::REVERSED);
+                }
+                cell.fg = Color::Reset;
+                cell.bg = Color::Reset;
+            }
+        }
+    }
+}
+
+/// Nearest xterm 256-color index for an RGB color: the 6×6×6 cube or the gray ramp.
+fn to_ansi256(color: Color) -> Color {
+    let Color::Rgb(r, g, b) = color else {
+        return color;
+    };
+
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let nearest_level = |v: u8| (0..6).min_by_key(|&i| LEVELS[i].abs_diff(v)).unwrap_or(0);
+    let (ri, gi, bi) = (nearest_level(r), nearest_level(g), nearest_level(b));
+    let cube = (LEVELS[ri], LEVELS[gi], LEVELS[bi]);
+
+    let average = (r as u16 + g as u16 + b as u16) / 3;
+    let gray_step = ((average.saturating_sub(8)) / 10).min(23) as u8;
+    let gray_value = 8 + 10 * gray_step;
+
+    let distance = |(cr, cg, cb): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(cr, r) + d(cg, g) + d(cb, b)
+    };
+    if distance((gray_value, gray_value, gray_value)) < distance(cube) {
+        Color::Indexed(232 + gray_step)
+    } else {
+        Color::Indexed(16 + 36 * ri as u8 + 6 * gi as u8 + bi as u8)
+    }
+}
+
+/// The active palette; dark until `init` runs.
+pub fn palette() -> &'static Palette {
+    current().palette()
+}
+
+/// `COLORFGBG` is free to read; the OSC 11 query costs a round trip to the terminal.
+fn detect_light_background() -> Option<bool> {
+    if let Ok(value) = std::env::var("COLORFGBG") {
+        return parse_colorfgbg(&value);
+    }
+    query_background().as_deref().and_then(parse_osc11).map(|rgb| luminance(rgb) > 0.5)
+}
+
+/// `COLORFGBG` is `fg;bg` (sometimes `fg;default;bg`) in ANSI color numbers.
+fn parse_colorfgbg(value: &str) -> Option<bool> {
+    let bg: u8 = value.rsplit(';').next()?.parse().ok()?;
+    Some(bg == 7 || bg >= 9)
+}
+
+/// Parse an OSC 11 reply such as `ESC ]11;rgb:ffff/ffff/ffff BEL` into 0..=1 channels.
+fn parse_osc11(reply: &str) -> Option<(f64, f64, f64)> {
+    let rgb = reply.split("rgb:").nth(1)?;
+    let rgb = rgb.trim_end_matches(['\x07', '\\']).trim_end_matches('\x1b');
+    let mut channels = rgb.split('/').map(|hex| {
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        let max = (1u32 << (4 * hex.len().clamp(1, 4))) - 1;
+        Some(value as f64 / max as f64)
+    });
+    Some((channels.next()??, channels.next()??, channels.next()??))
+}
+
+fn luminance((r, g, b): (f64, f64, f64)) -> f64 {
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+/// Ask the terminal for its background color. Needs raw mode, and must run before the
+/// input thread starts reading, or the reply would show up as key presses.
+#[cfg(unix)]
+fn query_background() -> Option<String> {
+    use std::io::{Read, Write};
+    use std::os::fd::AsRawFd;
+    use std::time::{Duration, Instant};
+
+    const TIMEOUT: Duration = Duration::from_millis(100);
+
+    let mut tty = std::fs::OpenOptions::new().read(true).write(true).open("/dev/tty").ok()?;
+    tty.write_all(b"\x1b]11;?\x07").ok()?;
+    tty.flush().ok()?;
+
+    let deadline = Instant::now() + TIMEOUT;
+    let mut reply = Vec::new();
+    let mut buf = [0u8; 64];
+    while !(reply.ends_with(b"\x07") || reply.ends_with(b"\x1b\\")) {
+        let remaining = deadline.saturating_duration_since(Instant::now()).as_millis() as i32;
+        let mut pollfd = libc::pollfd { fd: tty.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+        if remaining == 0 || unsafe { libc::poll(&mut pollfd, 1, remaining) } <= 0 {
+            return None;
+        }
+        let n = tty.read(&mut buf).ok()?;
+        if n == 0 {
+            return None;
+        }
+        reply.extend_from_slice(&buf[..n]);
+    }
+    String::from_utf8(reply).ok()
+}
+
+#[cfg(not(unix))]
+fn query_background() -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_support_from_env() {
+        let env = |v: &str| Some(v.to_string());
+        assert_eq!(ColorSupport::from_env(false, None, env("truecolor")), ColorSupport::TrueColor);
+        assert_eq!(ColorSupport::from_env(false, None, None), ColorSupport::Ansi256);
+        assert_eq!(ColorSupport::from_env(false, env("1"), env("truecolor")), ColorSupport::Monochrome);
+        assert_eq!(ColorSupport::from_env(false, env(""), None), ColorSupport::Ansi256);
+        assert_eq!(ColorSupport::from_env(true, None, None), ColorSupport::Monochrome);
+    }
+
+    #[test]
+    fn test_to_ansi256() {
+        assert_eq!(to_ansi256(Color::Rgb(255, 0, 0)), Color::Indexed(196));
+        assert_eq!(to_ansi256(Color::Rgb(30, 30, 40)), Color::Indexed(234));
+        assert_eq!(to_ansi256(Color::Cyan), Color::Cyan);
+    }
+
+    #[test]
+    fn test_monochrome_keeps_highlights() {
+        use ratatui::layout::Rect;
+        let mut buf = Buffer::empty(Rect::new(0, 0, 2, 1));
+        buf.get_mut(0, 0).set_bg(DARK.highlight_bg).set_fg(DARK.text);
+        buf.get_mut(1, 0).set_bg(DARK.zebra);
+
+        adapt_buffer(&mut buf, ColorSupport::Monochrome, &DARK);
+        assert!(buf.get(0, 0).modifier.contains(Modifier::REVERSED));
+        assert!(!buf.get(1, 0).modifier.contains(Modifier::REVERSED));
+        assert!(buf.content.iter().all(|c| c.fg == Color::Reset && c.bg == Color::Reset));
+    }
+
+    #[test]
+    fn test_badges_are_readable() {
+        for theme in Theme::ALL {
+            let palette = theme.palette();
+            let badges = [palette.normal_badge, palette.filter_badge, palette.filtered_badge, palette.selected_badge, palette.delete_badge, palette.quit_badge];
+            assert!(badges.iter().all(|badge| badge.fg != badge.bg), "{}", theme.name());
+            assert_ne!(palette.text, palette.highlight_bg, "{}", theme.name());
+        }
+    }
+
+    #[test]
+    fn test_parse_colorfgbg() {
+        assert_eq!(parse_colorfgbg("15;0"), Some(false));
+        assert_eq!(parse_colorfgbg("0;default;15"), Some(true));
+        assert_eq!(parse_colorfgbg("0;7"), Some(true));
+        assert_eq!(parse_colorfgbg("garbage"), None);
+    }
+
+    #[test]
+    fn test_parse_osc11() {
+        let light = parse_osc11("\x1b]11;rgb:ffff/ffff/fefe\x07").unwrap();
+        assert!(luminance(light) > 0.5);
+        let dark = parse_osc11("\x1b]11;rgb:1c/1c/20\x1b\\").unwrap();
+        assert!(luminance(dark) < 0.5);
+        assert!(parse_osc11("\x1b]11;?\x07").is_none());
+    }
+}
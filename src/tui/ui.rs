@@ -2,11 +2,21 @@ use super::app::{App, DeleteMode, DeletePeriod};
 use super::components::{
     dim_background, draw_confirm_quit_popup, draw_entry_list, draw_header, draw_preview,
     draw_search_bar, draw_status_bar,
-    draw_delete_period_popup, draw_delete_confirmation_popup, draw_single_delete_confirmation_popup,
+    draw_db_picker_popup, draw_help_popup, draw_snippet_name_popup, draw_snippet_picker_popup, draw_transform_picker_popup, draw_marked_delete_confirmation_popup, draw_delete_period_popup, draw_delete_confirmation_popup, draw_single_delete_confirmation_popup,
 };
+use super::fuzzy;
+use super::lock::draw_lock_screen;
+use super::template::draw_template_form;
+use super::viewer::draw_viewer;
+use super::theme::{self, palette};
 use ratatui::prelude::*;
 
 pub fn draw(f: &mut Frame, app: &mut App) {
+    draw_widgets(f, app);
+    theme::adapt(f.buffer_mut());
+}
+
+fn draw_widgets(f: &mut Frame, app: &mut App) {
     let size = f.size();
 
     if size.height < 5 {
@@ -15,6 +25,16 @@ pub fn draw(f: &mut Frame, app: &mut App) {
         return;
     }
 
+    if let Some(lock) = app.lock.as_ref().filter(|lock| lock.locked) {
+        draw_lock_screen(f, lock);
+        return;
+    }
+
+    if let Some(viewer) = app.viewer.as_mut() {
+        draw_viewer(f, size, viewer);
+        return;
+    }
+
     let show_search_bar = app.is_filtering || !app.filter_text.is_empty();
 
     let constraints = if show_search_bar {
@@ -41,64 +61,49 @@ pub fn draw(f: &mut Frame, app: &mut App) {
     draw_header(
         f,
         body_area,
-        "History",
+        &app.db_label(),
+        app.incognito.label().as_deref(),
         &app.get_entry_count_info(),
-        app.loading,
+        app.spinner(),
     );
 
     // Inner area inside the border
     let inner = body_area.inner(&ratatui::layout::Margin { vertical: 1, horizontal: 1 });
 
-    let body_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Length(1), Constraint::Percentage(50)])
-        .split(inner);
-
-    let list_area = body_chunks[0];
-    let divider_area = body_chunks[1];
-    let preview_area = body_chunks[2];
+    let [list_area, divider_area, preview_area] = app.body_areas(inner);
 
-    let visible_entries = app.get_visible_entries();
-    draw_entry_list(
-        f,
-        list_area,
-        visible_entries,
-        app.selected_index,
-        app.scroll_offset,
-        &app.filter_text,
-    );
+    // `lang:`, `:kind` and `@app` filters narrow the list but aren't text to highlight.
+    let search_text = fuzzy::parse_query(&app.filter_text).text;
+    if !list_area.is_empty() {
+        let visible_entries: Vec<_> = app.get_visible_entries().into_iter().map(|e| app.shown(e)).collect();
+        draw_entry_list(
+            f,
+            list_area,
+            visible_entries.iter().map(|e| e.as_ref()).collect(),
+            app.selected_index,
+            app.scroll_offset,
+            &search_text,
+            &app.marked,
+        );
+    }
 
-    let divider_lines: Vec<_> = (0..divider_area.height)
-        .map(|_| ratatui::text::Line::from("│"))
-        .collect();
+    // A column of `│` between side-by-side panes, a row of `─` between stacked ones.
+    let divider_lines: Vec<_> = if divider_area.width == 1 {
+        (0..divider_area.height).map(|_| ratatui::text::Line::from("│")).collect()
+    } else {
+        vec![ratatui::text::Line::from("─".repeat(divider_area.width as usize))]
+    };
     let divider = ratatui::widgets::Paragraph::new(divider_lines)
-        .style(Style::default().fg(Color::Rgb(60, 60, 80)));
+        .style(Style::default().fg(palette().border));
     f.render_widget(divider, divider_area);
 
-    let current_entry = app.current_entry();
-    let preview_height = preview_area.height as usize;
-    let (total_lines, first_match) = draw_preview(
-        f,
-        preview_area,
-        current_entry,
-        &app.filter_text,
-        app.preview_scroll,
-    );
-
-    if let Some(match_line) = first_match {
-        if match_line >= app.preview_scroll + preview_height || match_line < app.preview_scroll {
-            app.preview_scroll = match_line.saturating_sub(preview_height / 4);
-        }
-    }
-
-    let max_scroll = total_lines.saturating_sub(preview_height);
-    if app.preview_scroll > max_scroll {
-        app.preview_scroll = max_scroll;
+    if !preview_area.is_empty() {
+        draw_preview_pane(f, app, preview_area, &search_text);
     }
 
     // Draw search bar if active
     if show_search_bar {
-        let match_count = app.filtered_entries().len();
+        let match_count = app.filtered_len();
         draw_search_bar(
             f,
             chunks[1],
@@ -106,25 +111,9 @@ pub fn draw(f: &mut Frame, app: &mut App) {
             app.is_filtering,
             match_count,
         );
-        draw_status_bar(
-            f,
-            chunks[2],
-            app.is_filtering,
-            &app.filter_text,
-            app.confirm_quit,
-            app.is_in_delete_mode(),
-            app.message.as_deref(),
-        );
+        draw_status_bar(f, chunks[2], app);
     } else {
-        draw_status_bar(
-            f,
-            chunks[1],
-            app.is_filtering,
-            &app.filter_text,
-            app.confirm_quit,
-            app.is_in_delete_mode(),
-            app.message.as_deref(),
-        );
+        draw_status_bar(f, chunks[1], app);
     }
 
     // Render overlays on top of everything
@@ -145,7 +134,7 @@ pub fn draw(f: &mut Frame, app: &mut App) {
         DeleteMode::ConfirmingSingle => {
             if let Some(entry) = app.current_entry() {
                 dim_background(f);
-                draw_single_delete_confirmation_popup(f, size, entry);
+                draw_single_delete_confirmation_popup(f, size, &app.shown(entry));
             }
         }
         DeleteMode::ConfirmingAll { confirmation_count } => {
@@ -158,6 +147,69 @@ pub fn draw(f: &mut Frame, app: &mut App) {
                 *confirmation_count
             );
         }
+        DeleteMode::ConfirmingMarked => {
+            dim_background(f);
+            draw_marked_delete_confirmation_popup(f, size, app.marked.len());
+        }
         DeleteMode::None => {}
     }
+
+    if let Some(form) = &app.template_form {
+        dim_background(f);
+        draw_template_form(f, size, form);
+    }
+
+    if let Some(picker) = &app.db_picker {
+        dim_background(f);
+        draw_db_picker_popup(f, size, picker, &app.db_path);
+    }
+
+    if let Some(name) = &app.snippet_name {
+        dim_background(f);
+        draw_snippet_name_popup(f, size, name);
+    }
+
+    if let Some(picker) = &app.snippet_picker {
+        dim_background(f);
+        draw_snippet_picker_popup(f, size, picker);
+    }
+
+    if let Some(picker) = &app.transform_picker {
+        dim_background(f);
+        draw_transform_picker_popup(f, size, picker, app.preview_entry());
+    }
+
+    if let Some(scroll) = app.help_scroll.as_mut() {
+        dim_background(f);
+        draw_help_popup(f, size, scroll);
+    }
+}
+
+/// The highlighted entry in `area`, scrolled to the first search match when it's out of view.
+fn draw_preview_pane(f: &mut Frame, app: &mut App, area: Rect, search_text: &str) {
+    let mut preview_cache = std::mem::take(&mut app.preview_cache);
+    app.load_full_entry();
+    let current_entry = app.preview_entry().map(|e| app.shown(e));
+    let preview_height = area.height as usize;
+    let (total_lines, first_match) = draw_preview(
+        f,
+        area,
+        current_entry.as_deref(),
+        search_text,
+        app.preview_scroll,
+        current_entry.as_ref().is_some_and(|e| app.decoded == Some(e.id)),
+        &mut preview_cache,
+    );
+    app.preview_cache = preview_cache;
+
+    if let Some(match_line) = first_match
+        && (match_line >= app.preview_scroll + preview_height || match_line < app.preview_scroll)
+    {
+        app.preview_scroll = match_line.saturating_sub(preview_height / 4);
+    }
+
+    let max_scroll = total_lines.saturating_sub(preview_height);
+    if app.preview_scroll > max_scroll {
+        app.preview_scroll = max_scroll;
+    }
 }
@@ -0,0 +1,371 @@
+//! Full-screen view of one entry, opened with `f`: numbered lines, optional wrapping and a
+//! search within the entry, for reading long logs.
+
+use crate::tui::components::find_match_ranges;
+use crate::tui::theme::palette;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    prelude::*,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Paragraph},
+};
+use std::ops::Range;
+use unicode_width::UnicodeWidthChar;
+
+/// Columns `h` and `l` move the text sideways by when lines aren't wrapped.
+const SIDEWAYS_STEP: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ViewerOutcome {
+    Viewing,
+    Close,
+}
+
+#[derive(Debug, Clone)]
+pub struct Viewer {
+    /// Shown in the border, e.g. when the entry was copied.
+    pub title: String,
+    lines: Vec<String>,
+    pub wrap: bool,
+    /// Index of the first line on screen.
+    pub top: usize,
+    /// Columns cut off the start of every line when they aren't wrapped.
+    pub left: usize,
+    /// Search text being typed after `/`, while `Some`.
+    pub input: Option<String>,
+    query: String,
+    /// Every match of `query` as line and byte range, in reading order.
+    matches: Vec<(usize, Range<usize>)>,
+    /// The match `n` and `N` last moved to.
+    current: Option<usize>,
+    /// `g` was pressed and the next `g` jumps to the top.
+    pending_g: bool,
+    /// Text columns and rows of the last frame, for paging and finding the last screenful.
+    page: (usize, usize),
+}
+
+impl Viewer {
+    pub fn new(title: String, content: &str) -> Self {
+        let content = content.strip_suffix('\n').unwrap_or(content);
+        let lines = content.split('\n').map(|line| line.trim_end_matches('\r').replace('\t', "    ")).collect();
+        Viewer {
+            title,
+            lines,
+            wrap: true,
+            top: 0,
+            left: 0,
+            input: None,
+            query: String::new(),
+            matches: Vec::new(),
+            current: None,
+            pending_g: false,
+            page: (80, 20),
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> ViewerOutcome {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if let Some(input) = self.input.as_mut() {
+            match key.code {
+                KeyCode::Esc => self.input = None,
+                KeyCode::Enter => {
+                    let query = self.input.take().unwrap_or_default();
+                    self.search(query);
+                }
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) if !ctrl => input.push(c),
+                _ => {}
+            }
+            return ViewerOutcome::Viewing;
+        }
+
+        let pending_g = std::mem::take(&mut self.pending_g);
+        let page = self.page.1.max(1);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return ViewerOutcome::Close,
+            KeyCode::Char('c') if ctrl => return ViewerOutcome::Close,
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_to(self.top + 1),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_to(self.top.saturating_sub(1)),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll_to(self.top + page),
+            KeyCode::PageUp | KeyCode::Char('b') => self.scroll_to(self.top.saturating_sub(page)),
+            KeyCode::Home => self.top = 0,
+            KeyCode::Char('g') if pending_g => self.top = 0,
+            KeyCode::Char('g') => self.pending_g = true,
+            KeyCode::End | KeyCode::Char('G') => self.top = self.last_top(),
+            KeyCode::Left | KeyCode::Char('h') if !self.wrap => self.left = self.left.saturating_sub(SIDEWAYS_STEP),
+            KeyCode::Right | KeyCode::Char('l') if !self.wrap => self.left += SIDEWAYS_STEP,
+            KeyCode::Char('w') => {
+                self.wrap = !self.wrap;
+                self.left = 0;
+                self.top = self.top.min(self.last_top());
+            }
+            KeyCode::Char('/') => self.input = Some(String::new()),
+            KeyCode::Char('n') => self.step_match(true),
+            KeyCode::Char('N') => self.step_match(false),
+            _ => {}
+        }
+        ViewerOutcome::Viewing
+    }
+
+    /// Find `query` in every line and move to its first match from the top of the screen on.
+    fn search(&mut self, query: String) {
+        self.matches = self
+            .lines
+            .iter()
+            .enumerate()
+            .flat_map(|(i, line)| find_match_ranges(line, &query).into_iter().map(move |range| (i, range)))
+            .collect();
+        self.query = query;
+        self.current = None;
+        self.step_match(true);
+    }
+
+    /// Move to the next match, or the previous one, wrapping around at either end.
+    fn step_match(&mut self, forward: bool) {
+        let count = self.matches.len();
+        if count == 0 {
+            return;
+        }
+        let next = match self.current {
+            Some(i) if forward => (i + 1) % count,
+            Some(i) => (i + count - 1) % count,
+            None if forward => self.matches.iter().position(|(line, _)| *line >= self.top).unwrap_or(0),
+            None => self.matches.iter().rposition(|(line, _)| *line < self.top).unwrap_or(count - 1),
+        };
+        self.current = Some(next);
+        let (line, range) = self.matches[next].clone();
+        self.reveal(line, range.start);
+    }
+
+    /// Scroll so `line` is on screen, and the column at byte `start` too when not wrapping.
+    fn reveal(&mut self, line: usize, start: usize) {
+        let (width, height) = self.page;
+        let rows_above: usize = (self.top..=line).map(|i| self.rows(&self.lines[i], width).len()).sum();
+        if line < self.top || rows_above > height {
+            self.scroll_to(line.saturating_sub(height / 4));
+        }
+        if !self.wrap {
+            let column = text_width(&self.lines[line][..start]);
+            if column < self.left || column >= self.left + width {
+                self.left = column.saturating_sub(width / 4);
+            }
+        }
+    }
+
+    fn scroll_to(&mut self, top: usize) {
+        self.top = top.min(self.last_top());
+    }
+
+    /// The top line that fills the screen down to the last line.
+    fn last_top(&self) -> usize {
+        let (width, height) = self.page;
+        let mut rows = 0;
+        for (i, line) in self.lines.iter().enumerate().rev() {
+            rows += self.rows(line, width).len();
+            if rows > height {
+                return (i + 1).min(self.lines.len() - 1);
+            }
+        }
+        0
+    }
+
+    /// Byte ranges of `line` shown on each screen row.
+    fn rows(&self, line: &str, width: usize) -> Vec<Range<usize>> {
+        if !self.wrap {
+            return vec![columns(line, self.left, width)];
+        }
+        let mut rows = Vec::new();
+        let (mut start, mut used) = (0, 0);
+        for (i, c) in line.char_indices() {
+            let w = c.width().unwrap_or(0);
+            if used + w > width && i > start {
+                rows.push(start..i);
+                (start, used) = (i, 0);
+            }
+            used += w;
+        }
+        rows.push(start..line.len());
+        rows
+    }
+
+    fn status(&self) -> Line<'static> {
+        if let Some(input) = &self.input {
+            return Line::from(vec![
+                Span::styled(format!("/{}", input), Style::default().fg(palette().search)),
+                Span::styled("│", Style::default().fg(palette().search)),
+            ]);
+        }
+        let mut status = format!(" line {}/{}", self.top + 1, self.lines.len());
+        if !self.query.is_empty() {
+            match (self.current, self.matches.len()) {
+                (_, 0) => status.push_str(&format!(" · no matches for \"{}\"", self.query)),
+                (Some(i), n) => status.push_str(&format!(" · match {}/{}", i + 1, n)),
+                (None, n) => status.push_str(&format!(" · {} matches", n)),
+            }
+        }
+        Line::from(vec![
+            Span::styled(status, Style::default().fg(palette().dim)),
+            Span::styled(
+                format!("   /:Search  n/N:Match  w:Wrap {}  gg/G:Top/End  q:Close", if self.wrap { "off" } else { "on" }),
+                Style::default().fg(palette().hint),
+            ),
+        ])
+    }
+}
+
+/// Columns `text` takes on screen.
+fn text_width(text: &str) -> usize {
+    text.chars().map(|c| c.width().unwrap_or(0)).sum()
+}
+
+/// Bytes of `line` within the `width` columns starting at column `left`.
+fn columns(line: &str, left: usize, width: usize) -> Range<usize> {
+    let (mut start, mut end, mut column) = (line.len(), line.len(), 0);
+    for (i, c) in line.char_indices() {
+        if column >= left && start == line.len() {
+            start = i;
+        }
+        column += c.width().unwrap_or(0);
+        if column > left + width {
+            end = i;
+            break;
+        }
+    }
+    start..end.max(start)
+}
+
+/// `row` of `line` as spans, with the parts inside `highlights` styled.
+fn row_spans(line: &str, row: Range<usize>, highlights: &[(Range<usize>, Style)]) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut at = row.start;
+    for (range, style) in highlights {
+        let (start, end) = (range.start.max(at), range.end.min(row.end));
+        if start >= end {
+            continue;
+        }
+        if start > at {
+            spans.push(Span::raw(line[at..start].to_string()));
+        }
+        spans.push(Span::styled(line[start..end].to_string(), *style));
+        at = end;
+    }
+    if at < row.end {
+        spans.push(Span::raw(line[at..row.end].to_string()));
+    }
+    spans
+}
+
+pub fn draw_viewer(f: &mut Frame, area: Rect, viewer: &mut Viewer) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(palette().border))
+        .title(Span::styled(format!(" {} ", viewer.title), Style::default().fg(palette().accent).add_modifier(Modifier::BOLD)));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    if inner.height < 2 {
+        return;
+    }
+
+    let number_width = viewer.lines.len().to_string().len();
+    let width = (inner.width as usize).saturating_sub(number_width + 1).max(1);
+    let height = inner.height as usize - 1;
+    viewer.page = (width, height);
+    viewer.top = viewer.top.min(viewer.last_top());
+
+    let found = Style::default().bg(Color::Yellow).fg(Color::Black);
+    let current = viewer.current.map(|i| &viewer.matches[i]);
+    let first = viewer.matches.partition_point(|(line, _)| *line < viewer.top);
+    let mut rows: Vec<Line> = Vec::with_capacity(height);
+    for (i, line) in viewer.lines.iter().enumerate().skip(viewer.top) {
+        let highlights: Vec<(Range<usize>, Style)> = viewer.matches[first..]
+            .iter()
+            .skip_while(|(at, _)| *at < i)
+            .take_while(|(at, _)| *at == i)
+            .map(|m| (m.1.clone(), if Some(m) == current { found.add_modifier(Modifier::REVERSED) } else { found }))
+            .collect();
+        for (n, row) in viewer.rows(line, width).into_iter().enumerate() {
+            if rows.len() == height {
+                break;
+            }
+            let number = if n == 0 { format!("{:>w$} ", i + 1, w = number_width) } else { " ".repeat(number_width + 1) };
+            let mut spans = vec![Span::styled(number, Style::default().fg(palette().dim))];
+            spans.extend(row_spans(line, row, &highlights));
+            rows.push(Line::from(spans));
+        }
+        if rows.len() == height {
+            break;
+        }
+    }
+
+    f.render_widget(Paragraph::new(rows), Rect { height: inner.height - 1, ..inner });
+    f.render_widget(Paragraph::new(viewer.status()), Rect { y: inner.y + inner.height - 1, height: 1, ..inner });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(viewer: &mut Viewer, keys: &str) {
+        for c in keys.chars() {
+            let code = if c == '\n' { KeyCode::Enter } else { KeyCode::Char(c) };
+            viewer.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+        }
+    }
+
+    fn log(lines: usize) -> String {
+        (1..=lines).map(|i| if i % 10 == 0 { format!("{} ERROR failed", i) } else { format!("{} ok", i) }).collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn test_jumps_and_paging() {
+        let mut viewer = Viewer::new("log".to_string(), &log(100));
+        viewer.page = (40, 10);
+        press(&mut viewer, "G");
+        assert_eq!(viewer.top, 90);
+        press(&mut viewer, "j");
+        assert_eq!(viewer.top, 90);
+        press(&mut viewer, "gg");
+        assert_eq!(viewer.top, 0);
+        press(&mut viewer, "g j");
+        assert_eq!(viewer.top, 11);
+        assert_eq!(viewer.handle_key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE)), ViewerOutcome::Close);
+    }
+
+    #[test]
+    fn test_search_moves_between_matches() {
+        let mut viewer = Viewer::new("log".to_string(), &log(100));
+        viewer.page = (40, 10);
+        press(&mut viewer, "/error\n");
+        assert_eq!(viewer.matches.len(), 10);
+        assert_eq!((viewer.current, viewer.top), (Some(0), 0));
+        press(&mut viewer, "nn");
+        assert_eq!((viewer.current, viewer.top), (Some(2), 27));
+        press(&mut viewer, "NNN");
+        assert_eq!((viewer.current, viewer.top), (Some(9), 90));
+        assert!(viewer.status().to_string().contains("match 10/10"));
+
+        press(&mut viewer, "/nope\n");
+        assert!(viewer.status().to_string().contains("no matches for \"nope\""));
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let mut viewer = Viewer::new("wide".to_string(), "abcdefghij\n\tx\r\n日本語\n");
+        assert_eq!(viewer.lines, ["abcdefghij", "    x", "日本語"]);
+        let only = |rows: Vec<Range<usize>>| {
+            assert_eq!(rows.len(), 1);
+            rows[0].clone()
+        };
+        assert_eq!(viewer.rows("abcdefghij", 4), [0..4, 4..8, 8..10]);
+        assert_eq!(viewer.rows("日本語", 4), [0..6, 6..9]);
+        assert_eq!(only(viewer.rows("", 4)), 0..0);
+
+        press(&mut viewer, "wll");
+        assert_eq!(only(viewer.rows("abcdefghijklmnopqrstu", 4)), 16..20);
+        assert_eq!(only(viewer.rows("short", 4)), 5..5);
+        assert_eq!(row_spans("abcdef", 1..5, &[(2..4, Style::default().bold())]).len(), 3);
+    }
+}
@@ -0,0 +1,337 @@
+//! URL cleanup rules applied to captures or to entries as they're restored to the clipboard.
+
+use crate::config::{UrlCleanupStage, UrlRule};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::process::Command;
+
+static URL: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?i)https?://[^\s<>"'`]+"#).expect("URL pattern is valid"));
+static TITLE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)<title[^>]*>(.*?)</title>").expect("title pattern is valid"));
+
+/// Query parameters the "clean URL" transform removes: campaign tags and ad click ids.
+const TRACKING_PARAMS: [&str; 12] = [
+    "utm_*", "fbclid", "gclid", "dclid", "gbraid", "wbraid", "msclkid", "yclid", "mc_cid", "mc_eid", "igshid", "_hs*",
+];
+/// Longest page title kept; the list shows one line anyway.
+const MAX_TITLE_CHARS: usize = 200;
+
+/// Where in an entry's life cleanup runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+    Capture,
+    Copy,
+}
+
+impl UrlCleanupStage {
+    fn includes(self, stage: Stage) -> bool {
+        matches!(
+            (self, stage),
+            (UrlCleanupStage::Both, _)
+                | (UrlCleanupStage::Capture, Stage::Capture)
+                | (UrlCleanupStage::Copy, Stage::Copy)
+        )
+    }
+}
+
+/// Rewrite every URL in `text` with the rules that apply at `stage`.
+pub fn clean(text: &str, rules: &[UrlRule], stage: Stage) -> String {
+    let rules: Vec<&UrlRule> = rules.iter().filter(|rule| rule.when.includes(stage)).collect();
+    if rules.is_empty() || !text.contains("://") {
+        return text.to_string();
+    }
+
+    URL.replace_all(text, |caps: &regex::Captures| {
+        // Punctuation ending a sentence isn't part of the URL.
+        let url = caps[0].trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '}']);
+        let trailing = &caps[0][url.len()..];
+        format!("{}{}", clean_url(url, &rules), trailing)
+    })
+    .into_owned()
+}
+
+/// Every URL in `text` without tracking parameters, whatever `url_rules` says.
+pub fn strip_tracking(text: &str) -> String {
+    let rule = UrlRule {
+        hosts: vec![],
+        strip_params: TRACKING_PARAMS.iter().map(|param| param.to_string()).collect(),
+        normalize: false,
+        expand: false,
+        when: UrlCleanupStage::Both,
+    };
+    clean(text, &[rule], Stage::Copy)
+}
+
+/// Open `url` in the default browser, adding `https://` to a bare `www.` address.
+pub fn open_in_browser(url: &str) -> std::io::Result<()> {
+    let url = url.trim();
+    let url = if url.contains("://") { url.to_string() } else { format!("https://{}", url) };
+    let opener = if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+    Command::new(opener)
+        .arg(url)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map(|mut child| {
+            // Reaped off-thread so a long-running opener doesn't hold up the caller or linger as a zombie.
+            std::thread::spawn(move || child.wait());
+        })
+}
+
+/// Title of the page at `url`, fetched with curl. `None` when it can't be fetched or has none.
+pub fn fetch_title(url: &str) -> Option<String> {
+    let output = Command::new("curl")
+        .args(["--silent", "--location", "--max-time", "5", "--max-filesize", "2000000", "--user-agent", "clippie"])
+        // Text like `file://…` or `dict://…` is classified as a URL too; only fetch web pages.
+        .args(["--proto", "=http,https", "--proto-redir", "=http,https"])
+        .arg(url)
+        .output()
+        .ok()?;
+    title_of(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// The `<title>` of an HTML page, entities decoded and whitespace collapsed.
+fn title_of(html: &str) -> Option<String> {
+    let raw = TITLE.captures(html)?.get(1)?.as_str();
+    let title = decode_entities(&raw.split_whitespace().collect::<Vec<_>>().join(" "));
+    let title = match title.char_indices().nth(MAX_TITLE_CHARS) {
+        Some((end, _)) => format!("{}…", &title[..end]),
+        None => title,
+    };
+    (!title.is_empty()).then_some(title)
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest.find(';').filter(|&end| end <= 10).map(|end| &rest[1..end]);
+        let character = entity.and_then(|name| match name {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                None => name.strip_prefix('#').and_then(|n| n.parse().ok()).and_then(char::from_u32),
+            },
+        });
+        match (character, entity) {
+            (Some(c), Some(name)) => {
+                decoded.push(c);
+                rest = &rest[name.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn clean_url(url: &str, rules: &[&UrlRule]) -> String {
+    let Some(mut parts) = UrlParts::parse(url) else {
+        return url.to_string();
+    };
+
+    for rule in rules {
+        if rule.expand
+            && rule.matches_host(&parts.host)
+            && let Some(expanded) = expand(&parts.to_string())
+            && let Some(expanded) = UrlParts::parse(&expanded)
+        {
+            parts = expanded;
+        }
+    }
+
+    // Expansion may have moved to another host, so the remaining rules match against that.
+    for rule in rules {
+        if !rule.matches_host(&parts.host) {
+            continue;
+        }
+        if !rule.strip_params.is_empty() {
+            parts.query.retain(|pair| {
+                let key = pair.split_once('=').map_or(pair.as_str(), |(key, _)| key);
+                !rule.strip_params.iter().any(|pattern| param_matches(pattern, key))
+            });
+        }
+        if rule.normalize {
+            parts.normalize();
+        }
+    }
+    parts.to_string()
+}
+
+impl UrlRule {
+    fn matches_host(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        self.hosts.is_empty()
+            || self.hosts.iter().any(|pattern| {
+                let pattern = pattern.to_ascii_lowercase();
+                host == pattern || host.ends_with(&format!(".{}", pattern))
+            })
+    }
+}
+
+/// `utm_*` matches every key starting with `utm_`; other patterns match the whole key.
+fn param_matches(pattern: &str, key: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => key.to_ascii_lowercase().starts_with(&prefix.to_ascii_lowercase()),
+        None => key.eq_ignore_ascii_case(pattern),
+    }
+}
+
+/// Follow one redirect of a shortened link without downloading the target.
+fn expand(url: &str) -> Option<String> {
+    let output = Command::new("curl")
+        .args(["--silent", "--head", "--max-time", "3", "--output", "/dev/null", "--write-out", "%{redirect_url}"])
+        .args(["--proto", "=http,https"])
+        .arg(url)
+        .output()
+        .ok()?;
+    let target = String::from_utf8(output.stdout).ok()?;
+    let target = target.trim();
+    (output.status.success() && target.starts_with("http")).then(|| target.to_string())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct UrlParts {
+    scheme: String,
+    authority: String,
+    host: String,
+    path: String,
+    query: Vec<String>,
+    fragment: Option<String>,
+}
+
+impl UrlParts {
+    fn parse(url: &str) -> Option<Self> {
+        let (scheme, rest) = url.split_once("://")?;
+        let (rest, fragment) = match rest.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(fragment.to_string())),
+            None => (rest, None),
+        };
+        let (rest, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, ""),
+        };
+        let host_port = authority.rsplit('@').next().unwrap_or(authority);
+        let host = host_port.split(':').next().unwrap_or(host_port);
+        Some(UrlParts {
+            scheme: scheme.to_string(),
+            authority: authority.to_string(),
+            host: host.to_string(),
+            path: path.to_string(),
+            query: query.split('&').filter(|pair| !pair.is_empty()).map(str::to_string).collect(),
+            fragment,
+        })
+    }
+
+    /// Lowercase scheme and host, drop default ports and empty fragments.
+    fn normalize(&mut self) {
+        self.scheme = self.scheme.to_ascii_lowercase();
+        let default_port = if self.scheme == "https" { ":443" } else { ":80" };
+        let authority = self.authority.strip_suffix(default_port).unwrap_or(&self.authority);
+        self.authority = match authority.rsplit_once('@') {
+            Some((user, host)) => format!("{}@{}", user, host.to_ascii_lowercase()),
+            None => authority.to_ascii_lowercase(),
+        };
+        self.host = self.host.to_ascii_lowercase();
+        if self.fragment.as_deref() == Some("") {
+            self.fragment = None;
+        }
+    }
+}
+
+impl std::fmt::Display for UrlParts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}://{}{}", self.scheme, self.authority, self.path)?;
+        if !self.query.is_empty() {
+            write!(f, "?{}", self.query.join("&"))?;
+        }
+        if let Some(fragment) = &self.fragment {
+            write!(f, "#{}", fragment)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(hosts: &[&str], strip: &[&str], when: UrlCleanupStage) -> UrlRule {
+        UrlRule {
+            hosts: hosts.iter().map(|h| h.to_string()).collect(),
+            strip_params: strip.iter().map(|p| p.to_string()).collect(),
+            normalize: false,
+            expand: false,
+            when,
+        }
+    }
+
+    #[test]
+    fn test_strips_tracking_params() {
+        let rules = [rule(&[], &["utm_*", "fbclid", "gclid"], UrlCleanupStage::Capture)];
+        assert_eq!(
+            clean("see https://example.com/a?id=3&utm_source=x&UTM_medium=y&fbclid=z#top.", &rules, Stage::Capture),
+            "see https://example.com/a?id=3#top."
+        );
+        assert_eq!(clean("https://example.com/?gclid=1", &rules, Stage::Capture), "https://example.com/");
+        assert_eq!(clean("https://example.com/?gclid=1", &rules, Stage::Copy), "https://example.com/?gclid=1");
+        assert_eq!(clean("no links here", &rules, Stage::Capture), "no links here");
+    }
+
+    #[test]
+    fn test_rules_are_scoped_to_hosts() {
+        let rules = [rule(&["youtube.com"], &["si"], UrlCleanupStage::Both)];
+        assert_eq!(
+            clean("https://www.youtube.com/watch?v=abc&si=123", &rules, Stage::Copy),
+            "https://www.youtube.com/watch?v=abc"
+        );
+        assert_eq!(clean("https://example.com/?si=123", &rules, Stage::Copy), "https://example.com/?si=123");
+    }
+
+    #[test]
+    fn test_strip_tracking() {
+        assert_eq!(
+            strip_tracking("https://shop.example/p?id=1&utm_campaign=x&gclid=2&_hsenc=3 and https://a.example/?msclkid=4"),
+            "https://shop.example/p?id=1 and https://a.example/"
+        );
+    }
+
+    #[test]
+    fn test_title_of() {
+        assert_eq!(
+            title_of("<html><head><TITLE lang=\"en\">\n  Rust &amp; &#x1F980;\n  &lt;Docs&gt; </TITLE></head>").as_deref(),
+            Some("Rust & 🦀 <Docs>")
+        );
+        assert_eq!(title_of("<title>AT&T &bogus; 5 &lt 6</title>").as_deref(), Some("AT&T &bogus; 5 &lt 6"));
+        assert_eq!(title_of("<title> </title>"), None);
+        assert_eq!(title_of("no title here"), None);
+        assert_eq!(title_of(&format!("<title>{}</title>", "x".repeat(300))).unwrap().chars().count(), MAX_TITLE_CHARS + 1);
+    }
+
+    #[test]
+    fn test_fetch_title_is_web_only() {
+        let page = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(page.path(), "<title>Local</title>").unwrap();
+        assert_eq!(fetch_title(&format!("file://{}", page.path().display())), None);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let rules = [UrlRule { normalize: true, ..rule(&[], &[], UrlCleanupStage::Capture) }];
+        assert_eq!(
+            clean("HTTPS://User@Example.COM:443/Path?q=1#", &rules, Stage::Capture),
+            "https://User@example.com/Path?q=1"
+        );
+    }
+}